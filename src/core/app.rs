@@ -1,15 +1,93 @@
 use anyhow::Result;
 use rusqlite::Connection;
-use std::path::PathBuf;
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
 use std::time::{Duration, Instant};
 
-use super::mode::AppMode;
-use crate::core::apps::DesktopApp;
-use crate::core::clipboard::{self, ClipboardEntry, ClipboardMonitor};
-use crate::core::fs::{self, DirEntry};
-use crate::core::history::{self as history_fs, AppLaunchHistory, CommandHistory, RecentAccess};
-use crate::core::search::{SearchResult, SearchResultKind};
+use super::mode::{self, AppMode, ModeEvent};
+use crate::core::apps::{self, ApplicationWatcher, DesktopApp};
+use crate::core::clipboard::{self, ClipboardEntry, ClipboardMonitor, ClipboardSort};
+use crate::core::event::AppEvent;
+use crate::core::fs::{self, DirEntry, FileOpKind, FileOperation, SortDirection, SortOrder};
+use crate::core::history::{
+    self as history_fs, AppLaunchHistory, CommandHistory, RecentAccess, RecentOrder,
+};
+use crate::core::input;
+use crate::core::search::{
+    self, LruCache, SearchResult, SearchResultKind, SearchSortMode, SearchState,
+};
 use crate::core::search_config::SearchConfig;
+use crate::core::settings::{self, LauncherSettings, LauncherView};
+use crate::core::ssh::SshHostCache;
+
+/// How long informational status messages stay visible; errors stay until replaced
+pub const STATUS_TIMEOUT: Duration = Duration::from_secs(3);
+/// How long a toast stays on screen
+pub const TOAST_DURATION: Duration = Duration::from_secs(3);
+/// Toasts fade out over the end of their lifetime
+pub const TOAST_FADE: Duration = Duration::from_millis(500);
+/// Older toasts are dropped once this many are stacked
+const MAX_TOASTS: usize = 5;
+/// File operations remembered for Ctrl+Z; the oldest is forgotten past this
+const MAX_UNDO: usize = 20;
+
+/// What running a search result did, so the UI can follow up
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExecutedResultKind {
+    Opened(PathBuf),
+    /// A directory result was opened in the Files view's directory
+    NavigatedTo(PathBuf),
+    Launched(String),
+    /// A `:` command result, left for the caller to run
+    CommandRun(String),
+    /// An `=` calculation whose result was copied to the clipboard
+    Copied(String),
+}
+
+impl ExecutedResultKind {
+    /// What executing a result of this kind does, without doing it
+    pub fn of(kind: &SearchResultKind) -> Self {
+        match kind {
+            SearchResultKind::File(path) | SearchResultKind::RecentFile(path) if path.is_dir() => {
+                ExecutedResultKind::NavigatedTo(path.clone())
+            }
+            SearchResultKind::File(path)
+            | SearchResultKind::RecentFile(path)
+            | SearchResultKind::GrepResult { path, .. } => ExecutedResultKind::Opened(path.clone()),
+            SearchResultKind::Application(app) => ExecutedResultKind::Launched(app.name.clone()),
+            SearchResultKind::SshHost { alias, .. } => ExecutedResultKind::Launched(alias.clone()),
+            SearchResultKind::Command(cmd) => ExecutedResultKind::CommandRun(cmd.clone()),
+            SearchResultKind::Calculation(_) => ExecutedResultKind::Copied(kind.copy_text()),
+        }
+    }
+}
+
+/// How to reverse a file operation, newest last in `AppState::undo_stack`
+#[derive(Debug, Clone, PartialEq)]
+pub enum UndoAction {
+    /// `from` was moved to `to`; undone by moving it back
+    UndoRename { from: PathBuf, to: PathBuf },
+    /// Restored from the trash to `paths`, all at once
+    UndoMoveToTrash { paths: Vec<PathBuf> },
+    /// A copy was made at `dst`; undone by deleting it
+    UndoCopy { dst: PathBuf },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ToastKind {
+    Info,
+    Success,
+    Error,
+}
+
+/// A short notification stacked in the corner of the window until `expires`
+#[derive(Debug, Clone)]
+pub struct Toast {
+    pub message: String,
+    pub expires: Instant,
+    pub kind: ToastKind,
+}
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum FocusedPane {
@@ -18,26 +96,102 @@ pub enum FocusedPane {
     Preview,
 }
 
+/// Whether the window should be shown, next to what the viewport last reported.
+/// `ViewportCommand::Visible` is asynchronous, so the two can briefly disagree.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WindowVisibilityState {
+    pub intended: bool,
+    pub actual: bool,
+}
+
+impl WindowVisibilityState {
+    pub fn is_settled(&self) -> bool {
+        self.intended == self.actual
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum PreviewState {
     None,
+    Loading,
     Text(String),
     Summary(String),
 }
 
-pub struct App {
-    // Core State
+/// A line printed by a running command, by the stream it came from
+#[derive(Debug, Clone, PartialEq)]
+pub enum OutputLine {
+    Out(String),
+    Err(String),
+}
+
+/// The last `max_lines` lines of a command's output, and how many it printed in total
+#[derive(Debug, Default)]
+pub struct OutputTail {
+    lines: VecDeque<String>,
+    total: usize,
+    max_lines: usize,
+}
+
+impl OutputTail {
+    pub fn new(max_lines: usize) -> Self {
+        OutputTail {
+            lines: VecDeque::new(),
+            total: 0,
+            max_lines,
+        }
+    }
+
+    pub fn push(&mut self, line: String) {
+        if self.lines.len() >= self.max_lines {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(line);
+        self.total += 1;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total == 0
+    }
+
+    /// The kept lines, noting how many earlier ones were dropped
+    pub fn render(&self) -> String {
+        let kept = self
+            .lines
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join("\n");
+        if self.total <= self.lines.len() {
+            return kept;
+        }
+        format!(
+            "… ({} earlier lines not shown, {} lines total)\n{}",
+            self.total - self.lines.len(),
+            self.total,
+            kept
+        )
+    }
+}
+
+/// Data Filecast works on: the filesystem, database, clipboard and history
+pub struct AppState {
+    // Files
     pub current_path: PathBuf,
     pub file_list: Vec<DirEntry>,
-    pub selected_index: usize,
-    pub mode: AppMode,
-    pub should_quit: bool,
+    /// Entries of the current directory that couldn't be read
+    pub dir_errors: Vec<String>,
+    /// Bumped whenever `file_list` or the filtered list is replaced, so views can cache
+    /// figures derived from them
+    pub file_list_version: u64,
     pub tick_rate: Duration,
-    pub status_message: String,
-
-    // UI State
-    pub focused_pane: FocusedPane,
-    pub history_selected_index: usize,
+    pub show_hidden: bool,
+    pub sort_order: SortOrder,
+    pub sort_direction: SortDirection,
+    pub yanked_files: Vec<PathBuf>,
+    pub file_operation: Option<FileOperation>,
+    pub pending_file_operations: VecDeque<(FileOpKind, PathBuf, PathBuf)>,
+    pub undo_stack: VecDeque<UndoAction>,
 
     // History State (Temporary Navigation)
     pub history: Vec<PathBuf>,
@@ -45,25 +199,28 @@ pub struct App {
 
     // Persistent State (Recent Access)
     pub recent_files: Vec<RecentAccess>,
+    pub most_opened_files: Vec<RecentAccess>,
+    pub recent_file_exists_cache: HashMap<PathBuf, bool>,
     pub db_connection: Connection,
-
-    // Feature State
-    pub preview_state: PreviewState,
-    pub search_query: String,
-    pub command_input: String,
-    pub show_hidden: bool,
-    pub filtered_file_list: Vec<DirEntry>,
-    pub is_filtering: bool,
+    pub db_path: PathBuf,
 
     // Launcher State
     pub applications: Vec<DesktopApp>,
-    pub search_results: Vec<SearchResult>,
-    pub window_visible: bool,
+    application_watcher: Option<ApplicationWatcher>,
+    pub search_state: SearchState,
+    pub search_sort_mode: SearchSortMode,
+    /// Fuzzy, grep and find searches match case exactly; toggled with Alt+C
+    pub search_case_sensitive: bool,
 
     // Clipboard State
     pub clipboard_history: Vec<ClipboardEntry>,
+    pub clipboard_sort: ClipboardSort,
     pub clipboard_monitor: ClipboardMonitor,
     pub last_clipboard_cleanup: Instant,
+    pub clipboard_max_entry_size: usize,
+    pub last_monitor_activity: Instant,
+    last_monitor_check: Instant,
+    last_polled_clipboard: Option<String>,
 
     // Command History
     pub command_history: Vec<CommandHistory>,
@@ -73,89 +230,219 @@ pub struct App {
 
     // Search Config
     pub search_config: SearchConfig,
+    pub active_search_config: SearchConfig,
+    pub ssh_hosts: SshHostCache,
+    /// Fuzzy search results by query and directory, so Backspace doesn't re-score
+    pub search_cache: LruCache<(String, PathBuf), Vec<SearchResult>>,
+}
+
+/// What the user is looking at: selections, queries, filters, preview and window state
+pub struct UiState {
+    pub selected_index: usize,
+    pub mode: AppMode,
+    pub should_quit: bool,
+    pub status_message: String,
+    pub status_message_expires: Option<Instant>,
+    pub status_is_error: bool,
+    pub toasts: VecDeque<Toast>,
+    pub focused_pane: FocusedPane,
+    pub history_selected_index: usize,
+    pub directory_selection_memory: HashMap<PathBuf, usize>,
+    pub show_most_opened: bool,
+    /// A directory is being read, so the file list is stale. Reads are still synchronous,
+    /// so this is only ever observed once they move off the UI thread.
+    pub is_loading_directory: bool,
+
+    // Preview
+    pub preview_state: PreviewState,
+    pub preview_rx: Option<mpsc::Receiver<PreviewState>>,
+
+    // Running command
+    pub command_output_rx: Option<mpsc::Receiver<OutputLine>>,
+    /// Tails of the running command's stdout and stderr
+    pub live_command_output: OutputTail,
+    pub live_command_errors: OutputTail,
+    /// Bumped whenever the live output changes, so views only re-render it then
+    pub command_output_version: u64,
+    /// Whether the last command exited successfully, once it has finished
+    pub command_succeeded: Option<bool>,
+    command_status: Option<std::thread::JoinHandle<bool>>,
+
+    // Input and filters
+    pub search_query: String,
+    pub filtered_file_list: Vec<DirEntry>,
+    pub is_filtering: bool,
+    pub visual_start: usize,
+    pub open_with_files: Vec<PathBuf>,
+    pub clipboard_query: String,
+
+    // Window
+    pub window_visible: bool,
+    pub window_visibility_state: WindowVisibilityState,
+    /// View shown before the current one, which Escape returns to
+    pub previous_view: Option<LauncherView>,
+}
+
+pub struct App {
+    pub state: AppState,
+    pub ui_state: UiState,
 }
 
 impl App {
-    pub fn new(db_conn: Connection) -> Result<Self> {
-        use crate::core::apps;
+    #[tracing::instrument]
+    pub fn new(db_path: &Path) -> Result<Self> {
+        let (state, recovered_from) = AppState::open(db_path, std::env::current_dir()?)?;
+        let mut ui_state = UiState::new();
+        if let Some(backup) = recovered_from {
+            ui_state.set_error(format!(
+                "Warning: history database was corrupt and has been reset (old copy: {})",
+                backup.display()
+            ));
+        }
+        Ok(App { state, ui_state })
+    }
+
+    /// A search, file operation, preview or command is still reporting back
+    pub fn has_background_work(&self) -> bool {
+        self.state.search_state.is_streaming()
+            || self.state.file_operation.is_some()
+            || self.ui_state.preview_rx.is_some()
+            || self.ui_state.command_output_rx.is_some()
+    }
+}
 
-        // Initialize clipboard table
-        clipboard::init_clipboard_table(&db_conn)?;
+impl AppState {
+    /// Open the history database at `db_path` and list `start_dir`, watching for
+    /// installed applications. Also returns where a corrupt database was moved aside.
+    pub fn open(db_path: &Path, start_dir: PathBuf) -> Result<(Self, Option<PathBuf>)> {
+        let (db_conn, recovered_from) = {
+            let _span = tracing::info_span!("db_init").entered();
+            history_fs::open_with_recovery(db_path)?
+        };
 
         // Cleanup expired clipboard entries on startup
         let _ = clipboard::cleanup_expired(&db_conn);
 
-        let initial_path = std::env::current_dir()?;
-        let initial_list = fs::read_directory(&initial_path, false)?;
-        let recent_files = history_fs::get_recent_files(&db_conn, 10).unwrap_or_default();
-        let applications = apps::discover_applications();
-        let clipboard_history = clipboard::get_history(&db_conn, 50).unwrap_or_default();
-        let clipboard_monitor = ClipboardMonitor::start();
-        let command_history = history_fs::get_command_history(&db_conn, 20).unwrap_or_default();
-        let app_launch_history = history_fs::get_app_launch_history(&db_conn, 20).unwrap_or_default();
-        let search_config = SearchConfig::load();
-
-        Ok(App {
-            current_path: initial_path.clone(),
-            file_list: initial_list,
-            selected_index: 0,
-            mode: AppMode::Normal,
-            should_quit: false,
-            tick_rate: Duration::from_millis(250),
-            status_message: String::from("Welcome to Files Launcher!"),
+        let mut state = Self::with_connection(db_conn, db_path, start_dir, SearchConfig::load())?;
 
-            focused_pane: FocusedPane::FileList,
-            history_selected_index: 0,
+        let application_cache = apps::discover_applications();
+        state.applications = application_cache.applications();
+        apps::sort_applications_by_frequency(&mut state.applications, &state.db_connection);
+        state.application_watcher = match ApplicationWatcher::start(application_cache) {
+            Ok(watcher) => Some(watcher),
+            Err(e) => {
+                tracing::warn!("Application hot-reload disabled: {}", e);
+                None
+            }
+        };
 
-            history: vec![initial_path],
-            history_index: 0,
+        Ok((state, recovered_from))
+    }
 
-            recent_files,
-            db_connection: db_conn,
+    /// State over an already opened database, without applications
+    fn with_connection(
+        db_connection: Connection,
+        db_path: &Path,
+        start_dir: PathBuf,
+        search_config: SearchConfig,
+    ) -> Result<Self> {
+        let (initial_list, initial_dir_errors) = fs::read_directory(&start_dir, false)?;
+        let clipboard_history =
+            clipboard::get_history(&db_connection, 50, ClipboardSort::default())
+                .unwrap_or_default();
+        let active_search_config = search_config.clone();
 
-            preview_state: PreviewState::None,
-            search_query: String::new(),
-            command_input: String::new(),
+        let mut state = AppState {
+            current_path: start_dir.clone(),
+            file_list: initial_list,
+            dir_errors: initial_dir_errors,
+            file_list_version: 0,
+            tick_rate: Duration::from_millis(250),
             show_hidden: false,
-            filtered_file_list: Vec::new(),
-            is_filtering: false,
+            sort_order: SortOrder::default(),
+            sort_direction: SortDirection::default(),
+            yanked_files: Vec::new(),
+            file_operation: None,
+            pending_file_operations: VecDeque::new(),
+            undo_stack: VecDeque::new(),
 
-            applications,
-            search_results: Vec::new(),
-            window_visible: true,
+            history: vec![start_dir],
+            history_index: 0,
+
+            recent_files: Vec::new(),
+            most_opened_files: Vec::new(),
+            recent_file_exists_cache: HashMap::new(),
+            db_connection,
+            db_path: db_path.to_path_buf(),
+
+            applications: Vec::new(),
+            application_watcher: None,
+            search_state: SearchState::Empty,
+            search_sort_mode: SearchSortMode::default(),
+            search_case_sensitive: false,
 
             clipboard_history,
-            clipboard_monitor,
+            clipboard_sort: ClipboardSort::default(),
+            clipboard_monitor: ClipboardMonitor::start(),
             last_clipboard_cleanup: Instant::now(),
+            clipboard_max_entry_size: clipboard::DEFAULT_MAX_ENTRY_SIZE,
+            last_monitor_activity: Instant::now(),
+            last_monitor_check: Instant::now(),
+            last_polled_clipboard: None,
 
-            command_history,
-            app_launch_history,
+            command_history: Vec::new(),
+            app_launch_history: Vec::new(),
             search_config,
-        })
+            active_search_config,
+            ssh_hosts: SshHostCache::default(),
+            search_cache: LruCache::default(),
+        };
+        state.refresh_history();
+        state.refresh_command_history();
+        state.refresh_app_launch_history();
+        state.refresh_active_search_config();
+
+        Ok(state)
     }
 
     pub fn _on_tick(&mut self) {
         self.recent_files =
-            history_fs::get_recent_files(&self.db_connection, 10).unwrap_or_default();
+            history_fs::get_recent_files(&self.db_connection, 10, RecentOrder::LastAccessed)
+                .unwrap_or_default();
     }
 
-    pub fn _quit(&mut self) {
-        self.should_quit = true;
+    pub fn refresh_history(&mut self) {
+        self.recent_files =
+            history_fs::get_recent_files(&self.db_connection, 10, RecentOrder::LastAccessed)
+                .unwrap_or_default();
+        self.most_opened_files =
+            history_fs::get_most_opened_files(&self.db_connection, 10).unwrap_or_default();
+        self.refresh_exists_cache();
+        self.search_cache.clear();
     }
 
-    pub fn toggle_visibility(&mut self) {
-        self.window_visible = !self.window_visible;
-        if self.window_visible {
-            self.search_query.clear();
-            self.search_results.clear();
-            self.refresh_history();
-            self.refresh_command_history();
-        }
+    fn refresh_exists_cache(&mut self) {
+        self.recent_file_exists_cache = self
+            .recent_files
+            .iter()
+            .chain(&self.most_opened_files)
+            .map(|r| (r.path.clone(), r.path.exists()))
+            .collect();
     }
 
-    pub fn refresh_history(&mut self) {
-        self.recent_files =
-            history_fs::get_recent_files(&self.db_connection, 10).unwrap_or_default();
+    /// Whether a recent path existed when history was last refreshed
+    pub fn recent_file_exists(&self, path: &Path) -> bool {
+        self.recent_file_exists_cache
+            .get(path)
+            .copied()
+            .unwrap_or(false)
+    }
+
+    /// Drop recent entries for files that have since been deleted
+    pub fn remove_missing_recent(&mut self) -> Result<usize> {
+        let removed = history_fs::clean_recent_history(&self.db_connection)?;
+        self.refresh_history();
+        Ok(removed)
     }
 
     pub fn refresh_command_history(&mut self) {
@@ -168,26 +455,48 @@ impl App {
             history_fs::get_app_launch_history(&self.db_connection, 20).unwrap_or_default();
     }
 
-    fn load_directory(&mut self, path: PathBuf, entries: Vec<DirEntry>) {
-        self.current_path = path;
-        self.file_list = entries;
-        self.selected_index = 0;
-        self.update_preview();
+    /// Recompute the search config from the global one plus any `.filecast.yml`
+    /// in the current directory
+    pub fn refresh_active_search_config(&mut self) {
+        self.active_search_config = match SearchConfig::load_local(&self.current_path) {
+            Some(local) => self.search_config.merged_with(&local),
+            None => self.search_config.clone(),
+        };
+        self.active_search_config.case_sensitive = self.search_case_sensitive;
+        self.search_cache.clear();
     }
 
+    /// Switch case-sensitive matching; true if it changed
+    pub fn set_search_case_sensitive(&mut self, case_sensitive: bool) -> bool {
+        if self.search_case_sensitive == case_sensitive {
+            return false;
+        }
+        self.search_case_sensitive = case_sensitive;
+        self.refresh_active_search_config();
+        true
+    }
+
+    /// Make `new_path` the current directory, recording it in the navigation history
     pub fn change_directory(&mut self, new_path: PathBuf) -> Result<()> {
-        let entries = fs::read_directory(&new_path, self.show_hidden)?;
+        let (mut entries, errors) = fs::read_directory(&new_path, self.show_hidden)?;
+        fs::sort_entries(&mut entries, self.sort_order, self.sort_direction);
+        self.dir_errors = errors;
         self.push_to_history(new_path.clone());
-        self.load_directory(new_path, entries);
-        self.status_message = format!("Changed directory to: {}", self.current_path.display());
+        self.current_path = new_path;
+        self.file_list = entries;
+        self.file_list_version += 1;
+        self.refresh_active_search_config();
         Ok(())
     }
 
+    /// Re-read the current directory
     pub fn refresh_directory(&mut self) -> Result<()> {
-        let entries = fs::read_directory(&self.current_path, self.show_hidden)?;
+        let (mut entries, errors) = fs::read_directory(&self.current_path, self.show_hidden)?;
+        fs::sort_entries(&mut entries, self.sort_order, self.sort_direction);
+        self.dir_errors = errors;
         self.file_list = entries;
-        self.selected_index = 0;
-        self.update_preview();
+        self.file_list_version += 1;
+        self.search_cache.clear();
         Ok(())
     }
 
@@ -197,219 +506,1575 @@ impl App {
         self.history_index = self.history.len() - 1;
     }
 
-    fn filter_files(&mut self) {
-        if self.search_query.is_empty() {
-            self.is_filtering = false;
-            self.filtered_file_list.clear();
-            return;
+    /// Re-sort the current listing
+    pub fn set_files_sort(&mut self, order: SortOrder, direction: SortDirection) {
+        self.sort_order = order;
+        self.sort_direction = direction;
+        fs::sort_entries(&mut self.file_list, order, direction);
+    }
+
+    /// Re-sort the current results by `mode`
+    pub fn set_search_sort_mode(&mut self, mode: SearchSortMode) {
+        if self.search_sort_mode != mode {
+            self.search_sort_mode = mode;
+            self.search_state.sort(mode);
         }
+    }
 
-        let query = self.search_query.to_lowercase();
-        self.filtered_file_list = self
-            .file_list
+    /// Apply application list changes from the `.desktop` watcher; true if they changed
+    pub fn poll_applications(&mut self) -> bool {
+        let Some(watcher) = &self.application_watcher else {
+            return false;
+        };
+        // Package installs touch many files at once; only the latest list matters
+        let Some(AppEvent::ApplicationsChanged(mut applications)) = watcher.rx.try_iter().last()
+        else {
+            return false;
+        };
+
+        apps::sort_applications_by_frequency(&mut applications, &self.db_connection);
+        self.applications = applications;
+        self.search_cache.clear();
+        true
+    }
+
+    /// Past commands matching what follows a `:`, most relevant first
+    pub fn command_suggestions(&self, typed: &str) -> Vec<SearchResult> {
+        let history = history_fs::get_command_history(&self.db_connection, 200).unwrap_or_default();
+        search::command_results(typed, &history)
+    }
+
+    /// Recent files whose path contains `pattern`, most recent first
+    pub fn recent_file_results(&self, pattern: &str) -> Vec<SearchResult> {
+        history_fs::search_recent_files(&self.db_connection, pattern, 50)
+            .unwrap_or_default()
             .iter()
-            .filter(|entry| entry.name.to_lowercase().contains(&query))
-            .cloned()
-            .collect();
-        self.is_filtering = true;
-        self.selected_index = 0;
+            .map(|recent| SearchResult::recent_file(recent, 0.5))
+            .collect()
     }
 
-    pub fn get_display_list(&self) -> &[DirEntry] {
-        if self.is_filtering {
-            &self.filtered_file_list
-        } else {
-            &self.file_list
+    /// Fuzzy-match `query` against files, history, applications and SSH hosts
+    pub fn fuzzy_results(&mut self, query: &str) -> Vec<SearchResult> {
+        let key = (query.to_string(), self.current_path.clone());
+        if let Some(cached) = self.search_cache.get(&key) {
+            return cached.clone();
         }
+        let results = search::search_all(
+            query,
+            &self.file_list,
+            &self.recent_files,
+            &self.most_opened_files,
+            &self.applications,
+            self.ssh_hosts.hosts(),
+            &self.active_search_config,
+        );
+        self.search_cache.put(key, results.clone());
+        results
     }
 
-    fn update_preview(&mut self) {
-        let display_list = self.get_display_list();
-        if display_list.is_empty() {
-            self.preview_state = PreviewState::None;
-            return;
+    /// Pull in any results streamed from a background search
+    pub fn poll_search(&mut self) -> bool {
+        let changed = self.search_state.poll();
+        if changed {
+            self.search_state.sort(self.search_sort_mode);
         }
+        changed
+    }
 
-        let selected = &display_list[self.selected_index];
-        if selected.is_dir {
-            self.preview_state = PreviewState::Summary(format!(
-                "Directory: {}\nItems: {}",
-                selected.name,
-                self.file_list.len()
-            ));
-        } else {
-            // Simple text preview for files up to a certain size
-            if selected.size < 1024 * 100 {
-                // 100KB limit
-                match std::fs::read_to_string(&selected.path) {
-                    Ok(content) => {
-                        let lines: Vec<&str> = content.lines().take(20).collect();
-                        self.preview_state = PreviewState::Text(lines.join("\n"));
-                    }
-                    Err(_) => {
-                        self.preview_state = PreviewState::Summary(format!(
-                            "Binary file or failed to read: {}",
-                            selected.name
-                        ))
-                    }
-                }
-            } else {
-                self.preview_state = PreviewState::Summary(format!(
-                    "File too large for preview: {} ({} bytes)",
-                    selected.name, selected.size
-                ));
-            }
-        }
+    /// Record that `path` was opened, then open it with the default application
+    pub fn open_file(&mut self, path: &Path) -> Result<()> {
+        history_fs::log_access(&self.db_connection, path)?;
+        self.refresh_history();
+        open_file_platform(path)
     }
 
-    pub fn enter_selected(&mut self) -> Result<()> {
-        match self.focused_pane {
-            FocusedPane::FileList => {
-                let display_list = self.get_display_list();
-                if display_list.is_empty() {
-                    return Ok(());
-                }
-                let selected = display_list[self.selected_index].clone();
+    /// Record `command` as run in the current directory
+    pub fn log_command(&mut self, command: &str) {
+        let _ = history_fs::log_command(&self.db_connection, command, &self.current_path);
+        self.refresh_command_history();
+    }
 
-                self.is_filtering = false;
-                self.search_query.clear();
-                self.filtered_file_list.clear();
+    /// Record a launch of `app` for the frequency ordering
+    pub fn log_app_launch(&mut self, app: &DesktopApp) {
+        let _ = history_fs::log_app_launch(&self.db_connection, &app.name, &app.path);
+        self.refresh_app_launch_history();
+    }
 
-                if selected.is_dir {
-                    self.change_directory(selected.path)?;
-                } else {
-                    self.open_file(selected.path)?;
-                }
-            }
-            FocusedPane::History => {
-                if self.recent_files.is_empty() {
-                    return Ok(());
-                }
-                let selected = self.recent_files[self.history_selected_index].clone();
-                if selected.path.is_dir() {
-                    self.change_directory(selected.path)?;
-                } else {
-                    self.open_file(selected.path)?;
-                }
-            }
-            FocusedPane::Preview => {
-                self.status_message = String::from("Cannot enter from preview pane");
+    /// Move `paths` to the trash as a single undoable action
+    pub fn trash(&mut self, paths: Vec<PathBuf>) -> Result<usize> {
+        if paths.is_empty() {
+            return Ok(0);
+        }
+        trash::delete_all(&paths)?;
+        let count = paths.len();
+        self.push_undo(UndoAction::UndoMoveToTrash { paths });
+        self.refresh_directory()?;
+        Ok(count)
+    }
+
+    /// Queue copies or moves of the yanked paths into the current directory, skipping
+    /// any whose destination already exists. Returns how many were skipped.
+    pub fn paste_yanked(&mut self, kind: FileOpKind) -> usize {
+        let mut skipped = 0;
+        for src in &self.yanked_files {
+            let Some(name) = src.file_name() else {
+                continue;
+            };
+            let dst = self.current_path.join(name);
+            if dst.exists() {
+                skipped += 1;
+                continue;
             }
+            self.pending_file_operations
+                .push_back((kind, src.clone(), dst));
         }
-        Ok(())
+
+        if kind == FileOpKind::Move {
+            self.yanked_files.clear();
+        }
+        if self.file_operation.is_none() {
+            self.start_next_file_operation();
+        }
+        skipped
     }
 
-    pub fn go_up(&mut self) -> Result<()> {
-        match self.focused_pane {
-            FocusedPane::FileList => {
-                if let Some(parent) = self.current_path.parent() {
-                    self.change_directory(parent.to_path_buf())?;
+    /// Move `paths` into the current directory, renaming any that would collide with an
+    /// existing entry. Every path is attempted; the error is the last one that failed.
+    pub fn move_dropped_files(&mut self, paths: &[PathBuf]) -> Result<usize> {
+        let mut moved = 0;
+        let mut failed = None;
+        for src in paths {
+            match fs::move_to_directory(src, &self.current_path) {
+                Ok(dst) if dst == *src => {}
+                Ok(dst) => {
+                    self.push_undo(UndoAction::UndoRename {
+                        from: src.clone(),
+                        to: dst,
+                    });
+                    moved += 1;
                 }
-            }
-            FocusedPane::History | FocusedPane::Preview => {
-                self.status_message = String::from("Can only navigate up from file list pane");
+                Err(e) => failed = Some(anyhow::anyhow!("Failed to move {}: {}", src.display(), e)),
             }
         }
-        Ok(())
-    }
 
-    pub fn open_file(&mut self, path: PathBuf) -> Result<()> {
-        history_fs::log_access(&self.db_connection, &path)?;
-        self.refresh_history();
-        opener::open(&path)?;
-        self.status_message = format!(
-            "Opened: {}",
-            path.file_name().unwrap_or_default().to_string_lossy()
-        );
-        Ok(())
+        let _ = self.refresh_directory();
+        match failed {
+            Some(e) => Err(e),
+            None => Ok(moved),
+        }
     }
 
-    pub fn update_search(&mut self, query: &str) {
-        use crate::core::search;
-        self.search_query = query.to_string();
+    fn start_next_file_operation(&mut self) {
+        self.file_operation = self
+            .pending_file_operations
+            .pop_front()
+            .map(|(kind, src, dst)| FileOperation::start(kind, src, dst));
+    }
 
-        if query.is_empty() {
-            self.search_results.clear();
-            self.is_filtering = false;
-            self.filtered_file_list.clear();
-            return;
+    /// Drain progress from the running file operation. Once it finishes, the next one is
+    /// started and what happened to it is returned.
+    pub fn poll_file_operation(&mut self) -> Option<Result<String>> {
+        let op = self.file_operation.as_mut()?;
+        if op.poll() {
+            return None;
         }
 
-        // Update search results (files + apps)
-        self.search_results = search::search_all(
-            query,
-            &self.file_list,
-            &self.recent_files,
-            &self.applications,
-            &self.search_config,
-        );
+        let name = op
+            .src
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+        let verb = match op.kind {
+            FileOpKind::Copy => "Copied",
+            FileOpKind::Move => "Moved",
+        };
+        let outcome = if op.cancel.is_cancelled() {
+            Ok(format!("Cancelled: {}", name))
+        } else if op.is_complete() {
+            let undo = match op.kind {
+                FileOpKind::Copy => UndoAction::UndoCopy {
+                    dst: op.dst.clone(),
+                },
+                FileOpKind::Move => UndoAction::UndoRename {
+                    from: op.src.clone(),
+                    to: op.dst.clone(),
+                },
+            };
+            self.push_undo(undo);
+            Ok(format!("{}: {}", verb, name))
+        } else {
+            Err(anyhow::anyhow!("Failed: {}", name))
+        };
 
-        self.filter_files();
+        self.start_next_file_operation();
+        let _ = self.refresh_directory();
+        Some(outcome)
     }
 
-    pub fn execute_search_result(&mut self, index: usize) -> Result<()> {
-        if index >= self.search_results.len() {
-            return Ok(());
+    /// Cancel the running file operation and drop any queued ones
+    pub fn cancel_file_operation(&mut self) {
+        if let Some(op) = &self.file_operation {
+            op.cancel.cancel();
         }
+        self.pending_file_operations.clear();
+    }
 
-        let result = &self.search_results[index];
-        match &result.kind {
-            SearchResultKind::File(path) | SearchResultKind::RecentFile(path) => {
-                if path.is_dir() {
-                    self.change_directory(path.clone())?;
-                } else {
-                    self.open_file(path.clone())?;
+    fn push_undo(&mut self, action: UndoAction) {
+        if self.undo_stack.len() >= MAX_UNDO {
+            self.undo_stack.pop_front();
+        }
+        self.undo_stack.push_back(action);
+    }
+
+    /// Reverse the most recent file operation, describing what was done, or None if
+    /// there is nothing to undo. The action is dropped even if undoing it fails, so a
+    /// stale entry can't block the ones before it.
+    pub fn undo_last(&mut self) -> Result<Option<String>> {
+        let Some(action) = self.undo_stack.pop_back() else {
+            return Ok(None);
+        };
+
+        let description = match &action {
+            UndoAction::UndoRename { from, to } => {
+                if from.exists() {
+                    anyhow::bail!("{} already exists", from.display());
                 }
+                fs::rename_or_copy(to, from)?;
+                format!("Moved back: {}", fs::abbreviate_path(from))
             }
-            SearchResultKind::Application(app) => {
-                let app_clone = app.clone();
-                let _ = history_fs::log_app_launch(&self.db_connection, &app_clone.name, &app_clone.path);
-                self.refresh_app_launch_history();
-                app_clone.launch()?;
-                self.status_message = format!("Launched: {}", app_clone.name);
-            }
-            SearchResultKind::Command(cmd) => {
-                self.command_input = cmd.clone();
-                self.mode = AppMode::Command;
+            UndoAction::UndoMoveToTrash { paths } => {
+                fs::restore_from_trash(paths)?;
+                format!("Restored: {}", describe_files(paths))
             }
-            SearchResultKind::GrepResult {
-                path,
-                line: _,
-                content: _,
-            } => {
-                self.open_file(path.clone())?;
+            UndoAction::UndoCopy { dst } => {
+                if dst.is_dir() {
+                    std::fs::remove_dir_all(dst)?;
+                } else {
+                    std::fs::remove_file(dst)?;
+                }
+                format!("Removed copy: {}", fs::abbreviate_path(dst))
             }
-        }
-        Ok(())
-    }
+        };
 
-    /// Open the parent folder of a file in the file manager
-    pub fn reveal_in_folder(&self, path: &PathBuf) -> Result<()> {
-        if let Some(parent) = path.parent() {
-            opener::open(parent)?;
-        }
-        Ok(())
+        self.refresh_directory()?;
+        Ok(Some(description))
     }
 
-    /// Refresh clipboard history from database
-    pub fn refresh_clipboard(&mut self) {
-        self.clipboard_history =
-            clipboard::get_history(&self.db_connection, 50).unwrap_or_default();
+    /// Reload clipboard entries, full-text filtered by `query` when it is set
+    pub fn refresh_clipboard(&mut self, query: &str) {
+        let query = query.trim();
+        self.clipboard_history = if query.is_empty() {
+            clipboard::get_history(&self.db_connection, 50, self.clipboard_sort)
+        } else {
+            clipboard::search_clipboard(&self.db_connection, query, 50, self.clipboard_sort)
+        }
+        .unwrap_or_default();
     }
 
-    /// Check for new clipboard entries from the monitor
-    pub fn check_clipboard_updates(&mut self) {
-        while let Ok(content) = self.clipboard_monitor.receiver.try_recv() {
-            if clipboard::add_entry(&self.db_connection, &content, "text").unwrap_or(false) {
-                self.refresh_clipboard();
+    /// Store new clipboard entries from the monitor; true if the history needs reloading
+    pub fn check_clipboard_updates(&mut self) -> bool {
+        let mut changed = false;
+        while let Ok((content, content_type)) = self.clipboard_monitor.receiver.try_recv() {
+            self.last_monitor_activity = Instant::now();
+            let content_type = if content_type == clipboard::CONTENT_TYPE_TEXT {
+                clipboard::detect_content_type(&content)
+            } else {
+                content_type
+            };
+            let content = clipboard::truncate_entry(content, self.clipboard_max_entry_size);
+            if clipboard::add_entry(&self.db_connection, &content, content_type).unwrap_or(false) {
+                changed = true;
             }
         }
 
         // Periodic cleanup (every 5 minutes)
         if self.last_clipboard_cleanup.elapsed() > Duration::from_secs(300) {
             let _ = clipboard::cleanup_expired(&self.db_connection);
-            self.refresh_clipboard();
             self.last_clipboard_cleanup = Instant::now();
+            changed = true;
+        }
+
+        if self.last_monitor_check.elapsed() >= CLIPBOARD_MONITOR_TIMEOUT {
+            self.check_clipboard_monitor();
+            self.last_monitor_check = Instant::now();
         }
+        changed
+    }
+
+    /// Restart the clipboard monitor if it has gone quiet while the clipboard kept changing
+    fn check_clipboard_monitor(&mut self) {
+        let current = clipboard::read_clipboard_text();
+        let changed = self.last_polled_clipboard.is_some() && current != self.last_polled_clipboard;
+        self.last_polled_clipboard = current;
+
+        if changed && self.last_monitor_activity.elapsed() >= CLIPBOARD_MONITOR_TIMEOUT {
+            tracing::warn!("Clipboard monitor stopped reporting changes, restarting it");
+            self.clipboard_monitor.restart();
+            self.last_monitor_activity = Instant::now();
+        }
+    }
+}
+
+impl UiState {
+    pub fn new() -> Self {
+        UiState {
+            selected_index: 0,
+            mode: AppMode::Normal,
+            should_quit: false,
+            status_message: String::from("Welcome to Files Launcher!"),
+            status_message_expires: Some(Instant::now() + STATUS_TIMEOUT),
+            status_is_error: false,
+            toasts: VecDeque::new(),
+            focused_pane: FocusedPane::FileList,
+            history_selected_index: 0,
+            directory_selection_memory: HashMap::new(),
+            show_most_opened: false,
+            is_loading_directory: false,
+
+            preview_state: PreviewState::None,
+            preview_rx: None,
+
+            command_output_rx: None,
+            live_command_output: OutputTail::default(),
+            live_command_errors: OutputTail::default(),
+            command_output_version: 0,
+            command_succeeded: None,
+            command_status: None,
+
+            search_query: String::new(),
+            filtered_file_list: Vec::new(),
+            is_filtering: false,
+            visual_start: 0,
+            open_with_files: Vec::new(),
+            clipboard_query: String::new(),
+
+            window_visible: true,
+            window_visibility_state: WindowVisibilityState {
+                intended: true,
+                actual: true,
+            },
+            previous_view: None,
+        }
+    }
+
+    /// How long until a toast starts fading: zero while one is fading, None without toasts.
+    /// Toasts are static until then, so the UI only needs to redraw continuously during a fade.
+    pub fn next_toast_repaint(&self) -> Option<Duration> {
+        let now = Instant::now();
+        self.toasts
+            .iter()
+            .map(|toast| toast.expires.checked_sub(TOAST_FADE).unwrap_or(now))
+            .min()
+            .map(|fade_start| fade_start.saturating_duration_since(now))
+    }
+
+    /// The running command's output, with its stderr in a separate "Errors" section
+    pub fn command_output_text(&self) -> String {
+        let mut text = self.live_command_output.render();
+        if !self.live_command_errors.is_empty() {
+            text.push_str("\n\nErrors:\n");
+            text.push_str(&self.live_command_errors.render());
+        }
+        text
+    }
+
+    pub fn _quit(&mut self) {
+        self.set_mode(ModeEvent::Quit);
+    }
+
+    pub fn toggle_visibility(&mut self, state: &mut AppState) {
+        self.window_visible = !self.window_visible;
+        if self.window_visible {
+            self.search_query.clear();
+            state.search_state.clear();
+            state.refresh_history();
+            state.refresh_command_history();
+        }
+    }
+
+    /// Report how many missing recent entries were dropped
+    pub fn remove_missing_recent(&mut self, state: &mut AppState) -> Result<usize> {
+        let removed = state.remove_missing_recent()?;
+        self.set_status(
+            format!("Removed {} missing entries", removed),
+            Some(STATUS_TIMEOUT),
+        );
+        Ok(removed)
+    }
+
+    /// Switch case-sensitive matching and re-run the current search with it
+    pub fn set_search_case_sensitive(&mut self, state: &mut AppState, case_sensitive: bool) {
+        if state.set_search_case_sensitive(case_sensitive) {
+            let query = self.search_query.clone();
+            self.update_search(state, &query);
+        }
+    }
+
+    pub fn change_directory(&mut self, state: &mut AppState, new_path: PathBuf) -> Result<()> {
+        let previous_path = state.current_path.clone();
+        self.is_loading_directory = true;
+        let changed = state.change_directory(new_path);
+        self.is_loading_directory = false;
+        changed?;
+
+        // Remember where the cursor was so coming back restores it
+        self.directory_selection_memory
+            .insert(previous_path, self.selected_index);
+        self.selected_index = self
+            .directory_selection_memory
+            .get(&state.current_path)
+            .copied()
+            .filter(|&idx| idx < state.file_list.len())
+            .unwrap_or(0);
+        self.exit_visual_mode();
+        self.update_preview(state);
+        self.set_status(
+            format!(
+                "Changed directory to: {}",
+                fs::abbreviate_path(&state.current_path)
+            ),
+            Some(STATUS_TIMEOUT),
+        );
+        Ok(())
+    }
+
+    pub fn refresh_directory(&mut self, state: &mut AppState) -> Result<()> {
+        self.is_loading_directory = true;
+        let refreshed = state.refresh_directory();
+        self.is_loading_directory = false;
+        refreshed?;
+        self.listing_replaced(state);
+        Ok(())
+    }
+
+    /// Start over at the top of a listing that was just re-read
+    fn listing_replaced(&mut self, state: &AppState) {
+        self.selected_index = 0;
+        self.update_preview(state);
+    }
+
+    fn filter_files(&mut self, state: &mut AppState) {
+        if self.search_query.is_empty() {
+            self.is_filtering = false;
+            self.filtered_file_list.clear();
+            return;
+        }
+
+        let query = self.search_query.to_lowercase();
+        self.filtered_file_list = state
+            .file_list
+            .iter()
+            .filter(|entry| entry.name.to_lowercase().contains(&query))
+            .cloned()
+            .collect();
+        state.file_list_version += 1;
+        self.is_filtering = true;
+        self.selected_index = 0;
+    }
+
+    pub fn get_display_list<'a>(&'a self, state: &'a AppState) -> &'a [DirEntry] {
+        if self.is_filtering {
+            &self.filtered_file_list
+        } else {
+            &state.file_list
+        }
+    }
+
+    fn update_preview(&mut self, state: &AppState) {
+        // Dropping the previous receiver abandons any preview still loading
+        self.preview_rx = None;
+
+        let display_list = self.get_display_list(state);
+        if display_list.is_empty() {
+            self.preview_state = PreviewState::None;
+            return;
+        }
+
+        let selected = &display_list[self.selected_index];
+        if selected.is_dir {
+            self.preview_state = PreviewState::Summary(format!(
+                "Directory: {}\nItems: {}",
+                selected.name,
+                state.file_list.len()
+            ));
+        } else {
+            // Simple text preview for files up to a certain size
+            if selected.size < 1024 * 100 {
+                // 100KB limit; read off the UI thread since slow mounts can stall
+                let (tx, rx) = mpsc::channel();
+                let path = selected.path.clone();
+                let name = selected.name.clone();
+                std::thread::spawn(move || {
+                    let state = match std::fs::read_to_string(&path) {
+                        Ok(content) => {
+                            let lines: Vec<&str> = content.lines().take(20).collect();
+                            PreviewState::Text(lines.join("\n"))
+                        }
+                        Err(_) => PreviewState::Summary(format!(
+                            "Binary file or failed to read: {}",
+                            name
+                        )),
+                    };
+                    let _ = tx.send(state);
+                });
+                self.preview_state = PreviewState::Loading;
+                self.preview_rx = Some(rx);
+            } else {
+                self.preview_state = PreviewState::Summary(format!(
+                    "File too large for preview: {} ({} bytes)",
+                    selected.name, selected.size
+                ));
+            }
+        }
+    }
+
+    /// Apply application list changes, refreshing fuzzy results so a just-installed app
+    /// shows up; grep/find don't list apps
+    pub fn poll_applications(&mut self, state: &mut AppState) -> bool {
+        if !state.poll_applications() {
+            return false;
+        }
+        if !self.search_query.is_empty() && !search::is_external_query(&self.search_query) {
+            let query = self.search_query.clone();
+            self.update_search(state, &query);
+        }
+        true
+    }
+
+    /// Pick up a preview finished by the background reader
+    pub fn poll_preview(&mut self) -> bool {
+        let Some(rx) = &self.preview_rx else {
+            return false;
+        };
+        match rx.try_recv() {
+            Ok(state) => {
+                self.preview_state = state;
+                self.preview_rx = None;
+                true
+            }
+            Err(mpsc::TryRecvError::Empty) => false,
+            Err(mpsc::TryRecvError::Disconnected) => {
+                self.preview_rx = None;
+                false
+            }
+        }
+    }
+
+    /// Start a shell pipeline in the current directory, streaming the last `max_lines`
+    /// lines of its stdout and stderr into `live_command_output` and `live_command_errors`
+    pub fn start_command(
+        &mut self,
+        state: &AppState,
+        stages: &[&str],
+        max_lines: usize,
+    ) -> Result<()> {
+        let (rx, status) = spawn_pipeline(stages, &state.current_path)?;
+        self.live_command_output = OutputTail::new(max_lines);
+        self.live_command_errors = OutputTail::new(max_lines);
+        self.command_output_version += 1;
+        self.command_output_rx = Some(rx);
+        self.command_status = Some(status);
+        self.command_succeeded = None;
+        self.preview_state = PreviewState::Text(String::new());
+        Ok(())
+    }
+
+    pub fn is_command_running(&self) -> bool {
+        self.command_output_rx.is_some()
+    }
+
+    /// Append any new lines from the running command; true when the output changed
+    pub fn poll_command_output(&mut self, state: &mut AppState) -> bool {
+        let Some(rx) = &self.command_output_rx else {
+            return false;
+        };
+        let mut changed = false;
+        let mut finished = false;
+        loop {
+            match rx.try_recv() {
+                Ok(OutputLine::Out(line)) => {
+                    self.live_command_output.push(line);
+                    changed = true;
+                }
+                Ok(OutputLine::Err(line)) => {
+                    self.live_command_errors.push(line);
+                    changed = true;
+                }
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    finished = true;
+                    break;
+                }
+            }
+        }
+
+        if finished {
+            // The status thread holds the last sender, so it has already returned
+            let succeeded = self
+                .command_status
+                .take()
+                .and_then(|status| status.join().ok())
+                .unwrap_or(false);
+            self.command_output_rx = None;
+            self.command_succeeded = Some(succeeded);
+            let _ = self.refresh_directory(state);
+            changed = true;
+        }
+        if changed {
+            self.command_output_version += 1;
+            self.preview_state = PreviewState::Text(self.command_output_text());
+        }
+        changed
+    }
+
+    pub fn enter_selected(&mut self, state: &mut AppState) -> Result<()> {
+        match self.focused_pane {
+            FocusedPane::FileList => {
+                let display_list = self.get_display_list(state);
+                if display_list.is_empty() {
+                    return Ok(());
+                }
+                let selected = display_list[self.selected_index].clone();
+
+                self.is_filtering = false;
+                self.search_query.clear();
+                self.filtered_file_list.clear();
+
+                if selected.is_dir {
+                    self.change_directory(state, selected.path)?;
+                } else {
+                    self.open_file(state, selected.path)?;
+                }
+            }
+            FocusedPane::History => {
+                if state.recent_files.is_empty() {
+                    return Ok(());
+                }
+                let selected = state.recent_files[self.history_selected_index].clone();
+                if selected.path.is_dir() {
+                    self.change_directory(state, selected.path)?;
+                } else {
+                    self.open_file(state, selected.path)?;
+                }
+            }
+            FocusedPane::Preview => {
+                self.set_status("Cannot enter from preview pane", Some(STATUS_TIMEOUT));
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply `event` to the current mode. Rejected transitions are logged and leave the
+    /// mode as it was; state belonging to the mode being left is reset.
+    pub fn set_mode(&mut self, event: ModeEvent) {
+        let from = self.mode;
+        match mode::transition(from, event) {
+            Ok(to) => {
+                if from == AppMode::Visual && to != AppMode::Visual {
+                    self.visual_start = self.selected_index;
+                }
+                if to == AppMode::Quit {
+                    self.should_quit = true;
+                }
+                self.mode = to;
+            }
+            Err(e) => tracing::warn!("Ignoring {:?}: {}", event, e),
+        }
+    }
+
+    /// Start a range selection anchored at the current file
+    pub fn enter_visual_mode(&mut self) {
+        self.set_mode(ModeEvent::EnterVisual);
+        if self.mode == AppMode::Visual {
+            self.visual_start = self.selected_index;
+        }
+    }
+
+    pub fn exit_visual_mode(&mut self) {
+        if self.mode == AppMode::Visual {
+            self.set_mode(ModeEvent::Cancel);
+        }
+    }
+
+    /// Indices of the visual selection in the display list, if in Visual mode
+    pub fn visual_range(&self) -> Option<std::ops::RangeInclusive<usize>> {
+        if self.mode != AppMode::Visual {
+            return None;
+        }
+        let start = self.visual_start.min(self.selected_index);
+        let end = self.visual_start.max(self.selected_index);
+        Some(start..=end)
+    }
+
+    fn visual_selection(&self, state: &AppState) -> Vec<PathBuf> {
+        let Some(range) = self.visual_range() else {
+            return Vec::new();
+        };
+        self.get_display_list(state)
+            .get(range)
+            .unwrap_or_default()
+            .iter()
+            .filter(|entry| entry.name != "..")
+            .map(|entry| entry.path.clone())
+            .collect()
+    }
+
+    /// Copy the selected paths into `yanked_files` and leave Visual mode
+    pub fn yank_selection(&mut self, state: &mut AppState) {
+        state.yanked_files = self.visual_selection(state);
+        self.set_status(
+            format!("Yanked {} items", state.yanked_files.len()),
+            Some(STATUS_TIMEOUT),
+        );
+        self.exit_visual_mode();
+    }
+
+    /// Move the selected paths to the trash and leave Visual mode
+    pub fn trash_selection(&mut self, state: &mut AppState) -> Result<usize> {
+        let paths = self.visual_selection(state);
+        self.exit_visual_mode();
+        let count = state.trash(paths)?;
+        if count > 0 {
+            self.set_status(
+                format!("Moved {} items to trash", count),
+                Some(STATUS_TIMEOUT),
+            );
+            self.listing_replaced(state);
+        }
+        Ok(count)
+    }
+
+    /// Copy or move the yanked paths into the current directory in the background.
+    /// Existing destinations are skipped rather than overwritten.
+    pub fn paste_yanked(&mut self, state: &mut AppState, kind: FileOpKind) {
+        if state.yanked_files.is_empty() {
+            self.set_status("Nothing yanked", Some(STATUS_TIMEOUT));
+            return;
+        }
+        let skipped = state.paste_yanked(kind);
+        if skipped > 0 {
+            self.set_status(
+                format!("Skipped {} items that already exist", skipped),
+                Some(STATUS_TIMEOUT),
+            );
+        }
+    }
+
+    /// Move files dropped on the window into the current directory
+    pub fn move_dropped_files(&mut self, state: &mut AppState, paths: &[PathBuf]) {
+        let moved = state.move_dropped_files(paths);
+        self.listing_replaced(state);
+        match moved {
+            Ok(moved) => {
+                self.set_status(format!("Moved {} items here", moved), Some(STATUS_TIMEOUT))
+            }
+            Err(e) => self.set_error(e.to_string()),
+        }
+    }
+
+    /// Report a finished file operation. Returns true while an operation is in flight.
+    pub fn poll_file_operation(&mut self, state: &mut AppState) -> bool {
+        if let Some(outcome) = state.poll_file_operation() {
+            match outcome {
+                Ok(message) => self.set_status(message, Some(STATUS_TIMEOUT)),
+                Err(e) => self.set_error(e.to_string()),
+            }
+            self.listing_replaced(state);
+        }
+        state.file_operation.is_some()
+    }
+
+    /// Reverse the most recent file operation and report what was undone
+    pub fn undo_last(&mut self, state: &mut AppState) -> Result<()> {
+        match state.undo_last()? {
+            Some(description) => {
+                self.listing_replaced(state);
+                self.set_status(description, Some(STATUS_TIMEOUT));
+            }
+            None => self.set_status("Nothing to undo", Some(STATUS_TIMEOUT)),
+        }
+        Ok(())
+    }
+
+    /// Remember `paths` so the next application launched from Search opens them
+    pub fn start_open_with(&mut self, state: &mut AppState, paths: Vec<PathBuf>) {
+        self.set_status(format!("Open {} with…", describe_files(&paths)), None);
+        self.open_with_files = paths;
+        self.search_query.clear();
+        state.search_state.clear();
+    }
+
+    /// Open-with for the files in the visual selection, leaving Visual mode.
+    /// Returns false if the selection holds no files.
+    pub fn start_open_with_selection(&mut self, state: &mut AppState) -> bool {
+        let files: Vec<PathBuf> = self
+            .visual_selection(state)
+            .into_iter()
+            .filter(|path| path.is_file())
+            .collect();
+        self.exit_visual_mode();
+        if files.is_empty() {
+            self.set_status("No files selected", Some(STATUS_TIMEOUT));
+            return false;
+        }
+        self.start_open_with(state, files);
+        true
+    }
+
+    /// Show an informational status message, cleared after `duration` if one is given
+    pub fn set_status(&mut self, message: impl Into<String>, duration: Option<Duration>) {
+        self.status_message = message.into();
+        self.status_message_expires = duration.map(|d| Instant::now() + d);
+        self.status_is_error = false;
+    }
+
+    /// Show an error status message that stays until the next status replaces it
+    pub fn set_error(&mut self, message: impl Into<String>) {
+        self.status_message = message.into();
+        self.status_message_expires = None;
+        self.status_is_error = true;
+    }
+
+    /// Clear the status message once its timeout has passed. Returns true if it was cleared.
+    pub fn expire_status(&mut self) -> bool {
+        match self.status_message_expires {
+            Some(expires) if Instant::now() >= expires => {
+                self.status_message.clear();
+                self.status_message_expires = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Copy a clipboard history entry back to the clipboard. With `auto_paste`, also hide
+    /// the window and paste it into the window that had focus before.
+    pub fn copy_clipboard_entry(&mut self, content: &str, auto_paste: bool) {
+        if let Err(e) = clipboard::copy_to_clipboard(content) {
+            self.set_error(format!("Failed to copy: {}", e));
+            return;
+        }
+        if !auto_paste {
+            self.show_toast("Copied to clipboard", ToastKind::Success);
+            return;
+        }
+        match input::paste_to_previous_window() {
+            Ok(()) => self.window_visible = false,
+            Err(e) => self.set_error(format!("Auto-paste failed: {}", e)),
+        }
+    }
+
+    pub fn show_toast(&mut self, message: impl Into<String>, kind: ToastKind) {
+        if self.toasts.len() >= MAX_TOASTS {
+            self.toasts.pop_front();
+        }
+        self.toasts.push_back(Toast {
+            message: message.into(),
+            expires: Instant::now() + TOAST_DURATION,
+            kind,
+        });
+    }
+
+    /// Copy `text` to the system clipboard and confirm with a toast
+    pub fn copy_path(&mut self, text: &str) {
+        match clipboard::copy_to_clipboard(text) {
+            Ok(()) => self.show_toast(format!("Copied: {}", text), ToastKind::Success),
+            Err(e) => self.set_error(format!("Failed to copy: {}", e)),
+        }
+    }
+
+    pub fn go_up(&mut self, state: &mut AppState) -> Result<()> {
+        match self.focused_pane {
+            FocusedPane::FileList => {
+                if let Some(parent) = state.current_path.parent() {
+                    let child = state.current_path.clone();
+                    self.change_directory(state, parent.to_path_buf())?;
+                    // Land on the directory we just came out of
+                    self.selected_index = state
+                        .file_list
+                        .iter()
+                        .position(|entry| entry.path == child && entry.name != "..")
+                        .unwrap_or(0);
+                    self.update_preview(state);
+                }
+            }
+            FocusedPane::History | FocusedPane::Preview => {
+                self.set_status(
+                    "Can only navigate up from file list pane",
+                    Some(STATUS_TIMEOUT),
+                );
+            }
+        }
+        Ok(())
+    }
+
+    pub fn open_file(&mut self, state: &mut AppState, path: PathBuf) -> Result<()> {
+        if let Err(e) = state.open_file(&path) {
+            self.set_error(format!("Failed to open: {}", e));
+            return Err(e);
+        }
+        self.set_status(
+            format!(
+                "Opened: {}",
+                path.file_name().unwrap_or_default().to_string_lossy()
+            ),
+            Some(STATUS_TIMEOUT),
+        );
+        Ok(())
+    }
+
+    pub fn update_search(&mut self, state: &mut AppState, query: &str) {
+        // Commands go to the shell whole; anything else is scored against every entry
+        let max_len = state.active_search_config.max_search_query_len;
+        let query = match query.char_indices().nth(max_len) {
+            Some((end, _)) if !query.starts_with(':') => {
+                self.set_status(
+                    format!("Search query truncated to {} characters", max_len),
+                    Some(STATUS_TIMEOUT),
+                );
+                &query[..end]
+            }
+            _ => query,
+        };
+        self.search_query = query.to_string();
+
+        // Suggest past commands from the first keystroke, in the order they were scored
+        if let Some(typed) = query.strip_prefix(':') {
+            state.search_state = SearchState::Complete(state.command_suggestions(typed));
+            return;
+        }
+
+        if let Some(expression) = query.strip_prefix('=') {
+            state.search_state =
+                SearchState::Complete(vec![crate::core::calc::calculate(expression)]);
+            return;
+        }
+
+        // A path that exists is the only result; no need to score anything against it
+        if search::looks_like_absolute_path(query)
+            && let Some(entry) =
+                search::expand_path_query(query).and_then(|path| DirEntry::from_path(path).ok())
+        {
+            state.search_state = SearchState::Complete(vec![SearchResult::file(&entry, 1.0)]);
+            return;
+        }
+
+        if query.is_empty() || state.active_search_config.is_below_min_length(query) {
+            state.search_state.clear();
+            self.is_filtering = false;
+            self.filtered_file_list.clear();
+            return;
+        }
+
+        if let Some(pattern) = query.strip_prefix('~') {
+            // Equal scores keep the database's most-recent-first order under the score sort
+            state.search_state = SearchState::Complete(state.recent_file_results(pattern));
+        } else if search::is_external_query(query) {
+            // grep/find can be slow, so stream their results in from a background thread
+            let rx = search::spawn_external_search(
+                query.to_string(),
+                state.active_search_config.clone(),
+                state.current_path.clone(),
+            );
+            state.search_state = SearchState::Streaming {
+                results: Vec::new(),
+                rx,
+            };
+        } else {
+            // Update search results (files + apps)
+            state.search_state = SearchState::Complete(state.fuzzy_results(query));
+        }
+        state.search_state.sort(state.search_sort_mode);
+
+        self.filter_files(state);
+    }
+
+    pub fn execute_search_result(
+        &mut self,
+        state: &mut AppState,
+        index: usize,
+    ) -> Result<ExecutedResultKind> {
+        let Some(result) = state.search_state.get(index).cloned() else {
+            anyhow::bail!("No search result at index {}", index);
+        };
+
+        let executed = ExecutedResultKind::of(&result.kind);
+        match &result.kind {
+            SearchResultKind::File(_)
+            | SearchResultKind::RecentFile(_)
+            | SearchResultKind::GrepResult { .. } => match &executed {
+                ExecutedResultKind::NavigatedTo(path) => {
+                    self.change_directory(state, path.clone())?
+                }
+                ExecutedResultKind::Opened(path) => self.open_file(state, path.clone())?,
+                _ => {}
+            },
+            SearchResultKind::Application(app) => {
+                state.log_app_launch(app);
+                let files = std::mem::take(&mut self.open_with_files);
+                if files.is_empty() {
+                    app.launch()?;
+                    self.set_status(format!("Launched: {}", app.name), Some(STATUS_TIMEOUT));
+                } else {
+                    let paths: Vec<&Path> = files.iter().map(PathBuf::as_path).collect();
+                    app.launch_with_files(&paths)?;
+                    self.set_status(
+                        format!("Opened {} with {}", describe_files(&files), app.name),
+                        Some(STATUS_TIMEOUT),
+                    );
+                }
+            }
+            SearchResultKind::Command(_) => {}
+            SearchResultKind::SshHost { alias, .. } => {
+                open_ssh_session(alias, &settings::default_terminal_command())?;
+                self.set_status(format!("Connecting to {}", alias), Some(STATUS_TIMEOUT));
+            }
+            SearchResultKind::Calculation(value) => {
+                if value.is_nan() {
+                    anyhow::bail!("{}", result.name);
+                }
+                let text = result.kind.copy_text();
+                clipboard::copy_to_clipboard(&text)?;
+                self.show_toast(format!("Copied: {}", text), ToastKind::Success);
+            }
+        }
+        Ok(executed)
+    }
+
+    /// Launch one of an application result's desktop actions
+    pub fn execute_search_action(
+        &mut self,
+        state: &mut AppState,
+        index: usize,
+        action_index: usize,
+    ) -> Result<()> {
+        let Some(SearchResultKind::Application(app)) =
+            state.search_state.get(index).map(|r| &r.kind)
+        else {
+            return Ok(());
+        };
+        let app_clone = app.clone();
+        let Some(action) = app_clone.actions.get(action_index) else {
+            return Ok(());
+        };
+
+        state.log_app_launch(&app_clone);
+        app_clone.launch_action(action)?;
+        self.set_status(
+            format!("Launched: {} ({})", app_clone.name, action.name),
+            Some(STATUS_TIMEOUT),
+        );
+        Ok(())
+    }
+
+    /// Adopt the sort orders and clipboard limits saved in settings
+    pub fn apply_settings(&mut self, state: &mut AppState, settings: &LauncherSettings) {
+        state.clipboard_max_entry_size = settings.clipboard_max_entry_size;
+        self.set_files_sort(
+            state,
+            settings.files_sort_order,
+            settings.files_sort_direction,
+        );
+        state.set_search_sort_mode(settings.search_sort_mode);
+        self.set_search_case_sensitive(state, settings.search_case_sensitive);
+        if state.clipboard_sort != settings.clipboard_sort {
+            state.clipboard_sort = settings.clipboard_sort;
+            state.refresh_clipboard(&self.clipboard_query);
+        }
+    }
+
+    /// Re-sort the current listing, keeping the selected entry selected
+    pub fn set_files_sort(
+        &mut self,
+        state: &mut AppState,
+        order: SortOrder,
+        direction: SortDirection,
+    ) {
+        let selected = self
+            .get_display_list(state)
+            .get(self.selected_index)
+            .map(|e| e.path.clone());
+        state.set_files_sort(order, direction);
+        fs::sort_entries(&mut self.filtered_file_list, order, direction);
+        if let Some(selected) = selected {
+            self.selected_index = self
+                .get_display_list(state)
+                .iter()
+                .position(|e| e.path == selected)
+                .unwrap_or(0);
+        }
+    }
+
+    /// Switch to the next sort mode and re-sort the current results
+    pub fn cycle_search_sort_mode(&mut self, state: &mut AppState) {
+        state.set_search_sort_mode(state.search_sort_mode.next());
+        self.set_status(
+            format!("Sort: {}", state.search_sort_mode.label()),
+            Some(STATUS_TIMEOUT),
+        );
+    }
+}
+
+impl Default for UiState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Open the parent folder of a file in the file manager
+pub fn reveal_in_folder(path: &Path) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        opener::open(parent)?;
+    }
+    Ok(())
+}
+
+/// How long the clipboard monitor may stay silent before it is checked for staleness
+const CLIPBOARD_MONITOR_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Spawn `terminal_cmd` (a program plus optional arguments) with `path` as its working directory
+pub fn open_terminal_here(path: &Path, terminal_cmd: &str) -> Result<()> {
+    let mut parts = terminal_cmd.split_whitespace();
+    let Some(program) = parts.next() else {
+        anyhow::bail!("No terminal command configured");
+    };
+
+    std::process::Command::new(program)
+        .args(parts)
+        .current_dir(path)
+        .spawn()?;
+    Ok(())
+}
+
+/// The file's name, or "N files" for several
+pub fn describe_files(paths: &[PathBuf]) -> String {
+    match paths {
+        [path] => path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned(),
+        _ => format!("{} files", paths.len()),
+    }
+}
+
+/// Open `terminal_cmd` running `ssh <alias>`
+pub fn open_ssh_session(alias: &str, terminal_cmd: &str) -> Result<()> {
+    let mut parts = terminal_cmd.split_whitespace();
+    let Some(program) = parts.next() else {
+        anyhow::bail!("No terminal command configured");
+    };
+
+    std::process::Command::new(program)
+        .args(parts)
+        .args(["-e", "ssh", alias])
+        .spawn()?;
+    Ok(())
+}
+
+/// Longest `a | b | ...` pipeline command mode will run
+const MAX_PIPELINE_STAGES: usize = 8;
+
+/// Start `stages` as a shell-style pipeline in `cwd`, each stage's stdout feeding the next.
+/// The last stage's stdout and every stage's stderr arrive line by line on the receiver,
+/// which disconnects once the pipeline has exited; the handle yields whether it succeeded.
+pub fn spawn_pipeline(
+    stages: &[&str],
+    cwd: &Path,
+) -> Result<(mpsc::Receiver<OutputLine>, std::thread::JoinHandle<bool>)> {
+    use anyhow::Context;
+    use std::process::{Command, Stdio};
+
+    if stages.is_empty() {
+        anyhow::bail!("Empty command");
+    }
+    if stages.len() > MAX_PIPELINE_STAGES {
+        anyhow::bail!(
+            "Pipelines are limited to {} stages (got {})",
+            MAX_PIPELINE_STAGES,
+            stages.len()
+        );
+    }
+
+    let (tx, rx) = mpsc::channel();
+    let mut children = Vec::new();
+    let mut readers = Vec::new();
+    let mut previous_stdout = None;
+    let last = stages.len() - 1;
+
+    for (i, stage) in stages.iter().enumerate() {
+        let parts: Vec<&str> = stage.split_whitespace().collect();
+        let Some((program, args)) = parts.split_first() else {
+            anyhow::bail!("Pipeline stage {} is empty", i + 1);
+        };
+
+        let mut cmd = Command::new(program);
+        cmd.args(args)
+            .current_dir(cwd)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        if let Some(stdout) = previous_stdout.take() {
+            cmd.stdin(Stdio::from(stdout));
+        }
+        let mut child = cmd
+            .spawn()
+            .with_context(|| format!("Failed to run {}", program))?;
+
+        if i == last {
+            if let Some(stdout) = child.stdout.take() {
+                readers.push(stream_lines(stdout, tx.clone(), OutputLine::Out));
+            }
+        } else {
+            previous_stdout = child.stdout.take();
+        }
+        // Drain stderr as we go so a chatty stage can't block on a full pipe
+        if let Some(stderr) = child.stderr.take() {
+            readers.push(stream_lines(stderr, tx.clone(), OutputLine::Err));
+        }
+        children.push(child);
+    }
+
+    // Keeping `tx` here means the channel only disconnects once the status is known
+    let status = std::thread::spawn(move || {
+        for reader in readers {
+            let _ = reader.join();
+        }
+        let mut succeeded = false;
+        for mut child in children {
+            succeeded = child.wait().map(|s| s.success()).unwrap_or(false);
+        }
+        drop(tx);
+        succeeded
+    });
+
+    Ok((rx, status))
+}
+
+/// Forward each line of `source` to `tx`, tagged by `line`, until it closes or the
+/// receiver goes away
+fn stream_lines(
+    source: impl std::io::Read + Send + 'static,
+    tx: mpsc::Sender<OutputLine>,
+    line: fn(String) -> OutputLine,
+) -> std::thread::JoinHandle<()> {
+    use std::io::BufRead;
+
+    std::thread::spawn(move || {
+        let mut reader = std::io::BufReader::new(source);
+        let mut buf = Vec::new();
+        while reader.read_until(b'\n', &mut buf).unwrap_or(0) > 0 {
+            let text = String::from_utf8_lossy(&buf);
+            if tx
+                .send(line(text.trim_end_matches(['\n', '\r']).to_string()))
+                .is_err()
+            {
+                break;
+            }
+            buf.clear();
+        }
+    })
+}
+
+/// Open `path` with the default application, falling back to common openers when
+/// `opener` can't (e.g. minimal desktops without a configured `xdg-open`)
+fn open_file_platform(path: &Path) -> Result<()> {
+    open_with_fallbacks(
+        path,
+        |path| opener::open(path).map_err(anyhow::Error::from),
+        |program, args, path| {
+            std::process::Command::new(program)
+                .args(args)
+                .arg(path)
+                .spawn()
+                .map(|_| ())
+        },
+    )
+}
+
+/// Openers tried in order after `opener` fails, with the arguments before the path
+const OPENER_FALLBACKS: [(&str, &[&str]); 3] =
+    [("xdg-open", &[]), ("gio", &["open"]), ("mimeopen", &["-d"])];
+
+fn open_with_fallbacks(
+    path: &Path,
+    open: impl FnOnce(&Path) -> Result<()>,
+    mut spawn: impl FnMut(&str, &[&str], &Path) -> std::io::Result<()>,
+) -> Result<()> {
+    let Err(err) = open(path) else {
+        return Ok(());
+    };
+
+    for (program, args) in OPENER_FALLBACKS {
+        if spawn(program, args, path).is_ok() {
+            return Ok(());
+        }
+    }
+
+    anyhow::bail!(
+        "no application could open {} ({}; xdg-open, gio and mimeopen also failed)",
+        path.display(),
+        err
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::apps::DesktopApp;
+
+    /// Runs the fallback chain with `opener` failing, recording each spawn attempt;
+    /// the first `succeed_at` attempts fail
+    fn fallback_attempts(succeed_at: usize) -> (Result<()>, Vec<String>) {
+        let mut attempts = Vec::new();
+        let result = open_with_fallbacks(
+            Path::new("/tmp/report.pdf"),
+            |_| anyhow::bail!("no default opener"),
+            |program, args, path| {
+                let mut command = vec![program.to_string()];
+                command.extend(args.iter().map(|a| a.to_string()));
+                command.push(path.display().to_string());
+                attempts.push(command.join(" "));
+                if attempts.len() > succeed_at {
+                    Ok(())
+                } else {
+                    Err(std::io::Error::from(std::io::ErrorKind::NotFound))
+                }
+            },
+        );
+        (result, attempts)
+    }
+
+    #[test]
+    fn opener_success_skips_fallbacks() {
+        let mut spawned = false;
+        let result = open_with_fallbacks(
+            Path::new("/tmp/report.pdf"),
+            |_| Ok(()),
+            |_, _, _| {
+                spawned = true;
+                Ok(())
+            },
+        );
+        assert!(result.is_ok());
+        assert!(!spawned);
+    }
+
+    #[test]
+    fn fallbacks_are_tried_in_order() {
+        let (result, attempts) = fallback_attempts(0);
+        assert!(result.is_ok());
+        assert_eq!(attempts, ["xdg-open /tmp/report.pdf"]);
+
+        let (result, attempts) = fallback_attempts(2);
+        assert!(result.is_ok());
+        assert_eq!(
+            attempts,
+            [
+                "xdg-open /tmp/report.pdf",
+                "gio open /tmp/report.pdf",
+                "mimeopen -d /tmp/report.pdf"
+            ]
+        );
+    }
+
+    #[test]
+    fn all_fallbacks_failing_is_an_error() {
+        let (result, attempts) = fallback_attempts(3);
+        assert_eq!(attempts.len(), 3);
+        let message = result.unwrap_err().to_string();
+        assert!(message.contains("/tmp/report.pdf"));
+        assert!(message.contains("no default opener"));
+    }
+
+    #[test]
+    fn output_tail_keeps_the_last_lines() {
+        let mut tail = OutputTail::new(3);
+        assert!(tail.is_empty());
+        for i in 1..=5 {
+            tail.push(format!("line {}", i));
+        }
+        assert_eq!(
+            tail.render(),
+            "… (2 earlier lines not shown, 5 lines total)\nline 3\nline 4\nline 5"
+        );
+
+        let mut short = OutputTail::new(3);
+        short.push("only".to_string());
+        assert_eq!(short.render(), "only");
+    }
+
+    /// State over a fresh database, listing an empty `files` directory under a temp dir
+    fn test_state(name: &str) -> (AppState, PathBuf) {
+        let root =
+            std::env::temp_dir().join(format!("filecast-state-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let files = root.join("files");
+        std::fs::create_dir_all(&files).unwrap();
+        let db_path = root.join("history.db");
+        let (db, _) = history_fs::open_with_recovery(&db_path).unwrap();
+        let state =
+            AppState::with_connection(db, &db_path, files, SearchConfig::default()).unwrap();
+        (state, root)
+    }
+
+    fn listed_names(state: &AppState) -> Vec<&str> {
+        state
+            .file_list
+            .iter()
+            .map(|entry| entry.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn change_directory_lists_and_records_history() {
+        let (mut state, root) = test_state("cd");
+        let dir = root.join("files").join("docs");
+        std::fs::create_dir(&dir).unwrap();
+        std::fs::write(dir.join("b.txt"), "b").unwrap();
+        std::fs::write(dir.join("a.txt"), "a").unwrap();
+        let version = state.file_list_version;
+
+        state.change_directory(dir.clone()).unwrap();
+        assert_eq!(state.current_path, dir);
+        assert_eq!(listed_names(&state), ["..", "a.txt", "b.txt"]);
+        assert_eq!(state.history.last(), Some(&dir));
+        assert_eq!(state.history_index, state.history.len() - 1);
+        assert!(state.file_list_version > version);
+
+        assert!(state.change_directory(root.join("missing")).is_err());
+        assert_eq!(state.current_path, dir);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn refresh_directory_picks_up_new_files() {
+        let (mut state, root) = test_state("refresh");
+        std::fs::write(root.join("files").join("new.txt"), "new").unwrap();
+        assert_eq!(listed_names(&state), [".."]);
+
+        state.refresh_directory().unwrap();
+        assert_eq!(listed_names(&state), ["..", "new.txt"]);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn undo_moves_a_dropped_file_back() {
+        let (mut state, root) = test_state("undo");
+        let src = root.join("dropped.txt");
+        std::fs::write(&src, "dropped").unwrap();
+
+        assert_eq!(
+            state
+                .move_dropped_files(std::slice::from_ref(&src))
+                .unwrap(),
+            1
+        );
+        assert!(!src.exists());
+        assert_eq!(listed_names(&state), ["..", "dropped.txt"]);
+
+        let description = state.undo_last().unwrap().unwrap();
+        assert!(description.starts_with("Moved back"));
+        assert!(src.exists());
+        assert_eq!(listed_names(&state), [".."]);
+        assert_eq!(state.undo_last().unwrap(), None);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn paste_skips_existing_destinations() {
+        let (mut state, root) = test_state("paste");
+        let src = root.join("notes.txt");
+        std::fs::write(&src, "notes").unwrap();
+        std::fs::write(root.join("files").join("notes.txt"), "already here").unwrap();
+        state.yanked_files = vec![src];
+
+        assert_eq!(state.paste_yanked(FileOpKind::Copy), 1);
+        assert!(state.file_operation.is_none());
+        assert!(state.pending_file_operations.is_empty());
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn missing_recent_files_are_removed() {
+        let (mut state, root) = test_state("recent");
+        let kept = root.join("files").join("kept.txt");
+        let gone = root.join("files").join("gone.txt");
+        std::fs::write(&kept, "kept").unwrap();
+        std::fs::write(&gone, "gone").unwrap();
+        history_fs::log_access(&state.db_connection, &kept).unwrap();
+        history_fs::log_access(&state.db_connection, &gone).unwrap();
+        std::fs::remove_file(&gone).unwrap();
+
+        state.refresh_history();
+        assert_eq!(state.recent_files.len(), 2);
+        assert_eq!(state.remove_missing_recent().unwrap(), 1);
+        assert_eq!(state.recent_files.len(), 1);
+        assert!(state.recent_file_exists(&state.recent_files[0].path));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn file_result_is_opened() {
+        let path = PathBuf::from("/nonexistent/report.pdf");
+        assert_eq!(
+            ExecutedResultKind::of(&SearchResultKind::File(path.clone())),
+            ExecutedResultKind::Opened(path.clone())
+        );
+        let grep = SearchResultKind::GrepResult {
+            path: path.clone(),
+            line: 3,
+            content: String::new(),
+        };
+        assert_eq!(
+            ExecutedResultKind::of(&grep),
+            ExecutedResultKind::Opened(path)
+        );
+    }
+
+    #[test]
+    fn directory_result_is_navigated_to() {
+        let dir = std::env::temp_dir();
+        assert_eq!(
+            ExecutedResultKind::of(&SearchResultKind::RecentFile(dir.clone())),
+            ExecutedResultKind::NavigatedTo(dir)
+        );
+    }
+
+    #[test]
+    fn application_result_is_launched() {
+        let kind = SearchResultKind::Application(DesktopApp::for_test("Firefox", &[]));
+        assert_eq!(
+            ExecutedResultKind::of(&kind),
+            ExecutedResultKind::Launched("Firefox".to_string())
+        );
+
+        let ssh = SearchResultKind::SshHost {
+            alias: "build-box".to_string(),
+            hostname: "10.0.0.5".to_string(),
+            user: None,
+        };
+        assert_eq!(
+            ExecutedResultKind::of(&ssh),
+            ExecutedResultKind::Launched("build-box".to_string())
+        );
+    }
+
+    #[test]
+    fn command_result_is_left_to_run() {
+        let kind = SearchResultKind::Command("ls -la".to_string());
+        assert_eq!(
+            ExecutedResultKind::of(&kind),
+            ExecutedResultKind::CommandRun("ls -la".to_string())
+        );
+    }
+
+    #[test]
+    fn calculation_result_is_copied() {
+        let kind = SearchResultKind::Calculation(42.5);
+        assert_eq!(
+            ExecutedResultKind::of(&kind),
+            ExecutedResultKind::Copied("42.5".to_string())
+        );
     }
 }
@@ -1,10 +1,20 @@
 use anyhow::Result;
-use std::path::PathBuf;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::mpsc::{self, Receiver};
+use std::time::SystemTime;
 
-#[derive(Debug, Clone)]
+use crate::core::event::AppEvent;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DesktopApp {
     pub name: String,
+    /// `name` without vendor prefixes, versions and release channels, for matching
+    pub normalized_name: String,
     pub exec: String,
     pub icon: Option<String>,
     pub description: Option<String>,
@@ -12,68 +22,273 @@ pub struct DesktopApp {
     pub keywords: Vec<String>,
     pub terminal: bool,
     pub path: PathBuf,
+    pub actions: Vec<AppAction>,
+}
+
+/// A `[Desktop Action <name>]` entry, e.g. "New Private Window"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppAction {
+    pub name: String,
+    pub exec: String,
 }
 
 impl DesktopApp {
     pub fn launch(&self) -> Result<()> {
-        let exec_clean = self
+        self.spawn(exec_args(&self.exec, &[])?)
+    }
+
+    /// Launch with `file` substituted for the `%f`/`%F` (path) and `%u`/`%U` (URI) field codes
+    pub fn launch_with_file(&self, file: &Path) -> Result<()> {
+        self.launch_with_files(&[file])
+    }
+
+    /// Open `files` as the Exec spec asks: one process for all of them when the app takes
+    /// `%F`/`%U`, otherwise one process per file
+    pub fn launch_with_files(&self, files: &[&Path]) -> Result<()> {
+        let takes_many = self
             .exec
-            .replace("%f", "")
-            .replace("%F", "")
-            .replace("%u", "")
-            .replace("%U", "")
-            .replace("%d", "")
-            .replace("%D", "")
-            .replace("%n", "")
-            .replace("%N", "")
-            .replace("%i", "")
-            .replace("%c", "")
-            .replace("%k", "")
-            .trim()
-            .to_string();
-
-        let parts: Vec<&str> = exec_clean.split_whitespace().collect();
-        if parts.is_empty() {
-            anyhow::bail!("Empty exec command");
-        }
-
-        let program = parts[0];
-        let args = &parts[1..];
+            .split_whitespace()
+            .any(|token| token == "%F" || token == "%U");
+        if takes_many || files.len() <= 1 {
+            return self.spawn(exec_args(&self.exec, files)?);
+        }
+
+        for file in files {
+            self.spawn(exec_args(&self.exec, &[*file])?)?;
+        }
+        Ok(())
+    }
+
+    pub fn launch_action(&self, action: &AppAction) -> Result<()> {
+        self.spawn(exec_args(&action.exec, &[])?)
+    }
 
+    fn spawn(&self, args: Vec<String>) -> Result<()> {
         if self.terminal {
             // Launch in terminal
             Command::new("x-terminal-emulator")
                 .arg("-e")
-                .arg(&exec_clean)
+                .arg(args.join(" "))
                 .spawn()?;
         } else {
-            Command::new(program).args(args).spawn()?;
+            Command::new(&args[0]).args(&args[1..]).spawn()?;
         }
 
         Ok(())
     }
 }
 
-/// Discover all installed applications by parsing .desktop files
-pub fn discover_applications() -> Vec<DesktopApp> {
-    let mut apps = Vec::new();
+#[cfg(test)]
+impl DesktopApp {
+    /// A system-wide app named `name` with the given keywords
+    pub fn for_test(name: &str, keywords: &[&str]) -> Self {
+        DesktopApp {
+            name: name.to_string(),
+            normalized_name: normalize_app_name(name),
+            exec: name.to_lowercase(),
+            icon: None,
+            description: None,
+            categories: Vec::new(),
+            keywords: keywords.iter().map(|k| k.to_string()).collect(),
+            terminal: false,
+            path: PathBuf::from(format!(
+                "/usr/share/applications/{}.desktop",
+                name.to_lowercase()
+            )),
+            actions: Vec::new(),
+        }
+    }
+}
+
+/// Split the Exec line into arguments, filling in file field codes and dropping the rest.
+/// A standalone `%F`/`%U` becomes one argument per file; `%f`/`%u` take the first file.
+/// Substitution happens per token so a path containing spaces stays one argument.
+fn exec_args(exec: &str, files: &[&Path]) -> Result<Vec<String>> {
+    let files = files
+        .iter()
+        .map(|path| {
+            let uri = url::Url::from_file_path(path)
+                .map_err(|_| anyhow::anyhow!("Not an absolute path: {}", path.display()))?;
+            Ok((path.to_string_lossy().into_owned(), uri.to_string()))
+        })
+        .collect::<Result<Vec<_>>>()?;
 
-    // Standard XDG application directories
-    let search_dirs = get_application_dirs();
+    let args = exec
+        .split_whitespace()
+        .flat_map(|token| match token {
+            "%F" => files.iter().map(|(path, _)| path.clone()).collect(),
+            "%U" => files.iter().map(|(_, uri)| uri.clone()).collect(),
+            _ => vec![expand_field_codes(token, files.first())],
+        })
+        .filter(|arg| !arg.is_empty())
+        .collect::<Vec<_>>();
 
-    for dir in search_dirs {
-        if let Ok(entries) = std::fs::read_dir(&dir) {
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if path.extension().map(|e| e == "desktop").unwrap_or(false) {
-                    if let Some(app) = parse_desktop_file(&path) {
-                        if !apps.iter().any(|a: &DesktopApp| a.name == app.name) {
-                            apps.push(app);
-                        }
-                    }
+    if args.is_empty() {
+        anyhow::bail!("Empty exec command");
+    }
+    Ok(args)
+}
+
+/// Expand the field codes in one Exec token in a single pass, so a `%` in the inserted
+/// path or URI is never read as another code. `%%` is a literal `%`; codes with nothing
+/// to fill in are dropped.
+fn expand_field_codes(token: &str, file: Option<&(String, String)>) -> String {
+    let mut expanded = String::with_capacity(token.len());
+    let mut chars = token.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            expanded.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('%') => expanded.push('%'),
+            Some('f' | 'F') => expanded.push_str(file.map(|(path, _)| path.as_str()).unwrap_or("")),
+            Some('u' | 'U') => expanded.push_str(file.map(|(_, uri)| uri.as_str()).unwrap_or("")),
+            Some('d' | 'D' | 'n' | 'N' | 'i' | 'c' | 'k' | 'v' | 'm') => {}
+            Some(other) => {
+                expanded.push('%');
+                expanded.push(other);
+            }
+            None => expanded.push('%'),
+        }
+    }
+    expanded
+}
+
+/// Discover all installed applications by parsing .desktop files. Directories that
+/// haven't changed since the last run are read from the on-disk `ApplicationCache`.
+#[tracing::instrument]
+pub fn discover_applications() -> ApplicationCache {
+    let mut cache = ApplicationCache::load();
+    if cache.refresh() {
+        cache.save();
+    }
+    cache
+}
+
+fn scan_application_dir(dir: &Path) -> Vec<DesktopApp> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().map(|e| e == "desktop").unwrap_or(false))
+        .filter_map(|path| parse_desktop_file(&path))
+        .collect()
+}
+
+/// Parsed applications from every application directory, with each directory's mtime
+/// when it was read. Adding, removing or renaming a `.desktop` file bumps its
+/// directory's mtime, so only directories with a new mtime are parsed again.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ApplicationCache {
+    entries: Vec<DesktopApp>,
+    dir_mtimes: HashMap<PathBuf, SystemTime>,
+}
+
+impl ApplicationCache {
+    fn cache_path() -> Option<PathBuf> {
+        dirs::cache_dir().map(|dir| dir.join("filecast").join("app_cache.json"))
+    }
+
+    /// The cache saved by the last run, or an empty one if it is missing or unreadable
+    pub fn load() -> Self {
+        Self::cache_path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        let Some(path) = Self::cache_path() else {
+            return;
+        };
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        match serde_json::to_string(self) {
+            Ok(content) => {
+                if let Err(e) = std::fs::write(&path, content) {
+                    tracing::warn!("Failed to write {}: {}", path.display(), e);
                 }
             }
+            Err(e) => tracing::warn!("Failed to serialize application cache: {}", e),
+        }
+    }
+
+    /// Re-scan directories whose mtime changed or can't be read. Returns true if the
+    /// cache changed and should be saved.
+    pub fn refresh(&mut self) -> bool {
+        let dirs = get_application_dirs();
+        let mut changed = false;
+
+        for dir in &dirs {
+            let modified = std::fs::metadata(dir).and_then(|m| m.modified()).ok();
+            let cached = self.dir_mtimes.get(dir).copied();
+            if modified.is_some() && modified == cached {
+                continue;
+            }
+
+            let before = self.entries.len();
+            self.entries
+                .retain(|app| app.path.parent() != Some(dir.as_path()));
+            let found = scan_application_dir(dir);
+            changed |= modified != cached || before != self.entries.len() || !found.is_empty();
+            self.entries.extend(found);
+
+            match modified {
+                Some(time) => self.dir_mtimes.insert(dir.clone(), time),
+                None => self.dir_mtimes.remove(dir),
+            };
+        }
+
+        // Drop directories that are no longer searched
+        let before = self.entries.len();
+        self.entries.retain(|app| {
+            app.path
+                .parent()
+                .is_some_and(|parent| dirs.iter().any(|d| d == parent))
+        });
+        self.dir_mtimes.retain(|dir, _| dirs.contains(dir));
+        changed || before != self.entries.len()
+    }
+
+    /// Cached applications sorted by name. When several directories define an app with
+    /// the same name, the one from the earlier directory wins.
+    pub fn applications(&self) -> Vec<DesktopApp> {
+        prioritized_applications(&self.entries, &get_application_dirs())
+    }
+
+    /// Re-read the `.desktop` file at `path`. Entries are keyed by path, so a renamed app
+    /// replaces its old entry and removing a file drops only that file's entry.
+    fn update_entry(&mut self, path: &Path) {
+        self.entries.retain(|app| app.path != path);
+        if let Some(app) = parse_desktop_file(path) {
+            self.entries.push(app);
+        }
+    }
+}
+
+/// One app per name from `entries`, taken from the earliest of `dirs` that defines it,
+/// sorted by name
+fn prioritized_applications(entries: &[DesktopApp], dirs: &[PathBuf]) -> Vec<DesktopApp> {
+    let mut apps: Vec<DesktopApp> = Vec::new();
+    let mut seen_dirs: Vec<&PathBuf> = Vec::new();
+
+    for dir in dirs {
+        if seen_dirs.contains(&dir) {
+            continue;
+        }
+        for app in entries
+            .iter()
+            .filter(|a| a.path.parent() == Some(dir.as_path()))
+        {
+            if !apps.iter().any(|a| a.name == app.name) {
+                apps.push(app.clone());
+            }
         }
+        seen_dirs.push(dir);
     }
 
     // Sort alphabetically
@@ -82,6 +297,81 @@ pub fn discover_applications() -> Vec<DesktopApp> {
     apps
 }
 
+/// Order apps by launch count, most used first; apps never launched keep alphabetical order at the end
+pub fn sort_applications_by_frequency(apps: &mut Vec<DesktopApp>, conn: &Connection) {
+    let launch_counts: HashMap<PathBuf, i32> =
+        crate::core::history::get_app_launch_history(conn, u32::MAX)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|entry| (entry.desktop_path, entry.launch_count))
+            .collect();
+
+    apps.sort_by(|a, b| {
+        let count_a = launch_counts.get(&a.path).copied().unwrap_or(0);
+        let count_b = launch_counts.get(&b.path).copied().unwrap_or(0);
+        count_b
+            .cmp(&count_a)
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+    });
+}
+
+/// Watches the application directories and reports the updated list when `.desktop`
+/// files are installed, changed or removed
+pub struct ApplicationWatcher {
+    _watcher: RecommendedWatcher,
+    pub rx: Receiver<AppEvent>,
+}
+
+impl ApplicationWatcher {
+    /// Start from `cache`, as `discover_applications` returned it
+    pub fn start(mut cache: ApplicationCache) -> notify::Result<Self> {
+        let (tx, rx) = mpsc::channel();
+
+        let mut watcher =
+            notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+                let Ok(event) = res else {
+                    return;
+                };
+                if !matches!(
+                    event.kind,
+                    EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+                ) {
+                    return;
+                }
+
+                let mut changed = false;
+                for path in &event.paths {
+                    if path.extension().map(|e| e != "desktop").unwrap_or(true) {
+                        continue;
+                    }
+                    // Renames and removals leave nothing to parse; treat both as a removal
+                    cache.update_entry(path);
+                    changed = true;
+                }
+
+                if changed {
+                    let _ = tx.send(AppEvent::ApplicationsChanged(cache.applications()));
+                }
+            })?;
+
+        let mut watched = Vec::new();
+        for dir in get_application_dirs() {
+            if watched.contains(&dir) || !dir.is_dir() {
+                continue;
+            }
+            if let Err(e) = watcher.watch(&dir, RecursiveMode::NonRecursive) {
+                tracing::warn!("Failed to watch {}: {}", dir.display(), e);
+            }
+            watched.push(dir);
+        }
+
+        Ok(ApplicationWatcher {
+            _watcher: watcher,
+            rx,
+        })
+    }
+}
+
 fn get_application_dirs() -> Vec<PathBuf> {
     let mut dirs = Vec::new();
 
@@ -89,14 +379,20 @@ fn get_application_dirs() -> Vec<PathBuf> {
         dirs.push(data_home.join("applications"));
     }
     if let Some(home) = dirs::home_dir() {
-        dirs.push(home.join(".local/share/applications"));
+        dirs.push(home.join(".local").join("share").join("applications"));
     }
 
     dirs.push(PathBuf::from("/usr/share/applications"));
     dirs.push(PathBuf::from("/usr/local/share/applications"));
 
     if let Some(data_home) = dirs::data_local_dir() {
-        dirs.push(data_home.join("flatpak/exports/share/applications"));
+        dirs.push(
+            data_home
+                .join("flatpak")
+                .join("exports")
+                .join("share")
+                .join("applications"),
+        );
     }
     dirs.push(PathBuf::from("/var/lib/flatpak/exports/share/applications"));
 
@@ -105,7 +401,66 @@ fn get_application_dirs() -> Vec<PathBuf> {
     dirs
 }
 
-fn parse_desktop_file(path: &PathBuf) -> Option<DesktopApp> {
+/// Vendor names that lead many application names, e.g. "Google Chrome"
+const APP_NAME_VENDORS: &[&str] = &[
+    "google",
+    "mozilla",
+    "microsoft",
+    "jetbrains",
+    "adobe",
+    "oracle",
+];
+
+/// Release channels that trail application names, e.g. "Firefox ESR"
+const APP_NAME_CHANNELS: &[&str] = &[
+    "stable", "beta", "dev", "nightly", "canary", "esr", "lts", "unstable", "preview", "insiders",
+];
+
+/// Strip parenthetical qualifiers, leading vendor names, and trailing release channels
+/// and version numbers: "Mozilla Firefox ESR" becomes "Firefox". Returns the trimmed
+/// name unchanged if nothing would be left.
+pub fn normalize_app_name(name: &str) -> String {
+    let mut unbracketed = String::with_capacity(name.len());
+    let mut depth = 0usize;
+    for c in name.chars() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth = depth.saturating_sub(1),
+            _ if depth == 0 => unbracketed.push(c),
+            _ => {}
+        }
+    }
+
+    let is_version = |word: &str| {
+        let digits = word.strip_prefix(['v', 'V']).unwrap_or(word);
+        digits.chars().any(|c| c.is_ascii_digit())
+            && digits.chars().all(|c| c.is_ascii_digit() || c == '.')
+    };
+
+    let mut words: Vec<&str> = unbracketed.split_whitespace().collect();
+    while words.len() > 1 && APP_NAME_VENDORS.contains(&words[0].to_lowercase().as_str()) {
+        words.remove(0);
+    }
+    while let Some(last) = words.last().filter(|_| words.len() > 1) {
+        // "Visual Studio Code - Insiders" leaves a dangling separator behind
+        if APP_NAME_CHANNELS.contains(&last.to_lowercase().as_str())
+            || is_version(last)
+            || *last == "-"
+        {
+            words.pop();
+        } else {
+            break;
+        }
+    }
+
+    if words.is_empty() {
+        name.trim().to_string()
+    } else {
+        words.join(" ")
+    }
+}
+
+fn parse_desktop_file(path: &Path) -> Option<DesktopApp> {
     use freedesktop_desktop_entry::DesktopEntry;
 
     let locales: &[&str] = &[];
@@ -141,7 +496,22 @@ fn parse_desktop_file(path: &PathBuf) -> Option<DesktopApp> {
         .map(|kws| kws.iter().map(|k| k.to_string()).collect())
         .unwrap_or_default();
 
+    let actions: Vec<AppAction> = entry
+        .actions()
+        .map(|ids| {
+            ids.iter()
+                .filter_map(|id| {
+                    Some(AppAction {
+                        name: entry.action_name(id, locales)?.to_string(),
+                        exec: entry.action_exec(id)?.to_string(),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
     Some(DesktopApp {
+        normalized_name: normalize_app_name(&name),
         name,
         exec,
         icon,
@@ -149,6 +519,119 @@ fn parse_desktop_file(path: &PathBuf) -> Option<DesktopApp> {
         categories,
         keywords,
         terminal,
-        path: path.clone(),
+        path: path.to_path_buf(),
+        actions,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_app_name_patterns() {
+        let cases = [
+            ("Mozilla Firefox ESR", "Firefox"),
+            ("Mozilla Firefox", "Firefox"),
+            ("Mozilla Thunderbird", "Thunderbird"),
+            ("Firefox Nightly", "Firefox"),
+            ("Firefox Developer Edition", "Firefox Developer Edition"),
+            ("Google Chrome", "Chrome"),
+            ("Google Chrome (stable)", "Chrome"),
+            ("Google Chrome Beta", "Chrome"),
+            ("Google Chrome Canary", "Chrome"),
+            ("Chromium Web Browser", "Chromium Web Browser"),
+            ("GIMP (GNU Image Manipulation Program)", "GIMP"),
+            ("Visual Studio Code - Insiders", "Visual Studio Code"),
+            ("Microsoft Edge Dev", "Edge"),
+            ("Microsoft Teams (Preview)", "Teams"),
+            ("JetBrains IntelliJ IDEA 2024.1", "IntelliJ IDEA"),
+            ("Blender 4.1", "Blender"),
+            ("Python 3.12", "Python"),
+            ("Inkscape v1.3", "Inkscape"),
+            ("LibreOffice Writer [Flatpak]", "LibreOffice Writer"),
+            ("Google", "Google"),
+        ];
+        for (name, expected) in cases {
+            assert_eq!(normalize_app_name(name), expected, "normalizing {:?}", name);
+        }
+    }
+
+    #[test]
+    fn normalize_app_name_keeps_names_that_would_vanish() {
+        assert_eq!(normalize_app_name("  (beta)  "), "(beta)");
+    }
+
+    fn app_in(dir: &str, file: &str, name: &str) -> DesktopApp {
+        DesktopApp {
+            path: Path::new(dir).join(file),
+            ..DesktopApp::for_test(name, &[])
+        }
+    }
+
+    #[test]
+    fn earlier_directories_override_later_ones() {
+        let dirs = [
+            PathBuf::from("/home/me/.local/share/applications"),
+            PathBuf::from("/usr/share/applications"),
+        ];
+        let user = app_in("/home/me/.local/share/applications", "foo.desktop", "Foo");
+        let system = app_in("/usr/share/applications", "foo.desktop", "Foo");
+
+        // Order of discovery (or of watcher updates) doesn't matter
+        for entries in [
+            vec![user.clone(), system.clone()],
+            vec![system.clone(), user.clone()],
+        ] {
+            let apps = prioritized_applications(&entries, &dirs);
+            assert_eq!(apps.len(), 1);
+            assert_eq!(apps[0].path, user.path);
+        }
+    }
+
+    #[test]
+    fn removing_an_override_falls_back_to_the_system_entry() {
+        let override_path = PathBuf::from("/nonexistent/filecast/applications/foo.desktop");
+        let system = app_in("/usr/share/applications", "foo.desktop", "Foo");
+        let mut cache = ApplicationCache {
+            entries: vec![
+                DesktopApp {
+                    path: override_path.clone(),
+                    ..DesktopApp::for_test("Foo", &[])
+                },
+                system.clone(),
+            ],
+            dir_mtimes: HashMap::new(),
+        };
+
+        cache.update_entry(&override_path);
+        let dirs = [
+            override_path.parent().unwrap().to_path_buf(),
+            PathBuf::from("/usr/share/applications"),
+        ];
+        let apps = prioritized_applications(&cache.entries, &dirs);
+        assert_eq!(apps.len(), 1);
+        assert_eq!(apps[0].path, system.path);
+    }
+
+    #[test]
+    fn exec_args_keeps_percent_signs_in_the_file_name() {
+        let file = Path::new("/tmp/50%done %u.txt");
+        assert_eq!(
+            exec_args("editor --file=%f %i", &[file]).unwrap(),
+            ["editor", "--file=/tmp/50%done %u.txt"]
+        );
+        assert_eq!(
+            exec_args("viewer %U", &[file]).unwrap(),
+            ["viewer", "file:///tmp/50%25done%20%25u.txt"]
+        );
+    }
+
+    #[test]
+    fn exec_args_expands_escaped_percent() {
+        assert_eq!(
+            exec_args("printf 100%% %f", &[]).unwrap(),
+            ["printf", "100%"]
+        );
+    }
+}
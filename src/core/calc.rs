@@ -0,0 +1,84 @@
+use crate::core::search::SearchResult;
+
+/// Linear conversions: (from, to, factor). Each also works in reverse.
+const UNIT_CONVERSIONS: &[(&str, &str, f64)] = &[
+    ("km", "miles", 0.621_371),
+    ("m", "ft", 3.280_84),
+    ("cm", "in", 0.393_701),
+    ("kg", "lb", 2.204_62),
+    ("g", "oz", 0.035_274),
+    ("l", "gal", 0.264_172),
+];
+
+/// Evaluate the text after `=` as a single search result: arithmetic through meval,
+/// or a unit conversion such as `5 km to miles` or `32 f to c`
+pub fn calculate(input: &str) -> SearchResult {
+    let (value, unit) = match convert_units(input) {
+        Some(converted) => converted,
+        None => match meval::eval_str(input) {
+            Ok(value) => (Ok(value), None),
+            Err(e) => (Err(e.to_string()), None),
+        },
+    };
+
+    match value.and_then(check_finite) {
+        Ok(value) => {
+            let formatted = format_number(value);
+            let name = match unit {
+                Some(unit) => format!("= {} {}", formatted, unit),
+                None => format!("= {}", formatted),
+            };
+            SearchResult::calculation(name, value)
+        }
+        Err(msg) => SearchResult::calculation(format!("Error: {}", msg), f64::NAN),
+    }
+}
+
+/// meval follows IEEE rules, so `1/0` comes back as infinity rather than an error
+fn check_finite(value: f64) -> Result<f64, String> {
+    if value.is_infinite() {
+        Err(String::from("division by zero"))
+    } else if value.is_nan() {
+        Err(String::from("not a number"))
+    } else {
+        Ok(value)
+    }
+}
+
+/// `<expression> <unit> to <unit>`; `None` when the input isn't shaped like a conversion
+fn convert_units(input: &str) -> Option<(Result<f64, String>, Option<&'static str>)> {
+    let (left, to) = input.rsplit_once(" to ")?;
+    let (amount, from) = left.trim().rsplit_once(char::is_whitespace)?;
+    let (from, to) = (from.to_lowercase(), to.trim().to_lowercase());
+
+    let amount = match meval::eval_str(amount) {
+        Ok(amount) => amount,
+        Err(e) => return Some((Err(e.to_string()), None)),
+    };
+
+    match (from.as_str(), to.as_str()) {
+        ("f", "c") => return Some((Ok((amount - 32.0) * 5.0 / 9.0), Some("°C"))),
+        ("c", "f") => return Some((Ok(amount * 9.0 / 5.0 + 32.0), Some("°F"))),
+        _ => {}
+    }
+
+    UNIT_CONVERSIONS.iter().find_map(|&(a, b, factor)| {
+        if from == a && to == b {
+            Some((Ok(amount * factor), Some(b)))
+        } else if from == b && to == a {
+            Some((Ok(amount / factor), Some(a)))
+        } else {
+            None
+        }
+    })
+}
+
+/// Whole numbers without a decimal point, others to at most 10 decimal places
+pub fn format_number(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{}", value as i64)
+    } else {
+        let text = format!("{:.10}", value);
+        text.trim_end_matches('0').trim_end_matches('.').to_string()
+    }
+}
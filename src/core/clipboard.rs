@@ -1,10 +1,44 @@
 use arboard::Clipboard;
 use chrono::{DateTime, TimeZone, Utc};
 use rusqlite::{params, Connection, Result};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, OnceLock};
 use std::thread;
 use std::time::Duration;
 
+/// Content copied with Ctrl+C (the `CLIPBOARD` selection)
+pub const CONTENT_TYPE_TEXT: &str = "text";
+/// Text selected with the mouse (the X11 `PRIMARY` selection)
+pub const CONTENT_TYPE_PRIMARY: &str = "primary_selection";
+pub const CONTENT_TYPE_URL: &str = "url";
+pub const CONTENT_TYPE_JSON: &str = "json";
+pub const CONTENT_TYPE_EMAIL: &str = "email";
+pub const CONTENT_TYPE_FILE_PATH: &str = "file_path";
+pub const CONTENT_TYPE_COLOR: &str = "color";
+pub const CONTENT_TYPE_UUID: &str = "uuid";
+/// A pinned snippet whose `{{variable}}` placeholders are filled in before copying
+pub const CONTENT_TYPE_TEMPLATE: &str = "template";
+
+/// Longest entry stored, in characters; longer copies are cut down
+pub const DEFAULT_MAX_ENTRY_SIZE: usize = 100_000;
+const TRUNCATED_SUFFIX: &str = "[truncated]";
+
+/// Every content type, in the order shown by the Clipboard view filter
+pub const CONTENT_TYPES: &[&str] = &[
+    CONTENT_TYPE_TEXT,
+    CONTENT_TYPE_PRIMARY,
+    CONTENT_TYPE_URL,
+    CONTENT_TYPE_JSON,
+    CONTENT_TYPE_EMAIL,
+    CONTENT_TYPE_FILE_PATH,
+    CONTENT_TYPE_COLOR,
+    CONTENT_TYPE_UUID,
+    CONTENT_TYPE_TEMPLATE,
+];
+
 #[derive(Debug, Clone)]
 pub struct ClipboardEntry {
     pub id: i64,
@@ -35,9 +69,52 @@ pub fn init_clipboard_table(conn: &Connection) -> Result<()> {
         "CREATE INDEX IF NOT EXISTS idx_clipboard_pinned ON clipboard_history(pinned)",
         [],
     )?;
+    // Full-text index over entry content, rowid = clipboard_history.id
+    conn.execute(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS clipboard_fts USING fts5(content)",
+        [],
+    )?;
+    // Index entries recorded before the FTS table existed
+    conn.execute(
+        "INSERT INTO clipboard_fts (rowid, content)
+         SELECT id, content FROM clipboard_history
+         WHERE deleted = 0 AND id NOT IN (SELECT rowid FROM clipboard_fts)",
+        [],
+    )?;
+    deduplicate_clipboard(conn)?;
     Ok(())
 }
 
+/// Copies this close together whose text only differs in surrounding whitespace are merged
+const DUPLICATE_WINDOW_SECS: i64 = 5;
+
+/// Soft-delete unpinned entries that have a newer copy of the same trimmed text
+/// within `DUPLICATE_WINDOW_SECS`, returning how many were removed
+pub fn deduplicate_clipboard(conn: &Connection) -> Result<usize> {
+    let tx = conn.unchecked_transaction()?;
+    let removed = tx.execute(
+        "UPDATE clipboard_history SET deleted = 1
+         WHERE pinned = 0 AND deleted = 0 AND EXISTS (
+             SELECT 1 FROM clipboard_history newer
+             WHERE newer.deleted = 0
+               AND newer.id > clipboard_history.id
+               AND abs(newer.created_at - clipboard_history.created_at) <= ?1
+               AND trim(newer.content, char(32, 9, 10, 13))
+                   = trim(clipboard_history.content, char(32, 9, 10, 13))
+         )",
+        params![DUPLICATE_WINDOW_SECS],
+    )?;
+    if removed > 0 {
+        tx.execute(
+            "DELETE FROM clipboard_fts
+             WHERE rowid IN (SELECT id FROM clipboard_history WHERE deleted = 1)",
+            [],
+        )?;
+    }
+    tx.commit()?;
+    Ok(removed)
+}
+
 /// Add new clipboard entry (returns true if actually added, false if duplicate)
 pub fn add_entry(conn: &Connection, content: &str, content_type: &str) -> Result<bool> {
     // Skip empty content
@@ -67,18 +144,55 @@ pub fn add_entry(conn: &Connection, content: &str, content_type: &str) -> Result
          VALUES (?1, ?2, ?3, 0, 0)",
         params![content, content_type, now],
     )?;
+    conn.execute(
+        "INSERT INTO clipboard_fts (rowid, content) VALUES (?1, ?2)",
+        params![conn.last_insert_rowid(), content],
+    )?;
     Ok(true)
 }
 
-/// Get clipboard history (non-deleted, ordered by pinned first then created_at desc)
-pub fn get_history(conn: &Connection, limit: u32) -> Result<Vec<ClipboardEntry>> {
-    let mut stmt = conn.prepare(
+/// How the clipboard list is ordered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ClipboardSort {
+    #[default]
+    PinnedFirst,
+    TimeDesc,
+}
+
+impl ClipboardSort {
+    pub const ALL: [ClipboardSort; 2] = [ClipboardSort::PinnedFirst, ClipboardSort::TimeDesc];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ClipboardSort::PinnedFirst => "Pinned first",
+            ClipboardSort::TimeDesc => "Newest first",
+        }
+    }
+
+    fn order_by(self, table: &str) -> String {
+        match self {
+            ClipboardSort::PinnedFirst => {
+                format!("{table}pinned DESC, {table}created_at DESC")
+            }
+            ClipboardSort::TimeDesc => format!("{table}created_at DESC"),
+        }
+    }
+}
+
+/// Get non-deleted clipboard history in `sort` order
+pub fn get_history(
+    conn: &Connection,
+    limit: u32,
+    sort: ClipboardSort,
+) -> Result<Vec<ClipboardEntry>> {
+    let mut stmt = conn.prepare(&format!(
         "SELECT id, content, content_type, created_at, pinned
          FROM clipboard_history
          WHERE deleted = 0
-         ORDER BY pinned DESC, created_at DESC
+         ORDER BY {}
          LIMIT ?1",
-    )?;
+        sort.order_by("")
+    ))?;
 
     let entries = stmt.query_map(params![limit], |row| {
         Ok(ClipboardEntry {
@@ -93,6 +207,51 @@ pub fn get_history(conn: &Connection, limit: u32) -> Result<Vec<ClipboardEntry>>
     entries.collect()
 }
 
+/// Full-text search over non-deleted entries. Each word matches as a prefix.
+pub fn search_clipboard(
+    conn: &Connection,
+    query: &str,
+    limit: u32,
+    sort: ClipboardSort,
+) -> Result<Vec<ClipboardEntry>> {
+    // Quote every word so FTS5 operators in the query are matched literally
+    let fts_query = query
+        .split_whitespace()
+        .map(|word| format!("\"{}\"*", word.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT ch.id, ch.content, ch.content_type, ch.created_at, ch.pinned
+         FROM clipboard_history ch
+         JOIN clipboard_fts cf ON ch.id = cf.rowid
+         WHERE cf.content MATCH ?1 AND ch.deleted = 0
+         ORDER BY {}
+         LIMIT ?2",
+        sort.order_by("ch.")
+    ))?;
+
+    let entries = stmt.query_map(params![fts_query, limit], |row| {
+        Ok(ClipboardEntry {
+            id: row.get(0)?,
+            content: row.get(1)?,
+            content_type: row.get(2)?,
+            created_at: Utc.timestamp_opt(row.get::<_, i64>(3)?, 0).unwrap(),
+            pinned: row.get::<_, i32>(4)? != 0,
+        })
+    })?;
+
+    entries.collect()
+}
+
+/// Cut `content` to `max_chars` characters, marking it `[truncated]` if anything was dropped
+pub fn truncate_entry(content: String, max_chars: usize) -> String {
+    match content.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}{}", &content[..end], TRUNCATED_SUFFIX),
+        None => content,
+    }
+}
+
 /// Toggle pin status
 pub fn toggle_pin(conn: &Connection, id: i64) -> Result<()> {
     conn.execute(
@@ -102,25 +261,151 @@ pub fn toggle_pin(conn: &Connection, id: i64) -> Result<()> {
     Ok(())
 }
 
+/// Pin an entry and mark it as a template
+pub fn pin_as_template(conn: &Connection, id: i64) -> Result<()> {
+    conn.execute(
+        "UPDATE clipboard_history SET pinned = 1, content_type = ?1 WHERE id = ?2",
+        params![CONTENT_TYPE_TEMPLATE, id],
+    )?;
+    Ok(())
+}
+
+/// Each `{{name}}` placeholder in `content` as `(start, end, name)` byte ranges. Names
+/// are letters, digits and `_`, optionally padded with spaces: `{{ table_name }}`.
+fn template_placeholders(content: &str) -> Vec<(usize, usize, &str)> {
+    let mut placeholders = Vec::new();
+    let mut from = 0;
+    while let Some(open) = content[from..].find("{{").map(|i| from + i) {
+        let Some(close) = content[open + 2..].find("}}").map(|i| open + 2 + i) else {
+            break;
+        };
+        let name = content[open + 2..close].trim();
+        if !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            placeholders.push((open, close + 2, name));
+            from = close + 2;
+        } else {
+            // Not a placeholder; a later `{{` may still start one
+            from = open + 1;
+        }
+    }
+    placeholders
+}
+
+/// Placeholder names in `content`, in order of first appearance
+pub fn extract_template_vars(content: &str) -> Vec<String> {
+    let mut vars: Vec<String> = Vec::new();
+    for (_, _, name) in template_placeholders(content) {
+        if !vars.iter().any(|v| v == name) {
+            vars.push(name.to_string());
+        }
+    }
+    vars
+}
+
+/// `content` with each placeholder replaced by its value; placeholders missing from
+/// `vars` are left as they are
+pub fn instantiate_template(content: &str, vars: &HashMap<String, String>) -> String {
+    let mut result = String::with_capacity(content.len());
+    let mut last = 0;
+    for (start, end, name) in template_placeholders(content) {
+        if let Some(value) = vars.get(name) {
+            result.push_str(&content[last..start]);
+            result.push_str(value);
+            last = end;
+        }
+    }
+    result.push_str(&content[last..]);
+    result
+}
+
 /// Soft delete entry
 pub fn delete_entry(conn: &Connection, id: i64) -> Result<()> {
     conn.execute(
         "UPDATE clipboard_history SET deleted = 1 WHERE id = ?1",
         params![id],
     )?;
+    conn.execute("DELETE FROM clipboard_fts WHERE rowid = ?1", params![id])?;
     Ok(())
 }
 
-/// Cleanup old entries (older than 24 hours, not pinned)
+/// Cleanup old entries (older than 24 hours, or 1 hour for primary selections, not pinned)
 pub fn cleanup_expired(conn: &Connection) -> Result<usize> {
     let cutoff = (Utc::now() - chrono::Duration::hours(24)).timestamp();
+    let primary_cutoff = (Utc::now() - chrono::Duration::hours(1)).timestamp();
     let deleted = conn.execute(
-        "DELETE FROM clipboard_history WHERE created_at < ?1 AND pinned = 0",
-        params![cutoff],
+        "DELETE FROM clipboard_history
+         WHERE pinned = 0
+           AND (created_at < ?1 OR (content_type = ?2 AND created_at < ?3))",
+        params![cutoff, CONTENT_TYPE_PRIMARY, primary_cutoff],
+    )?;
+    conn.execute(
+        "DELETE FROM clipboard_fts WHERE rowid NOT IN (SELECT id FROM clipboard_history)",
+        [],
     )?;
     Ok(deleted)
 }
 
+/// Guess what kind of text was copied, falling back to plain text
+pub fn detect_content_type(content: &str) -> &'static str {
+    let trimmed = content.trim();
+
+    if parse_hex_color(trimmed).is_some() {
+        CONTENT_TYPE_COLOR
+    } else if is_uuid(trimmed) {
+        CONTENT_TYPE_UUID
+    } else if trimmed.starts_with("http://") || trimmed.starts_with("https://") {
+        CONTENT_TYPE_URL
+    } else if is_email(trimmed) {
+        CONTENT_TYPE_EMAIL
+    } else if (trimmed.starts_with('{') || trimmed.starts_with('['))
+        && serde_json::from_str::<serde_json::Value>(trimmed).is_ok()
+    {
+        CONTENT_TYPE_JSON
+    } else if !trimmed.contains('\n') && std::path::Path::new(trimmed).exists() {
+        CONTENT_TYPE_FILE_PATH
+    } else {
+        CONTENT_TYPE_TEXT
+    }
+}
+
+/// Parse `#RRGGBB` into its RGB components
+pub fn parse_hex_color(s: &str) -> Option<(u8, u8, u8)> {
+    let hex = s.strip_prefix('#')?;
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some((channel(0)?, channel(2)?, channel(4)?))
+}
+
+fn is_uuid(s: &str) -> bool {
+    let groups: Vec<&str> = s.split('-').collect();
+    groups.len() == 5
+        && groups
+            .iter()
+            .zip([8, 4, 4, 4, 12])
+            .all(|(g, len)| g.len() == len && g.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// Loose `\S+@\S+\.\S+` match
+fn is_email(s: &str) -> bool {
+    if s.chars().any(char::is_whitespace) {
+        return false;
+    }
+    let Some((local, domain)) = s.split_once('@') else {
+        return false;
+    };
+    match domain.rsplit_once('.') {
+        Some((host, tld)) => !local.is_empty() && !host.is_empty() && !tld.is_empty(),
+        None => false,
+    }
+}
+
+/// Read the current clipboard text with a short-lived handle
+pub fn read_clipboard_text() -> Option<String> {
+    Clipboard::new().ok()?.get_text().ok()
+}
+
 /// Copy content back to clipboard
 pub fn copy_to_clipboard(content: &str) -> anyhow::Result<()> {
     let mut clipboard = Clipboard::new()?;
@@ -128,14 +413,61 @@ pub fn copy_to_clipboard(content: &str) -> anyhow::Result<()> {
     Ok(())
 }
 
-/// Clipboard monitor that runs in background thread
+/// Write `entries` to a timestamped text file in the Downloads folder (or home),
+/// separated by blank lines. Returns the file's path.
+pub fn export_entries(entries: &[&ClipboardEntry]) -> anyhow::Result<PathBuf> {
+    let dir = dirs::download_dir()
+        .or_else(dirs::home_dir)
+        .ok_or_else(|| anyhow::anyhow!("No home directory"))?;
+    let path = dir.join(format!(
+        "filecast-clipboard-{}.txt",
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    let content: Vec<&str> = entries.iter().map(|e| e.content.as_str()).collect();
+    std::fs::write(&path, content.join("\n\n"))?;
+    Ok(path)
+}
+
+/// Clipboard monitor that runs in background thread.
+/// Sends `(content, content_type)` pairs for each new clipboard value.
 pub struct ClipboardMonitor {
-    pub receiver: Receiver<String>,
+    pub receiver: Receiver<(String, &'static str)>,
+    primary_enabled: Arc<AtomicBool>,
+    /// Woken after each send so the UI doesn't have to poll for new entries
+    repaint: Arc<OnceLock<eframe::egui::Context>>,
 }
 
 impl ClipboardMonitor {
     pub fn start() -> Self {
-        let (tx, rx): (Sender<String>, Receiver<String>) = channel();
+        let primary_enabled = Arc::new(AtomicBool::new(false));
+        let repaint = Arc::new(OnceLock::new());
+
+        ClipboardMonitor {
+            receiver: Self::spawn(Arc::clone(&primary_enabled), Arc::clone(&repaint)),
+            primary_enabled,
+            repaint,
+        }
+    }
+
+    /// Request a repaint of `ctx` whenever a new entry arrives
+    pub fn set_repaint_context(&self, ctx: eframe::egui::Context) {
+        let _ = self.repaint.set(ctx);
+    }
+
+    /// Replace the monitor thread with a fresh one. The old thread exits on its next
+    /// send once its receiver is dropped.
+    pub fn restart(&mut self) {
+        self.receiver = Self::spawn(Arc::clone(&self.primary_enabled), Arc::clone(&self.repaint));
+    }
+
+    fn spawn(
+        watch_primary: Arc<AtomicBool>,
+        repaint: Arc<OnceLock<eframe::egui::Context>>,
+    ) -> Receiver<(String, &'static str)> {
+        let (tx, rx): (
+            Sender<(String, &'static str)>,
+            Receiver<(String, &'static str)>,
+        ) = channel();
 
         thread::spawn(move || {
             let mut clipboard = match Clipboard::new() {
@@ -144,23 +476,79 @@ impl ClipboardMonitor {
             };
 
             let mut last_content = clipboard.get_text().unwrap_or_default();
+            let mut last_primary = get_primary_text(&mut clipboard).unwrap_or_default();
+            let mut read_failed = false;
 
             loop {
                 thread::sleep(Duration::from_millis(500));
 
-                if let Ok(current) = clipboard.get_text() {
-                    if current != last_content && !current.is_empty() {
-                        last_content = current.clone();
-                        let _ = tx.send(current);
+                match clipboard.get_text() {
+                    Ok(current) => {
+                        read_failed = false;
+                        if current != last_content && !current.is_empty() {
+                            last_content = current.clone();
+                            if tx.send((current, CONTENT_TYPE_TEXT)).is_err() {
+                                return;
+                            }
+                            if let Some(ctx) = repaint.get() {
+                                ctx.request_repaint();
+                            }
+                        }
+                    }
+                    // Images and other non-text content; only text is recorded
+                    Err(arboard::Error::ContentNotAvailable) => read_failed = false,
+                    Err(e) => {
+                        // e.g. text that isn't valid UTF-8; log once rather than every poll
+                        if !read_failed {
+                            tracing::debug!("Skipping unreadable clipboard content: {}", e);
+                            read_failed = true;
+                        }
+                    }
+                }
+
+                if watch_primary.load(Ordering::Relaxed) {
+                    if let Some(current) = get_primary_text(&mut clipboard) {
+                        // Copying a selection updates both; keep only the regular entry
+                        if current != last_primary && current != last_content && !current.is_empty()
+                        {
+                            last_primary = current.clone();
+                            if tx.send((current, CONTENT_TYPE_PRIMARY)).is_err() {
+                                return;
+                            }
+                            if let Some(ctx) = repaint.get() {
+                                ctx.request_repaint();
+                            }
+                        }
                     }
                 }
             }
         });
 
-        ClipboardMonitor { receiver: rx }
+        rx
+    }
+
+    /// Enable or disable watching the X11 primary selection
+    pub fn set_primary_selection(&self, enabled: bool) {
+        self.primary_enabled.store(enabled, Ordering::Relaxed);
     }
 }
 
+#[cfg(target_os = "linux")]
+fn get_primary_text(clipboard: &mut Clipboard) -> Option<String> {
+    use arboard::{GetExtLinux, LinuxClipboardKind};
+
+    clipboard
+        .get()
+        .clipboard(LinuxClipboardKind::Primary)
+        .text()
+        .ok()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn get_primary_text(_clipboard: &mut Clipboard) -> Option<String> {
+    None
+}
+
 /// Format time ago for display
 pub fn format_time_ago(time: DateTime<Utc>) -> String {
     let now = Utc::now();
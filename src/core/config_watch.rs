@@ -0,0 +1,81 @@
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, Instant};
+
+use crate::core::search_config::SearchConfig;
+use crate::core::settings::{self, LauncherSettings};
+
+/// Quiet period after the last write before a file is reloaded
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConfigFile {
+    Settings,
+    SearchConfig,
+}
+
+/// Watches the config directory for edits to `settings.conf` and `search.yaml`
+pub struct ConfigWatcher {
+    _watcher: RecommendedWatcher,
+    rx: Receiver<ConfigFile>,
+    pending: Vec<(ConfigFile, Instant)>,
+}
+
+impl ConfigWatcher {
+    pub fn start() -> notify::Result<Self> {
+        let settings_path = LauncherSettings::config_path();
+        let search_path = SearchConfig::config_path();
+        let (tx, rx) = mpsc::channel();
+
+        let mut watcher =
+            notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+                let Ok(event) = res else {
+                    return;
+                };
+                // Editors often save by writing a new file and renaming it over the old one
+                if !matches!(event.kind, EventKind::Modify(_) | EventKind::Create(_)) {
+                    return;
+                }
+                for path in &event.paths {
+                    if *path == settings_path {
+                        let _ = tx.send(ConfigFile::Settings);
+                    } else if *path == search_path {
+                        let _ = tx.send(ConfigFile::SearchConfig);
+                    }
+                }
+            })?;
+
+        // Watch the directory rather than the files so replaced files keep being tracked
+        let dir = settings::config_dir();
+        std::fs::create_dir_all(&dir)?;
+        watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+
+        Ok(ConfigWatcher {
+            _watcher: watcher,
+            rx,
+            pending: Vec::new(),
+        })
+    }
+
+    /// Files whose last change is older than the debounce window and should be reloaded now
+    pub fn poll(&mut self) -> Vec<ConfigFile> {
+        while let Ok(file) = self.rx.try_recv() {
+            let now = Instant::now();
+            match self.pending.iter_mut().find(|(f, _)| *f == file) {
+                Some((_, last)) => *last = now,
+                None => self.pending.push((file, now)),
+            }
+        }
+
+        let mut ready = Vec::new();
+        self.pending.retain(|(file, last)| {
+            if last.elapsed() >= DEBOUNCE {
+                ready.push(*file);
+                false
+            } else {
+                true
+            }
+        });
+        ready
+    }
+}
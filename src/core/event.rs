@@ -0,0 +1,8 @@
+use crate::core::apps::DesktopApp;
+
+/// Changes picked up in the background and applied to `App` on the UI thread
+#[derive(Debug, Clone)]
+pub enum AppEvent {
+    /// The full application list after a `.desktop` file was added, changed or removed
+    ApplicationsChanged(Vec<DesktopApp>),
+}
@@ -1,6 +1,11 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::io::{self, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::thread;
 use std::time::SystemTime;
 
 #[derive(Debug, Clone)]
@@ -10,11 +15,26 @@ pub struct DirEntry {
     pub is_dir: bool,
     pub size: u64,
     pub modified: Option<SystemTime>,
+    /// Where the entry points if it is a symlink; `is_dir` and `size` describe the target
+    pub symlink_target: Option<PathBuf>,
+    /// A symlink whose target doesn't exist
+    pub broken_link: bool,
 }
 
 impl DirEntry {
     pub fn from_path(path: PathBuf) -> Result<Self> {
-        let metadata = fs::metadata(&path)?;
+        let link_metadata = fs::symlink_metadata(&path)?;
+        let symlink_target = if link_metadata.file_type().is_symlink() {
+            fs::read_link(&path).ok()
+        } else {
+            None
+        };
+        // Follow links for the target's type and size; a broken link keeps its own metadata
+        let (metadata, broken_link) = match fs::metadata(&path) {
+            Ok(metadata) => (metadata, false),
+            Err(_) if symlink_target.is_some() => (link_metadata, true),
+            Err(e) => return Err(e.into()),
+        };
         let name = path
             .file_name()
             .unwrap_or_default()
@@ -27,13 +47,60 @@ impl DirEntry {
             is_dir: metadata.is_dir(),
             size: metadata.len(),
             modified: metadata.modified().ok(),
+            symlink_target,
+            broken_link,
         })
     }
+
+    /// Listing order before name: directories, directory symlinks, files, file
+    /// symlinks, then broken symlinks
+    pub fn sort_tier(&self) -> u8 {
+        match (&self.symlink_target, self.is_dir) {
+            (None, true) => 0,
+            (Some(_), true) => 1,
+            (None, false) => 2,
+            (Some(_), false) if !self.broken_link => 3,
+            (Some(_), false) => 4,
+        }
+    }
+
+    /// Placeholder for an entry that is listed but can't be stat'd
+    pub fn inaccessible(path: PathBuf) -> Self {
+        let name = path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+
+        DirEntry {
+            path,
+            name,
+            is_dir: false,
+            size: 0,
+            modified: None,
+            symlink_target: None,
+            broken_link: false,
+        }
+    }
+}
+
+/// Stat one entry, falling back to a placeholder plus a description of the failure
+fn stat_entry(path: PathBuf) -> (DirEntry, Option<String>) {
+    match DirEntry::from_path(path.clone()) {
+        Ok(entry) => (entry, None),
+        Err(e) => {
+            let error = format!("{}: {}", path.display(), e);
+            (DirEntry::inaccessible(path), Some(error))
+        }
+    }
 }
 
-// Function to read a directory and return a vector of DirEntry
-pub fn read_directory(path: &Path, show_hidden: bool) -> Result<Vec<DirEntry>> {
+// Function to read a directory and return a vector of DirEntry. Entries that can't be
+// read are kept as placeholders where possible; their errors are returned alongside.
+// Only a failure to open the directory itself is an error.
+pub fn read_directory(path: &Path, show_hidden: bool) -> Result<(Vec<DirEntry>, Vec<String>)> {
     let mut entries = Vec::new();
+    let mut errors = Vec::new();
 
     if path.parent().is_some() {
         entries.push(DirEntry {
@@ -42,34 +109,538 @@ pub fn read_directory(path: &Path, show_hidden: bool) -> Result<Vec<DirEntry>> {
             is_dir: true,
             size: 0,
             modified: None,
+            symlink_target: None,
+            broken_link: false,
         });
     }
 
-    for entry in fs::read_dir(path)? {
-        let entry = entry?;
-        let path = entry.path();
+    let paths: Vec<PathBuf> = fs::read_dir(path)?
+        .filter_map(|entry| match entry {
+            Ok(e) => Some(e.path()),
+            Err(e) => {
+                errors.push(e.to_string());
+                None
+            }
+        })
+        .filter(|path| {
+            show_hidden
+                || !path
+                    .file_name()
+                    .map_or(false, |s| s.to_string_lossy().starts_with('.'))
+                || path
+                    .file_name()
+                    .map_or(false, |s| s.to_string_lossy() == "..")
+        })
+        .collect();
 
-        if !show_hidden
-            && path
-                .file_name()
-                .map_or(false, |s| s.to_string_lossy().starts_with('.'))
-            && path
-                .file_name()
-                .map_or(false, |s| s.to_string_lossy() != "..")
-        {
-            continue;
+    // Stat entries in parallel: slow mounts (NFS, SSHFS) pay a round trip per entry
+    #[cfg(feature = "rayon")]
+    let stats: Vec<(DirEntry, Option<String>)> = {
+        use rayon::prelude::*;
+        paths.into_par_iter().map(stat_entry).collect()
+    };
+    #[cfg(not(feature = "rayon"))]
+    let stats: Vec<(DirEntry, Option<String>)> = paths.into_iter().map(stat_entry).collect();
+
+    for (entry, error) in stats {
+        entries.push(entry);
+        errors.extend(error);
+    }
+
+    entries.sort_by(|a, b| {
+        (a.name != "..", a.sort_tier(), &a.name).cmp(&(b.name != "..", b.sort_tier(), &b.name))
+    });
+
+    Ok((entries, errors))
+}
+
+/// Patterns from the nearest `.gitignore` at or above `start_dir`, without comments
+/// or blank lines. Empty when there is none.
+pub fn load_gitignore_patterns(start_dir: &Path) -> Vec<String> {
+    let Some(content) = start_dir
+        .ancestors()
+        .find_map(|dir| fs::read_to_string(dir.join(".gitignore")).ok())
+    else {
+        return Vec::new();
+    };
+
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortOrder {
+    #[default]
+    Name,
+    Size,
+    Date,
+}
+
+impl SortOrder {
+    pub fn next(self) -> Self {
+        match self {
+            SortOrder::Name => SortOrder::Size,
+            SortOrder::Size => SortOrder::Date,
+            SortOrder::Date => SortOrder::Name,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SortOrder::Name => "Name",
+            SortOrder::Size => "Size",
+            SortOrder::Date => "Date",
         }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortDirection {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl SortDirection {
+    pub fn toggle(self) -> Self {
+        match self {
+            SortDirection::Asc => SortDirection::Desc,
+            SortDirection::Desc => SortDirection::Asc,
+        }
+    }
 
-        if let Ok(dir_entry) = DirEntry::from_path(path) {
-            entries.push(dir_entry);
+    pub fn label(self) -> &'static str {
+        match self {
+            SortDirection::Asc => "ascending",
+            SortDirection::Desc => "descending",
         }
     }
+}
 
-    entries.sort_by(|a, b| match (a.is_dir, b.is_dir) {
-        (true, false) => std::cmp::Ordering::Less,
-        (false, true) => std::cmp::Ordering::Greater,
-        _ => a.name.cmp(&b.name),
+/// Sort a directory listing in place. `..` stays first and directories stay above files.
+pub fn sort_entries(entries: &mut [DirEntry], order: SortOrder, direction: SortDirection) {
+    entries.sort_by(|a, b| {
+        let rank = |e: &DirEntry| (e.name != "..", e.sort_tier());
+        let by_key = match order {
+            SortOrder::Name => a.name.cmp(&b.name),
+            SortOrder::Size => a.size.cmp(&b.size).then_with(|| a.name.cmp(&b.name)),
+            SortOrder::Date => a
+                .modified
+                .cmp(&b.modified)
+                .then_with(|| a.name.cmp(&b.name)),
+        };
+        let by_key = match direction {
+            SortDirection::Asc => by_key,
+            SortDirection::Desc => by_key.reverse(),
+        };
+        rank(a).cmp(&rank(b)).then(by_key)
     });
+}
+
+/// `path` with the home directory shown as `~`, e.g. `~/projects/myapp`
+pub fn abbreviate_path(path: &Path) -> String {
+    match dirs::home_dir().and_then(|home| path.strip_prefix(home).ok().map(Path::to_path_buf)) {
+        Some(rest) if rest.as_os_str().is_empty() => "~".to_string(),
+        Some(rest) => format!("~/{}", rest.display()),
+        None => path.display().to_string(),
+    }
+}
+
+/// `(label, directory)` for each breadcrumb of `path`, starting from `~` when under home
+pub fn breadcrumbs(path: &Path) -> Vec<(String, PathBuf)> {
+    let home = dirs::home_dir().filter(|home| path.starts_with(home));
+    let (mut crumbs, mut current, rest) = match &home {
+        Some(home) => (
+            vec![("~".to_string(), home.clone())],
+            home.clone(),
+            path.strip_prefix(home).unwrap_or(Path::new("")),
+        ),
+        None => (Vec::new(), PathBuf::new(), path),
+    };
+
+    for component in rest.components() {
+        current.push(component);
+        let label = match component {
+            Component::RootDir => "/".to_string(),
+            other => other.as_os_str().to_string_lossy().into_owned(),
+        };
+        crumbs.push((label, current.clone()));
+    }
+    crumbs
+}
+
+const COPY_CHUNK_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FileOpKind {
+    Copy,
+    Move,
+}
+
+/// Shared flag a background file operation checks between chunks
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// A copy or move running on a background thread, reporting progress from 0.0 to 1.0.
+/// The operation is finished once `progress_rx` disconnects.
+pub struct FileOperation {
+    pub kind: FileOpKind,
+    pub src: PathBuf,
+    pub dst: PathBuf,
+    pub progress: f32,
+    pub progress_rx: Receiver<f32>,
+    pub cancel: CancellationToken,
+}
+
+impl FileOperation {
+    pub fn start(kind: FileOpKind, src: PathBuf, dst: PathBuf) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let cancel = CancellationToken::default();
+
+        let (thread_src, thread_dst, thread_cancel) = (src.clone(), dst.clone(), cancel.clone());
+        thread::spawn(move || {
+            if let Err(e) = run_file_operation(kind, &thread_src, &thread_dst, &tx, &thread_cancel)
+            {
+                tracing::warn!("{:?} {} failed: {}", kind, thread_src.display(), e);
+            }
+        });
+
+        FileOperation {
+            kind,
+            src,
+            dst,
+            progress: 0.0,
+            progress_rx: rx,
+            cancel,
+        }
+    }
+
+    /// Drain progress updates. Returns false once the operation has finished.
+    pub fn poll(&mut self) -> bool {
+        loop {
+            match self.progress_rx.try_recv() {
+                Ok(progress) => self.progress = progress,
+                Err(TryRecvError::Empty) => return true,
+                Err(TryRecvError::Disconnected) => return false,
+            }
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.progress >= 1.0
+    }
+}
+
+fn run_file_operation(
+    kind: FileOpKind,
+    src: &Path,
+    dst: &Path,
+    tx: &Sender<f32>,
+    cancel: &CancellationToken,
+) -> io::Result<()> {
+    ensure_not_inside(src, dst)?;
+
+    // A rename is instant when source and destination share a filesystem; only a
+    // cross-device move falls back to copying
+    if kind == FileOpKind::Move {
+        match fs::rename(src, dst) {
+            Ok(()) => {
+                let _ = tx.send(1.0);
+                return Ok(());
+            }
+            Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {}
+            Err(e) => return Err(e),
+        }
+    }
+
+    let created_dst = !dst.exists();
+    let mut files = Vec::new();
+    collect_files(src, dst, &mut files)?;
+    let total: u64 = files.iter().map(|(_, _, size)| size).sum::<u64>().max(1);
+
+    let mut written: u64 = 0;
+    let mut buf = vec![0u8; COPY_CHUNK_SIZE];
+    for (from, to, _) in &files {
+        if let Some(parent) = to.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        if from.is_symlink() {
+            copy_symlink(from, to)?;
+            continue;
+        }
+        let mut reader = fs::File::open(from)?;
+        let mut writer = fs::File::create(to)?;
+
+        loop {
+            if cancel.is_cancelled() {
+                drop(writer);
+                // Don't leave a half-copied tree behind
+                if src.is_dir() && created_dst {
+                    let _ = fs::remove_dir_all(dst);
+                } else {
+                    let _ = fs::remove_file(to);
+                }
+                return Err(io::Error::new(io::ErrorKind::Interrupted, "cancelled"));
+            }
+
+            let n = reader.read(&mut buf)?;
+            if n == 0 {
+                break;
+            }
+            writer.write_all(&buf[..n])?;
+            written += n as u64;
+            let _ = tx.send(written as f32 / total as f32);
+        }
+        copy_metadata(&reader, &writer)?;
+    }
+
+    // Empty directories still need to exist at the destination
+    if src.is_dir() {
+        fs::create_dir_all(dst)?;
+    }
+
+    if kind == FileOpKind::Move {
+        if src.is_dir() {
+            fs::remove_dir_all(src)?;
+        } else {
+            fs::remove_file(src)?;
+        }
+    }
+
+    let _ = tx.send(1.0);
+    Ok(())
+}
+
+/// Move `src` into `dst_dir`, appending `_1`, `_2`, ... to the name if it is taken.
+/// Renames when possible and falls back to copying across filesystems.
+/// Returns where the file ended up.
+pub fn move_to_directory(src: &Path, dst_dir: &Path) -> Result<PathBuf> {
+    if src.parent() == Some(dst_dir) {
+        return Ok(src.to_path_buf());
+    }
+    ensure_not_inside(src, dst_dir)?;
+    let name = src
+        .file_name()
+        .with_context(|| format!("{} has no file name", src.display()))?;
+    let dst = unique_destination(&dst_dir.join(name));
+    rename_or_copy(src, &dst)?;
+    Ok(dst)
+}
+
+/// Rename `src` to `dst`, copying then deleting when they are on different filesystems
+pub fn rename_or_copy(src: &Path, dst: &Path) -> io::Result<()> {
+    match fs::rename(src, dst) {
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices => copy_then_delete(src, dst),
+        result => result,
+    }
+}
+
+/// Put the most recently trashed item that came from each of `paths` back where it was.
+/// Nothing is restored if any of them is missing from the trash.
+pub fn restore_from_trash(paths: &[PathBuf]) -> Result<()> {
+    let trashed = trash::os_limited::list()?;
+    let items = paths
+        .iter()
+        .map(|path| {
+            trashed
+                .iter()
+                .filter(|item| item.original_path() == *path)
+                .max_by_key(|item| item.time_deleted)
+                .cloned()
+                .with_context(|| format!("{} is no longer in the trash", path.display()))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    trash::os_limited::restore_all(items)?;
+    Ok(())
+}
+
+/// `path` if nothing exists there, otherwise the first free `name_N.ext` next to it
+fn unique_destination(path: &Path) -> PathBuf {
+    if !path.exists() {
+        return path.to_path_buf();
+    }
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = path.extension().map(|e| e.to_string_lossy());
+    (1..)
+        .map(|n| {
+            let name = match &extension {
+                Some(ext) => format!("{}_{}.{}", stem, n, ext),
+                None => format!("{}_{}", stem, n),
+            };
+            path.with_file_name(name)
+        })
+        .find(|candidate| !candidate.exists())
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+/// Copy `src` to `dst` in `COPY_CHUNK_SIZE` chunks, then remove `src`
+fn copy_then_delete(src: &Path, dst: &Path) -> io::Result<()> {
+    let mut files = Vec::new();
+    collect_files(src, dst, &mut files)?;
+
+    let mut buf = vec![0u8; COPY_CHUNK_SIZE];
+    for (from, to, _) in &files {
+        if let Some(parent) = to.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        if from.is_symlink() {
+            copy_symlink(from, to)?;
+            continue;
+        }
+        let mut reader = fs::File::open(from)?;
+        let mut writer = fs::File::create(to)?;
+        loop {
+            let n = reader.read(&mut buf)?;
+            if n == 0 {
+                break;
+            }
+            writer.write_all(&buf[..n])?;
+        }
+        copy_metadata(&reader, &writer)?;
+    }
+
+    if src.is_dir() {
+        fs::create_dir_all(dst)?;
+        fs::remove_dir_all(src)
+    } else {
+        fs::remove_file(src)
+    }
+}
+
+/// Give a copied file the permissions and modification time of the original, so a
+/// moved script stays executable
+fn copy_metadata(from: &fs::File, to: &fs::File) -> io::Result<()> {
+    let metadata = from.metadata()?;
+    to.set_permissions(metadata.permissions())?;
+    to.set_modified(metadata.modified()?)
+}
+
+/// Copying or moving a directory into itself would recurse forever, and the move's
+/// cleanup would then delete both copies
+fn ensure_not_inside(src: &Path, dst: &Path) -> io::Result<()> {
+    if src.is_dir() && dst.starts_with(src) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} can't go inside itself", src.display()),
+        ));
+    }
+    Ok(())
+}
+
+/// Recreate the link at `from` as `to`, pointing at the same target
+fn copy_symlink(from: &Path, to: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(from)?, to)
+}
+
+/// Files under `src` with where each goes under `dst`. Symlinks are listed as-is
+/// (size 0) rather than followed, so linked directories can't loop.
+fn collect_files(
+    src: &Path,
+    dst: &Path,
+    files: &mut Vec<(PathBuf, PathBuf, u64)>,
+) -> io::Result<()> {
+    let metadata = fs::symlink_metadata(src)?;
+    if metadata.file_type().is_symlink() {
+        files.push((src.to_path_buf(), dst.to_path_buf(), 0));
+    } else if metadata.is_dir() {
+        for entry in fs::read_dir(src)? {
+            let entry = entry?;
+            collect_files(&entry.path(), &dst.join(entry.file_name()), files)?;
+        }
+    } else {
+        files.push((src.to_path_buf(), dst.to_path_buf(), metadata.len()));
+    }
+    Ok(())
+}
 
-    Ok(entries)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn abbreviate_path_home_is_tilde() {
+        let Some(home) = dirs::home_dir() else { return };
+        assert_eq!(abbreviate_path(&home), "~");
+    }
+
+    #[test]
+    fn abbreviate_path_under_home() {
+        let Some(home) = dirs::home_dir() else { return };
+        assert_eq!(
+            abbreviate_path(&home.join("projects/myapp")),
+            "~/projects/myapp"
+        );
+    }
+
+    #[test]
+    fn abbreviate_path_outside_home_is_unchanged() {
+        let path = Path::new("/definitely-not-home/projects");
+        assert_eq!(abbreviate_path(path), "/definitely-not-home/projects");
+    }
+
+    #[test]
+    fn abbreviate_path_root() {
+        assert_eq!(abbreviate_path(Path::new("/")), "/");
+    }
+
+    #[test]
+    fn read_directory_tiers_symlinks() {
+        use std::os::unix::fs::symlink;
+
+        let dir = std::env::temp_dir().join(format!("filecast-tiers-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("zdir")).unwrap();
+        fs::write(dir.join("afile"), "x").unwrap();
+        symlink(dir.join("zdir"), dir.join("dlink")).unwrap();
+        symlink(dir.join("afile"), dir.join("flink")).unwrap();
+        symlink(dir.join("missing"), dir.join("broken")).unwrap();
+
+        let (entries, errors) = read_directory(&dir, false).unwrap();
+        let _ = fs::remove_dir_all(&dir);
+
+        let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["..", "zdir", "dlink", "afile", "flink", "broken"]);
+        let tiers: Vec<u8> = entries[1..].iter().map(DirEntry::sort_tier).collect();
+        assert_eq!(tiers, [0, 1, 2, 3, 4]);
+        assert!(entries[5].broken_link);
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn copy_then_delete_keeps_mode_and_mtime() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("filecast-xdev-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("bin")).unwrap();
+        let src = dir.join("bin").join("run.sh");
+        fs::write(&src, "#!/bin/sh\n").unwrap();
+        fs::set_permissions(&src, fs::Permissions::from_mode(0o750)).unwrap();
+        let mtime = std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000);
+        let file = fs::File::options().write(true).open(&src).unwrap();
+        file.set_modified(mtime).unwrap();
+
+        let dst = dir.join("moved");
+        copy_then_delete(&dir.join("bin"), &dst).unwrap();
+        let metadata = fs::metadata(dst.join("run.sh")).unwrap();
+        let _ = fs::remove_dir_all(&dir);
+
+        assert!(!src.exists());
+        assert_eq!(metadata.permissions().mode() & 0o777, 0o750);
+        assert_eq!(metadata.modified().unwrap(), mtime);
+    }
 }
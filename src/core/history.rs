@@ -1,5 +1,6 @@
 use chrono::{DateTime, TimeZone, Utc};
-use rusqlite::{Connection, Result, params};
+use rusqlite::{Connection, ErrorCode, Result, params};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 #[derive(Clone)]
@@ -7,6 +8,37 @@ pub struct RecentAccess {
     pub path: PathBuf,
     pub last_accessed: DateTime<Utc>,
     pub access_count: i32,
+    /// Decaying access frequency: each access multiplies it by 0.9 and adds 1
+    pub frequency_score: f64,
+}
+
+/// Weight of the previous score when an access is logged
+const FREQUENCY_DECAY: f64 = 0.9;
+
+/// `PRAGMA user_version` from which recent_access paths are stored canonicalized
+const SCHEMA_VERSION_CANONICAL_PATHS: i32 = 2;
+
+/// How recent files are ordered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RecentOrder {
+    #[default]
+    LastAccessed,
+    FrequencyScore,
+    /// Frequency score divided by `1 + days since the last access`
+    Combined,
+}
+
+impl RecentOrder {
+    /// ORDER BY clause; `?now` is bound to the current timestamp
+    fn order_by(self) -> &'static str {
+        match self {
+            RecentOrder::LastAccessed => "last_accessed DESC",
+            RecentOrder::FrequencyScore => "frequency_score DESC, last_accessed DESC",
+            RecentOrder::Combined => {
+                "frequency_score / (1.0 + (:now - last_accessed) / 86400.0) DESC, last_accessed DESC"
+            }
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -25,16 +57,25 @@ pub struct AppLaunchHistory {
     pub launch_count: i32,
 }
 
+#[tracing::instrument]
 pub fn initialise(db_path: &Path) -> Result<Connection> {
     let conn = Connection::open(db_path)?;
     conn.execute(
         "CREATE TABLE IF NOT EXISTS recent_access (
             path TEXT PRIMARY KEY,
             last_accessed INTEGER NOT NULL,
-            access_count INTEGER NOT NULL
+            access_count INTEGER NOT NULL,
+            frequency_score REAL NOT NULL DEFAULT 0
         )",
         [],
     )?;
+    // Databases from before frequency_score start from their plain access counts
+    if !has_column(&conn, "recent_access", "frequency_score")? {
+        conn.execute_batch(
+            "ALTER TABLE recent_access ADD COLUMN frequency_score REAL NOT NULL DEFAULT 0;
+             UPDATE recent_access SET frequency_score = access_count * 1.0;",
+        )?;
+    }
     conn.execute(
         "CREATE TABLE IF NOT EXISTS command_history (
             command TEXT NOT NULL,
@@ -54,48 +95,239 @@ pub fn initialise(db_path: &Path) -> Result<Connection> {
         )",
         [],
     )?;
+    // Full-text index over recent paths, kept in sync by triggers; rowid = recent_access.rowid
+    conn.execute_batch(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS recent_access_fts USING fts5(path);
+         CREATE TRIGGER IF NOT EXISTS recent_access_fts_insert AFTER INSERT ON recent_access BEGIN
+             INSERT INTO recent_access_fts (rowid, path) VALUES (new.rowid, new.path);
+         END;
+         CREATE TRIGGER IF NOT EXISTS recent_access_fts_delete AFTER DELETE ON recent_access BEGIN
+             DELETE FROM recent_access_fts WHERE rowid = old.rowid;
+         END;
+         CREATE TRIGGER IF NOT EXISTS recent_access_fts_update AFTER UPDATE OF path ON recent_access BEGIN
+             UPDATE recent_access_fts SET path = new.path WHERE rowid = old.rowid;
+         END;
+         INSERT INTO recent_access_fts (rowid, path)
+             SELECT rowid, path FROM recent_access
+             WHERE rowid NOT IN (SELECT rowid FROM recent_access_fts);",
+    )?;
+
+    let version: i32 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    if version < SCHEMA_VERSION_CANONICAL_PATHS {
+        let merged = normalize_recent_access_paths(&conn)?;
+        if merged > 0 {
+            tracing::info!("Merged {} duplicate recent paths", merged);
+        }
+        conn.execute_batch(&format!(
+            "PRAGMA user_version = {}",
+            SCHEMA_VERSION_CANONICAL_PATHS
+        ))?;
+    }
+    Ok(conn)
+}
+
+/// `path` with `~/` expanded and symlinks, `.` and `..` resolved. Paths that don't
+/// exist can't be resolved and are returned as given.
+fn canonical_path_string(path: &Path) -> String {
+    let expanded = match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    };
+    std::fs::canonicalize(&expanded)
+        .unwrap_or(expanded)
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Rewrite recent_access paths to their canonical form, merging rows that turn out
+/// to be the same file: the merged row keeps the highest access count and score and
+/// the latest access time. Returns how many rows were merged away.
+fn normalize_recent_access_paths(conn: &Connection) -> Result<usize> {
+    let tx = conn.unchecked_transaction()?;
+
+    let rows = {
+        let mut stmt = tx.prepare(
+            "SELECT path, last_accessed, access_count, frequency_score FROM recent_access",
+        )?;
+        stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, i32>(2)?,
+                row.get::<_, f64>(3)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>>>()?
+    };
+
+    let mut groups: HashMap<String, Vec<(String, i64, i32, f64)>> = HashMap::new();
+    for row in rows {
+        groups
+            .entry(canonical_path_string(Path::new(&row.0)))
+            .or_default()
+            .push(row);
+    }
+
+    let mut merged = 0;
+    for (canonical, rows) in groups {
+        if rows.len() == 1 && rows[0].0 == canonical {
+            continue;
+        }
+
+        let last_accessed = rows.iter().map(|r| r.1).max().unwrap_or(0);
+        let access_count = rows.iter().map(|r| r.2).max().unwrap_or(0);
+        let frequency_score = rows.iter().map(|r| r.3).fold(0.0, f64::max);
+        for (path, ..) in &rows {
+            tx.execute("DELETE FROM recent_access WHERE path = ?1", params![path])?;
+        }
+        tx.execute(
+            "INSERT INTO recent_access (path, last_accessed, access_count, frequency_score)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![canonical, last_accessed, access_count, frequency_score],
+        )?;
+        merged += rows.len() - 1;
+    }
+
+    tx.commit()?;
+    Ok(merged)
+}
+
+fn has_column(conn: &Connection, table: &str, column: &str) -> Result<bool> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let names = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .collect::<Result<Vec<_>>>()?;
+    Ok(names.iter().any(|name| name == column))
+}
+
+/// Open the history database and create the clipboard table, moving a corrupt file
+/// aside and starting fresh if needed. Any other error (permissions, a locked file, a
+/// full disk) is returned as-is so a healthy database is never thrown away.
+/// Returns the connection and, if recovery happened, where the corrupt file was moved.
+pub fn open_with_recovery(db_path: &Path) -> anyhow::Result<(Connection, Option<PathBuf>)> {
+    match open_all_tables(db_path) {
+        Ok(conn) => Ok((conn, None)),
+        Err(err) if is_corruption(&err) => {
+            let backup = db_path.with_file_name(format!(
+                "{}.corrupt.{}",
+                db_path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| "history.db".to_string()),
+                Utc::now().timestamp()
+            ));
+            tracing::warn!(
+                "Failed to open database {} ({}), moving it to {}",
+                db_path.display(),
+                err,
+                backup.display()
+            );
+
+            std::fs::rename(db_path, &backup)?;
+            let conn = open_all_tables(db_path)?;
+            Ok((conn, Some(backup)))
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+fn open_all_tables(db_path: &Path) -> Result<Connection> {
+    let conn = initialise(db_path)?;
+    crate::core::clipboard::init_clipboard_table(&conn)?;
     Ok(conn)
 }
 
+fn is_corruption(err: &rusqlite::Error) -> bool {
+    matches!(
+        err.sqlite_error_code(),
+        Some(ErrorCode::DatabaseCorrupt | ErrorCode::NotADatabase)
+    )
+}
+
+/// Row counts for each history table
+#[derive(Debug, Clone, Default)]
+pub struct DbStats {
+    pub recent_access: i64,
+    pub clipboard_history: i64,
+    pub command_history: i64,
+    pub app_launch_history: i64,
+}
+
+pub fn db_stats(conn: &Connection) -> Result<DbStats> {
+    let count = |table: &str| {
+        conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| {
+            row.get::<_, i64>(0)
+        })
+    };
+
+    Ok(DbStats {
+        recent_access: count("recent_access")?,
+        clipboard_history: count("clipboard_history")?,
+        command_history: count("command_history")?,
+        app_launch_history: count("app_launch_history")?,
+    })
+}
+
+/// Compact the database file
+pub fn vacuum(conn: &Connection) -> Result<()> {
+    conn.execute_batch("PRAGMA optimize; PRAGMA vacuum;")?;
+    // VACUUM may renumber recent_access rowids, which the FTS index is keyed on
+    conn.execute_batch(
+        "DELETE FROM recent_access_fts;
+         INSERT INTO recent_access_fts (rowid, path) SELECT rowid, path FROM recent_access;",
+    )
+}
+
 pub fn log_access(conn: &Connection, path: &Path) -> Result<()> {
-    let path_str = path.to_string_lossy().to_string();
+    let path_str = canonical_path_string(path);
     let now = Utc::now().timestamp();
 
-    let mut stmt = conn.prepare("SELECT access_count FROM recent_access WHERE path = ?1")?;
+    let mut stmt =
+        conn.prepare("SELECT access_count, frequency_score FROM recent_access WHERE path = ?1")?;
     let mut rows = stmt.query(params![path_str])?;
 
     if let Some(row) = rows.next()? {
         let access_count: i32 = row.get(0)?;
+        let frequency_score: f64 = row.get(1)?;
         conn.execute(
-            "UPDATE recent_access SET last_accessed = ?1, access_count = ?2 WHERE path = ?3",
-            params![now, access_count + 1, path_str],
+            "UPDATE recent_access SET last_accessed = ?1, access_count = ?2, frequency_score = ?3
+             WHERE path = ?4",
+            params![
+                now,
+                access_count + 1,
+                frequency_score * FREQUENCY_DECAY + 1.0,
+                path_str
+            ],
         )?;
     } else {
         conn.execute(
-            "INSERT INTO recent_access (path, last_accessed, access_count) VALUES (?1, ?2, ?3)",
-            params![path_str, now, 1],
+            "INSERT INTO recent_access (path, last_accessed, access_count, frequency_score)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![path_str, now, 1, 1.0],
         )?;
     }
 
     Ok(())
 }
 
-pub fn get_recent_files(conn: &Connection, limit: u32) -> Result<Vec<RecentAccess>> {
-    let mut stmt = conn.prepare(
-        "SELECT path, last_accessed, access_count FROM recent_access ORDER BY last_accessed DESC LIMIT ?1",
-    )?;
+pub fn get_recent_files(
+    conn: &Connection,
+    limit: u32,
+    order_by: RecentOrder,
+) -> Result<Vec<RecentAccess>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT path, last_accessed, access_count, frequency_score FROM recent_access
+         ORDER BY {} LIMIT :limit",
+        order_by.order_by()
+    ))?;
 
-    let recent_files_iter = stmt.query_map(params![limit], |row| {
-        let path_str: String = row.get(0)?;
-        let last_accessed_ts: i64 = row.get(1)?;
-        let access_count: i32 = row.get(2)?;
-
-        Ok(RecentAccess {
-            path: PathBuf::from(path_str),
-            last_accessed: Utc.timestamp_opt(last_accessed_ts, 0).unwrap(),
-            access_count,
-        })
-    })?;
+    let now = Utc::now().timestamp();
+    let params: &[(&str, &dyn rusqlite::ToSql)] = if order_by == RecentOrder::Combined {
+        &[(":now", &now), (":limit", &limit)]
+    } else {
+        &[(":limit", &limit)]
+    };
+    let recent_files_iter = stmt.query_map(params, recent_access_from_row)?;
 
     let mut recent_files = Vec::new();
     for recent_file in recent_files_iter {
@@ -105,6 +337,95 @@ pub fn get_recent_files(conn: &Connection, limit: u32) -> Result<Vec<RecentAcces
     Ok(recent_files)
 }
 
+/// Map a `path, last_accessed, access_count, frequency_score` row
+fn recent_access_from_row(row: &rusqlite::Row) -> Result<RecentAccess> {
+    let path_str: String = row.get(0)?;
+    let last_accessed_ts: i64 = row.get(1)?;
+
+    Ok(RecentAccess {
+        path: PathBuf::from(path_str),
+        last_accessed: Utc.timestamp_opt(last_accessed_ts, 0).unwrap(),
+        access_count: row.get(2)?,
+        frequency_score: row.get(3)?,
+    })
+}
+
+/// Recent files whose path contains `query`, or matches all of its words as prefixes
+/// (so `proj rep` finds `~/projects/q3/reports.pdf`), most recent first
+pub fn search_recent_files(
+    conn: &Connection,
+    query: &str,
+    limit: u32,
+) -> Result<Vec<RecentAccess>> {
+    let query = query.trim();
+    if query.is_empty() {
+        return get_recent_files(conn, limit, RecentOrder::LastAccessed);
+    }
+
+    let like_pattern = format!(
+        "%{}%",
+        query
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_")
+    );
+    // Quote every word so FTS5 operators in the query are matched literally
+    let fts_query = query
+        .split_whitespace()
+        .map(|word| format!("\"{}\"*", word.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let mut stmt = conn.prepare(
+        "SELECT path, last_accessed, access_count, frequency_score FROM recent_access
+         WHERE path LIKE ?1 ESCAPE '\\'
+            OR rowid IN (SELECT rowid FROM recent_access_fts WHERE recent_access_fts MATCH ?2)
+         ORDER BY last_accessed DESC LIMIT ?3",
+    )?;
+
+    let iter = stmt.query_map(
+        params![like_pattern, fts_query, limit],
+        recent_access_from_row,
+    )?;
+
+    let mut matches = Vec::new();
+    for entry in iter {
+        matches.push(entry?);
+    }
+
+    Ok(matches)
+}
+
+/// Remove recent entries whose paths no longer exist, returning how many were removed
+pub fn clean_recent_history(conn: &Connection) -> Result<usize> {
+    let mut stmt = conn.prepare("SELECT path FROM recent_access")?;
+    let paths = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut removed = 0;
+    for path in paths.iter().filter(|p| !Path::new(p).exists()) {
+        removed += conn.execute("DELETE FROM recent_access WHERE path = ?1", params![path])?;
+    }
+
+    Ok(removed)
+}
+
+pub fn get_most_opened_files(conn: &Connection, limit: u32) -> Result<Vec<RecentAccess>> {
+    let mut stmt = conn.prepare(
+        "SELECT path, last_accessed, access_count, frequency_score FROM recent_access ORDER BY access_count DESC, last_accessed DESC LIMIT ?1",
+    )?;
+
+    let iter = stmt.query_map(params![limit], recent_access_from_row)?;
+
+    let mut most_opened = Vec::new();
+    for entry in iter {
+        most_opened.push(entry?);
+    }
+
+    Ok(most_opened)
+}
+
 pub fn log_command(conn: &Connection, command: &str, path: &Path) -> Result<()> {
     let path_str = path.to_string_lossy().to_string();
     let now = Utc::now().timestamp();
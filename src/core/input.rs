@@ -0,0 +1,49 @@
+use anyhow::{Result, bail};
+use std::process::Command;
+use std::thread;
+use std::time::Duration;
+
+use crate::core::search::on_path;
+
+/// Time for the clipboard owner to publish newly copied contents
+const CLIPBOARD_SETTLE_DELAY: Duration = Duration::from_millis(100);
+/// Time for the window manager to hide filecast and refocus the previous window
+const WINDOW_HIDE_DELAY: Duration = Duration::from_millis(150);
+
+/// Paste the clipboard into whichever window regains focus once filecast hides.
+/// The keystrokes are sent from a background thread after a short delay; an error is
+/// only returned when the tools needed to send them are missing.
+pub fn paste_to_previous_window() -> Result<()> {
+    if !on_path("xdotool") {
+        bail!("xdotool is not installed");
+    }
+
+    let mut command = if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        // Native Wayland windows ignore synthetic Ctrl+V, so type the text instead
+        if !on_path("wl-paste") {
+            bail!("wl-paste is not installed");
+        }
+        let mut command = Command::new("sh");
+        command.args([
+            "-c",
+            "wl-paste --no-newline | xdotool type --clearmodifiers --delay 0 --file -",
+        ]);
+        command
+    } else {
+        let mut command = Command::new("xdotool");
+        command.args(["key", "--clearmodifiers", "ctrl+v"]);
+        command
+    };
+
+    thread::spawn(move || {
+        thread::sleep(CLIPBOARD_SETTLE_DELAY + WINDOW_HIDE_DELAY);
+        match command.status() {
+            Ok(status) if !status.success() => {
+                tracing::warn!("Auto-paste command exited with {}", status)
+            }
+            Ok(_) => {}
+            Err(e) => tracing::warn!("Failed to run auto-paste command: {}", e),
+        }
+    });
+    Ok(())
+}
@@ -1,9 +1,14 @@
 pub mod app;
 pub mod apps;
+pub mod calc;
 pub mod clipboard;
+pub mod config_watch;
+pub mod event;
 pub mod fs;
 pub mod history;
+pub mod input;
 pub mod mode;
 pub mod search;
 pub mod search_config;
-pub mod settings;
\ No newline at end of file
+pub mod settings;
+pub mod ssh;
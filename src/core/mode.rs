@@ -1,10 +1,27 @@
 use std::fmt;
 
+/// Modes of the Files view. Every mode is entered from Normal and returns to it:
+///
+/// ```text
+///            EnterSearch            EnterCommand
+///   Search <------------- Normal -------------> Command
+///          -------------> |  ^ <-------------
+///         Confirm/Cancel  |  | Confirm/Cancel
+///                         |  |
+///              EnterVisual|  |Confirm/Cancel
+///                         v  |
+///                        Visual
+///
+///   Any mode but Quit --Quit--> Quit (final)
+/// ```
+///
+/// Moves go through [`transition`]; anything not drawn above is rejected.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AppMode {
     Normal,
     Search,
     Command,
+    Visual,
     Quit,
 }
 
@@ -14,7 +31,70 @@ impl fmt::Display for AppMode {
             AppMode::Normal => write!(f, "NORMAL"),
             AppMode::Search => write!(f, "SEARCH"),
             AppMode::Command => write!(f, "COMMAND"),
+            AppMode::Visual => write!(f, "VISUAL"),
             AppMode::Quit => write!(f, "QUIT"),
         }
     }
 }
+
+/// What the user did that may change the mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModeEvent {
+    EnterSearch,
+    EnterCommand,
+    EnterVisual,
+    Confirm,
+    Cancel,
+    Quit,
+}
+
+impl ModeEvent {
+    /// Mode this event leads to when it is allowed
+    fn target(self) -> AppMode {
+        match self {
+            ModeEvent::EnterSearch => AppMode::Search,
+            ModeEvent::EnterCommand => AppMode::Command,
+            ModeEvent::EnterVisual => AppMode::Visual,
+            ModeEvent::Confirm | ModeEvent::Cancel => AppMode::Normal,
+            ModeEvent::Quit => AppMode::Quit,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModeError {
+    InvalidTransition { from: AppMode, to: AppMode },
+}
+
+impl fmt::Display for ModeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ModeError::InvalidTransition { from, to } => {
+                write!(f, "cannot switch from {} to {} mode", from, to)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ModeError {}
+
+/// The mode after `event` in `current`. Cancel in Normal is a no-op rather than an
+/// error, since Escape is pressed there all the time.
+pub fn transition(current: AppMode, event: ModeEvent) -> Result<AppMode, ModeError> {
+    let to = event.target();
+    let allowed = match (current, event) {
+        (AppMode::Quit, _) => false,
+        (_, ModeEvent::Quit) => true,
+        (AppMode::Normal, ModeEvent::Cancel) => true,
+        (AppMode::Normal, ModeEvent::Confirm) => false,
+        (AppMode::Normal, _) => true,
+        (_, ModeEvent::Confirm | ModeEvent::Cancel) => true,
+        _ => false,
+    };
+
+    if allowed {
+        Ok(to)
+    } else {
+        Err(ModeError::InvalidTransition { from: current, to })
+    }
+}
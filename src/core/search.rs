@@ -1,9 +1,16 @@
-use std::path::PathBuf;
-use std::process::Command;
+use std::collections::{BinaryHeap, VecDeque};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::thread;
+use std::time::SystemTime;
 
 use crate::core::apps::DesktopApp;
-use crate::core::fs::DirEntry;
-use crate::core::history::RecentAccess;
+use crate::core::calc;
+use crate::core::fs::{self, DirEntry};
+use crate::core::history::{CommandHistory, RecentAccess};
 use crate::core::search_config::SearchConfig;
 
 #[derive(Debug, Clone)]
@@ -17,6 +24,44 @@ pub enum SearchResultKind {
         line: u32,
         content: String,
     },
+    SshHost {
+        alias: String,
+        hostname: String,
+        user: Option<String>,
+    },
+    /// Result of an `=` expression; NaN when it couldn't be evaluated
+    Calculation(f64),
+}
+
+impl SearchResultKind {
+    /// Short type label shown next to each result
+    pub fn label(&self) -> &'static str {
+        match self {
+            SearchResultKind::File(_) => "file",
+            SearchResultKind::RecentFile(_) => "recent",
+            SearchResultKind::Application(_) => "app",
+            SearchResultKind::Command(_) => "cmd",
+            SearchResultKind::GrepResult { .. } => "grep",
+            SearchResultKind::SshHost { .. } => "ssh",
+            SearchResultKind::Calculation(_) => "calc",
+        }
+    }
+
+    /// Text copied to the clipboard with Ctrl+C
+    pub fn copy_text(&self) -> String {
+        match self {
+            SearchResultKind::File(path) | SearchResultKind::RecentFile(path) => {
+                path.to_string_lossy().into_owned()
+            }
+            SearchResultKind::Application(app) => app.path.to_string_lossy().into_owned(),
+            SearchResultKind::Command(cmd) => cmd.clone(),
+            SearchResultKind::GrepResult { path, line, .. } => {
+                format!("{}:{}", path.to_string_lossy(), line)
+            }
+            SearchResultKind::SshHost { alias, .. } => format!("ssh {}", alias),
+            SearchResultKind::Calculation(value) => calc::format_number(*value),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -25,11 +70,245 @@ pub struct SearchResult {
     pub description: String,
     pub kind: SearchResultKind,
     pub icon: String,
-    pub score: u32,
+    pub score: f32,
+    pub modified: Option<SystemTime>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SearchSortMode {
+    #[default]
+    ByScore,
+    ByName,
+    ByModified,
+    ByType,
+}
+
+impl SearchSortMode {
+    pub fn next(self) -> Self {
+        match self {
+            SearchSortMode::ByScore => SearchSortMode::ByName,
+            SearchSortMode::ByName => SearchSortMode::ByModified,
+            SearchSortMode::ByModified => SearchSortMode::ByType,
+            SearchSortMode::ByType => SearchSortMode::ByScore,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SearchSortMode::ByScore => "Score",
+            SearchSortMode::ByName => "Name",
+            SearchSortMode::ByModified => "Modified",
+            SearchSortMode::ByType => "Type",
+        }
+    }
+}
+
+/// A backend that handles queries, picked by the query's first character
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchProvider {
+    Apps,
+    Files,
+    Grep,
+    Find,
+    Recent,
+    Commands,
+    Calc,
+}
+
+impl SearchProvider {
+    pub const ALL: [SearchProvider; 7] = [
+        SearchProvider::Apps,
+        SearchProvider::Files,
+        SearchProvider::Grep,
+        SearchProvider::Find,
+        SearchProvider::Recent,
+        SearchProvider::Commands,
+        SearchProvider::Calc,
+    ];
+
+    /// Character that routes a query here; apps and files share plain queries
+    pub fn prefix(self) -> Option<char> {
+        match self {
+            SearchProvider::Apps | SearchProvider::Files => None,
+            SearchProvider::Grep => Some('@'),
+            SearchProvider::Find => Some('/'),
+            SearchProvider::Recent => Some('~'),
+            SearchProvider::Commands => Some(':'),
+            SearchProvider::Calc => Some('='),
+        }
+    }
+
+    pub fn icon(self) -> &'static str {
+        match self {
+            SearchProvider::Apps => "🔍",
+            SearchProvider::Files => "📄",
+            SearchProvider::Grep => "🔎",
+            SearchProvider::Find => "📂",
+            SearchProvider::Recent => "🕘",
+            SearchProvider::Commands => ">",
+            SearchProvider::Calc => "🔢",
+        }
+    }
+
+    /// Name shown to the user, with the external tool that will run for grep and find
+    pub fn label(self) -> String {
+        match self {
+            SearchProvider::Apps => String::from("Apps"),
+            SearchProvider::Files => String::from("Files"),
+            SearchProvider::Grep => format!("Grep ({})", grep_backend()),
+            SearchProvider::Find => format!("Find ({})", find_backend()),
+            SearchProvider::Recent => String::from("Recent"),
+            SearchProvider::Commands => String::from("Commands"),
+            SearchProvider::Calc => String::from("Calculator"),
+        }
+    }
+
+    /// Whether this provider will handle `query`
+    pub fn handles(self, query: &str) -> bool {
+        let Some(first) = query.chars().next() else {
+            return false;
+        };
+        match self.prefix() {
+            Some(prefix) => first == prefix,
+            None => !Self::ALL.iter().any(|p| p.prefix() == Some(first)),
+        }
+    }
+
+    /// `query` without a leading provider prefix
+    pub fn strip_prefix(query: &str) -> &str {
+        match query.chars().next() {
+            Some(first) if Self::ALL.iter().any(|p| p.prefix() == Some(first)) => {
+                &query[first.len_utf8()..]
+            }
+            _ => query,
+        }
+    }
+}
+
+/// Whether an executable named `program` is on `$PATH`
+pub fn on_path(program: &str) -> bool {
+    std::env::var_os("PATH")
+        .is_some_and(|paths| std::env::split_paths(&paths).any(|dir| dir.join(program).is_file()))
+}
+
+/// The tool `@` queries run: ripgrep when installed, grep otherwise
+fn grep_backend() -> &'static str {
+    static BACKEND: OnceLock<&str> = OnceLock::new();
+    BACKEND.get_or_init(|| if on_path("rg") { "rg" } else { "grep" })
+}
+
+/// The tool `/` queries run: fd when installed, find otherwise
+fn find_backend() -> &'static str {
+    static BACKEND: OnceLock<&str> = OnceLock::new();
+    BACKEND.get_or_init(|| if on_path("fd") { "fd" } else { "find" })
+}
+
+/// Highest score first; falls back to name order if a score is NaN
+fn by_score(a: &SearchResult, b: &SearchResult) -> std::cmp::Ordering {
+    b.score
+        .partial_cmp(&a.score)
+        .unwrap_or_else(|| a.name.cmp(&b.name))
+}
+
+/// Sort results in place. Ties keep score order.
+pub fn sort_results(results: &mut [SearchResult], mode: SearchSortMode) {
+    results.sort_by(by_score);
+    match mode {
+        SearchSortMode::ByScore => {}
+        SearchSortMode::ByName => {
+            results.sort_by_key(|r| r.name.to_lowercase());
+        }
+        SearchSortMode::ByModified => {
+            // Newest first, results without a timestamp last
+            results.sort_by(|a, b| b.modified.cmp(&a.modified));
+        }
+        SearchSortMode::ByType => {
+            results.sort_by_key(|r| r.kind.label());
+        }
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Results for the current query, possibly still arriving from a background search
+#[derive(Default)]
+pub enum SearchState {
+    #[default]
+    Empty,
+    Complete(Vec<SearchResult>),
+    Streaming {
+        results: Vec<SearchResult>,
+        rx: Receiver<SearchResult>,
+    },
+}
+
+impl SearchState {
+    pub fn results(&self) -> &[SearchResult] {
+        match self {
+            SearchState::Empty => &[],
+            SearchState::Complete(results) | SearchState::Streaming { results, .. } => results,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.results().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.results().is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&SearchResult> {
+        self.results().get(index)
+    }
+
+    pub fn is_streaming(&self) -> bool {
+        matches!(self, SearchState::Streaming { .. })
+    }
+
+    pub fn sort(&mut self, mode: SearchSortMode) {
+        match self {
+            SearchState::Empty => {}
+            SearchState::Complete(results) | SearchState::Streaming { results, .. } => {
+                sort_results(results, mode)
+            }
+        }
+    }
+
+    /// Drop the current results, cancelling any background search
+    pub fn clear(&mut self) {
+        *self = SearchState::Empty;
+    }
+
+    /// Drain results from a background search. Returns true if anything changed.
+    pub fn poll(&mut self) -> bool {
+        let SearchState::Streaming { results, rx } = self else {
+            return false;
+        };
+
+        let mut changed = false;
+        loop {
+            match rx.try_recv() {
+                Ok(result) => {
+                    merge_result(results, result);
+                    changed = true;
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    let results = std::mem::take(results);
+                    *self = SearchState::Complete(results);
+                    return true;
+                }
+            }
+        }
+        changed
+    }
 }
 
 impl SearchResult {
-    pub fn file(entry: &DirEntry, score: u32) -> Self {
+    pub fn file(entry: &DirEntry, score: f32) -> Self {
         let icon = if entry.is_dir {
             "📁".to_string()
         } else {
@@ -42,10 +321,11 @@ impl SearchResult {
             kind: SearchResultKind::File(entry.path.clone()),
             icon,
             score,
+            modified: entry.modified,
         }
     }
 
-    pub fn recent_file(recent: &RecentAccess, score: u32) -> Self {
+    pub fn recent_file(recent: &RecentAccess, score: f32) -> Self {
         let name = recent
             .path
             .file_name()
@@ -65,10 +345,11 @@ impl SearchResult {
             kind: SearchResultKind::RecentFile(recent.path.clone()),
             icon,
             score,
+            modified: modified_time(&recent.path),
         }
     }
 
-    pub fn application(app: &DesktopApp, score: u32) -> Self {
+    pub fn application(app: &DesktopApp, score: f32) -> Self {
         SearchResult {
             name: app.name.clone(),
             description: app
@@ -78,6 +359,7 @@ impl SearchResult {
             kind: SearchResultKind::Application(app.clone()),
             icon: "🚀".to_string(),
             score,
+            modified: None,
         }
     }
 
@@ -87,11 +369,44 @@ impl SearchResult {
             description: "Execute shell command".to_string(),
             kind: SearchResultKind::Command(cmd.to_string()),
             icon: "⚡".to_string(),
-            score: 10,
+            score: 0.1,
+            modified: None,
+        }
+    }
+
+    pub fn calculation(name: String, value: f64) -> Self {
+        SearchResult {
+            name,
+            description: "Copy result".to_string(),
+            kind: SearchResultKind::Calculation(value),
+            icon: "🔢".to_string(),
+            score: 1.0,
+            modified: None,
+        }
+    }
+
+    pub fn ssh_host(alias: String, hostname: String, user: Option<String>, score: f32) -> Self {
+        let description = match &user {
+            Some(user) => format!("SSH: {}@{}", user, hostname),
+            None => format!("SSH: {}", hostname),
+        };
+
+        SearchResult {
+            name: alias.clone(),
+            description,
+            kind: SearchResultKind::SshHost {
+                alias,
+                hostname,
+                user,
+            },
+            icon: "🖥️".to_string(),
+            score,
+            modified: None,
         }
     }
 
     pub fn grep_result(path: PathBuf, line: u32, content: String) -> Self {
+        let modified = modified_time(&path);
         let name = path
             .file_name()
             .map(|n| n.to_string_lossy().to_string())
@@ -106,110 +421,203 @@ impl SearchResult {
                 content,
             },
             icon: "🔎".to_string(),
-            score: 30,
+            score: 0.3,
+            modified,
         }
     }
 }
 
-pub fn fuzzy_score(query: &str, text: &str) -> u32 {
-    let query_lower = query.to_lowercase();
-    let text_lower = text.to_lowercase();
+/// Bonus for a matched character at the start of the text or after a separator
+const BOUNDARY_BONUS: f32 = 0.1;
+/// Bonus for a matched character starting a CamelCase word, which marks a word more
+/// deliberately than a separator does
+const CAMEL_CASE_BONUS: f32 = 0.15;
+
+/// How well `query` matches `text`, from 0.0 (no match) to 1.0 (exact)
+pub fn fuzzy_score(query: &str, text: &str, case_sensitive: bool) -> f32 {
+    let (query_lower, text_lower) = if case_sensitive {
+        (query.to_string(), text.to_string())
+    } else {
+        (query.to_lowercase(), text.to_lowercase())
+    };
 
     if text_lower == query_lower {
-        return 100;
+        return 1.0;
     }
 
     if text_lower.starts_with(&query_lower) {
-        return 90;
+        return 0.9;
     }
 
     if text_lower.contains(&query_lower) {
-        return 70;
+        return 0.7;
     }
 
     let query_chars: Vec<char> = query_lower.chars().collect();
-    let text_chars: Vec<char> = text_lower.chars().collect();
+    // Walk the original text so CamelCase boundaries can be detected
+    let lower = |c: char| {
+        if case_sensitive {
+            c
+        } else if c.is_ascii() {
+            c.to_ascii_lowercase()
+        } else {
+            c.to_lowercase().next().unwrap_or(c)
+        }
+    };
 
     let mut query_idx = 0;
-    let mut consecutive_bonus = 0;
+    let mut consecutive_bonus = 0.0;
     let mut last_match_idx: Option<usize> = None;
+    let mut first_match_idx = 0;
+    let mut boundary_bonus = 0.0;
+    let mut prev: Option<char> = None;
+    let mut text_len = 0;
 
-    for (i, c) in text_chars.iter().enumerate() {
-        if query_idx < query_chars.len() && *c == query_chars[query_idx] {
-            if let Some(last) = last_match_idx {
-                if i == last + 1 {
-                    consecutive_bonus += 5;
-                }
+    for (i, c) in text.chars().enumerate() {
+        text_len = i + 1;
+        if query_idx < query_chars.len() && lower(c) == query_chars[query_idx] {
+            if last_match_idx.is_some_and(|last| i == last + 1) {
+                consecutive_bonus += 0.05;
             }
+            if query_idx == 0 {
+                first_match_idx = i;
+            }
+            boundary_bonus += match prev {
+                None | Some('_' | '-' | '.' | ' ' | '/') => BOUNDARY_BONUS,
+                Some(p) if p.is_lowercase() && c.is_uppercase() => CAMEL_CASE_BONUS,
+                _ => 0.0,
+            };
             last_match_idx = Some(i);
             query_idx += 1;
         }
+        prev = Some(c);
     }
 
     if query_idx == query_chars.len() {
-        let base_score = 40 + consecutive_bonus;
-        let boundary_bonus = if text_lower
-            .split_whitespace()
-            .any(|word| word.starts_with(&query_lower.chars().next().unwrap_or(' ').to_string()))
-        {
-            10
-        } else {
-            0
-        };
-        return (base_score + boundary_bonus).min(65);
+        // Prefer matches that start earlier in the text (0.0-0.1)
+        let position_penalty = 0.1 * first_match_idx as f32 / text_len.max(1) as f32;
+
+        let score = (0.4 + consecutive_bonus + boundary_bonus - position_penalty).max(0.0);
+        return score.min(0.65);
+    }
+
+    0.0
+}
+
+/// Metadata (keywords, categories, description) only counts on a substring match or better,
+/// since scattered subsequence matches against long text are mostly noise
+const MIN_METADATA_SCORE: f32 = 0.7;
+
+fn metadata_score(query: &str, text: &str) -> f32 {
+    let score = fuzzy_score(query, text, false);
+    if score >= MIN_METADATA_SCORE {
+        score
+    } else {
+        0.0
     }
+}
+
+/// Best match against an app's keywords, with categories weighted lower.
+/// Multi-word queries ("video editor") must match a keyword for every word.
+pub fn keyword_score(query: &str, app: &DesktopApp) -> f32 {
+    let words: Vec<&str> = query.split_whitespace().collect();
+    if words.len() > 1 {
+        let scores: Vec<f32> = words.iter().map(|w| single_keyword_score(w, app)).collect();
+        if scores.contains(&0.0) {
+            return 0.0;
+        }
+        return scores.iter().sum::<f32>() / scores.len() as f32;
+    }
+
+    single_keyword_score(query, app)
+}
+
+fn single_keyword_score(query: &str, app: &DesktopApp) -> f32 {
+    let keyword = app
+        .keywords
+        .iter()
+        .map(|k| metadata_score(query, k))
+        .fold(0.0, f32::max);
+
+    // Categories end up at 50% of a name match once the 80% keyword weight is applied
+    let category = app
+        .categories
+        .iter()
+        .map(|c| metadata_score(query, c) * 0.625)
+        .fold(0.0, f32::max);
 
-    0
+    keyword.max(category)
 }
 
-/// Search across all sources and return unified results
+/// Keyword matches never outrank a decent name match
+const MAX_KEYWORD_SCORE: f32 = 0.6;
+
+/// Score an application by name, keywords/categories and description
+/// Only the name honours `case_sensitive`; keywords and descriptions are prose
+fn app_score(query: &str, app: &DesktopApp, case_sensitive: bool) -> f32 {
+    let name_score = fuzzy_score(query, &app.name, case_sensitive).max(fuzzy_score(
+        query,
+        &app.normalized_name,
+        case_sensitive,
+    ));
+    let keyword = (keyword_score(query, app) * 0.8).min(MAX_KEYWORD_SCORE);
+    let desc = app
+        .description
+        .as_deref()
+        .map(|d| metadata_score(query, d) * 0.6)
+        .unwrap_or(0.0);
+
+    name_score.max(keyword).max(desc)
+}
+
+/// Multiplier for recently opened files
+const RECENT_MULTIPLIER: f32 = 1.15;
+
+/// Multiplier of `1 + 0.02 * log10(access_count + 1)` for files in the most-opened list
+fn frequency_multiplier(path: &Path, most_opened: &[RecentAccess]) -> f32 {
+    most_opened
+        .iter()
+        .find(|m| m.path == path)
+        .map(|m| 1.0 + 0.02 * ((m.access_count.max(0) + 1) as f32).log10())
+        .unwrap_or(1.0)
+}
+
+/// Search across all sources and return unified results. `@` and `/` queries run
+/// external tools and go through `spawn_external_search` instead.
 pub fn search_all(
     query: &str,
     files: &[DirEntry],
     recent: &[RecentAccess],
+    most_opened: &[RecentAccess],
     apps: &[DesktopApp],
+    ssh_hosts: &[SearchResult],
     config: &SearchConfig,
 ) -> Vec<SearchResult> {
-    let mut results = Vec::new();
-
     if query.is_empty() {
-        return results;
+        return Vec::new();
     }
 
-    if query.starts_with(':') {
-        let cmd = query.trim_start_matches(':').trim();
-        if !cmd.is_empty() {
-            results.push(SearchResult::command(cmd));
-        }
-        return results;
-    }
+    let mut results = TopResults::new();
 
-    if query.starts_with('@') {
-        let pattern = query.trim_start_matches('@').trim();
-        if !pattern.is_empty() {
-            return search_file_contents(pattern, config);
+    for app in apps {
+        let score = app_score(query, app, config.case_sensitive);
+        if score > 0.0 {
+            results.offer(score, || SearchResult::application(app, score));
         }
-        return results;
     }
 
-    if query.starts_with('/') {
-        let pattern = query.trim_start_matches('/').trim();
-        if !pattern.is_empty() {
-            return find_files(pattern, config);
+    for host in ssh_hosts {
+        let score = fuzzy_score(query, &host.name, config.case_sensitive);
+        if score > 0.0 {
+            results.offer(score, || SearchResult {
+                score,
+                ..host.clone()
+            });
         }
-        return results;
     }
 
-    for app in apps {
-        let score = fuzzy_score(query, &app.name);
-        if score > 0 {
-            results.push(SearchResult::application(app, score));
-        } else if let Some(ref desc) = app.description {
-            let desc_score = fuzzy_score(query, desc);
-            if desc_score > 30 {
-                results.push(SearchResult::application(app, desc_score / 2));
-            }
-        }
+    if results.is_full_of_strong_matches() {
+        return results.into_sorted_vec();
     }
 
     for recent_file in recent {
@@ -219,129 +627,532 @@ pub fn search_all(
             .map(|n| n.to_string_lossy().to_string())
             .unwrap_or_default();
 
-        let score = fuzzy_score(query, &name);
-        if score > 0 {
-            results.push(SearchResult::recent_file(recent_file, score + 10)); // Bonus for recent
+        let score = fuzzy_score(query, &name, config.case_sensitive);
+        if score > 0.0 {
+            let score =
+                score * RECENT_MULTIPLIER * frequency_multiplier(&recent_file.path, most_opened);
+            results.offer(score, || SearchResult::recent_file(recent_file, score));
         }
     }
 
+    if results.is_full_of_strong_matches() {
+        return results.into_sorted_vec();
+    }
+
     for file in files {
         if file.name == ".." {
             continue;
         }
 
-        let score = fuzzy_score(query, &file.name);
-        if score > 0 {
-            results.push(SearchResult::file(file, score));
+        let score = fuzzy_score(query, &file.name, config.case_sensitive);
+        if score > 0.0 {
+            let score = score * frequency_multiplier(&file.path, most_opened);
+            results.offer(score, || SearchResult::file(file, score));
         }
     }
 
-    results.sort_by(|a, b| b.score.cmp(&a.score));
+    results.into_sorted_vec()
+}
+
+/// Past commands suggested under a `:` query
+const MAX_HISTORY_COMMANDS: usize = 10;
+
+/// `:` query results: the typed command first, then past commands that fuzzy-match it,
+/// favouring ones run often and recently
+pub fn command_results(typed: &str, history: &[CommandHistory]) -> Vec<SearchResult> {
+    let typed = typed.trim();
+    let mut results = Vec::new();
+    if !typed.is_empty() {
+        let mut current = SearchResult::command(typed);
+        current.score = 1.0;
+        results.push(current);
+    }
+
+    let now = chrono::Utc::now();
+    let mut seen: Vec<&str> = vec![typed];
+    for entry in history {
+        if seen.contains(&entry.command.as_str()) {
+            continue;
+        }
+        seen.push(&entry.command);
+
+        let score = fuzzy_score(typed, &entry.command, false);
+        if score <= 0.0 {
+            continue;
+        }
+        let frequency = entry.run_count.min(10) as f32 / 10.0;
+        let days = (now - entry.last_run).num_hours().max(0) as f32 / 24.0;
+        let recency = 1.0 / (1.0 + days);
+        // Stays below 1.0 so the typed command always comes first
+        let score = score * 0.8 * (0.8 + 0.1 * frequency + 0.1 * recency);
 
-    results.truncate(20);
+        let mut result = SearchResult::command(&entry.command);
+        result.description = format!("Ran {}× • {}", entry.run_count, entry.path.display());
+        result.score = score;
+        results.push(result);
+    }
 
+    results.sort_by(by_score);
+    results.truncate(MAX_HISTORY_COMMANDS + 1);
     results
 }
 
-/// Search file contents using grep/ripgrep
-pub fn search_file_contents(pattern: &str, config: &SearchConfig) -> Vec<SearchResult> {
-    let mut results = Vec::new();
+const MAX_RESULTS: usize = 20;
 
-    // Try ripgrep first with exclusions
-    let output = {
-        let mut cmd = Command::new("rg");
-        cmd.args(["-n", "-i", "--max-count", "20"]);
-        for arg in config.rg_exclude_args() {
-            cmd.arg(&arg);
+/// Once every slot holds at least a substring match, later passes can't usefully compete
+const EARLY_EXIT_SCORE: f32 = 0.7;
+
+/// Orders results worst first, so a `BinaryHeap` of them keeps the weakest on top
+struct Ranked(SearchResult);
+
+impl PartialEq for Ranked {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == std::cmp::Ordering::Equal
+    }
+}
+
+impl Eq for Ranked {}
+
+impl PartialOrd for Ranked {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Ranked {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        by_score(&self.0, &other.0)
+    }
+}
+
+/// Searches remembered by `LruCache::default`
+pub const SEARCH_CACHE_SIZE: usize = 20;
+
+/// A small least-recently-used map; lookups are linear, which is fine at this size
+pub struct LruCache<K, V> {
+    capacity: usize,
+    entries: VecDeque<(K, V)>,
+}
+
+impl<K: PartialEq, V> LruCache<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        LruCache {
+            capacity,
+            entries: VecDeque::with_capacity(capacity),
         }
-        cmd.args([pattern, "."]);
-        cmd.output()
     }
-    .or_else(|_| {
-        // Fall back to grep with exclusions
-        let mut cmd = Command::new("grep");
-        cmd.args(["-r", "-n", "-i"]);
-        for arg in config.grep_exclude_args() {
-            cmd.arg(&arg);
+
+    /// The value for `key`, marking it as most recently used
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        let index = self.entries.iter().position(|(k, _)| k == key)?;
+        let entry = self.entries.remove(index)?;
+        self.entries.push_back(entry);
+        self.entries.back().map(|(_, v)| v)
+    }
+
+    /// Insert or replace `key`, evicting the least recently used entry when full
+    pub fn put(&mut self, key: K, value: V) {
+        self.entries.retain(|(k, _)| *k != key);
+        if self.entries.len() >= self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((key, value));
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+impl<K: PartialEq, V> Default for LruCache<K, V> {
+    fn default() -> Self {
+        Self::new(SEARCH_CACHE_SIZE)
+    }
+}
+
+/// The best `MAX_RESULTS` results seen so far. Memory stays bounded however many
+/// entries match, and results that can't make the cut are never built.
+struct TopResults {
+    heap: BinaryHeap<Ranked>,
+}
+
+impl TopResults {
+    fn new() -> Self {
+        Self {
+            heap: BinaryHeap::with_capacity(MAX_RESULTS + 1),
+        }
+    }
+
+    fn offer(&mut self, score: f32, result: impl FnOnce() -> SearchResult) {
+        if self.heap.len() >= MAX_RESULTS && self.weakest_score() >= score {
+            return;
+        }
+        self.heap.push(Ranked(result()));
+        if self.heap.len() > MAX_RESULTS {
+            self.heap.pop();
+        }
+    }
+
+    fn weakest_score(&self) -> f32 {
+        self.heap.peek().map_or(0.0, |r| r.0.score)
+    }
+
+    /// Whether every slot holds a match strong enough to skip the remaining passes
+    fn is_full_of_strong_matches(&self) -> bool {
+        self.heap.len() >= MAX_RESULTS && self.weakest_score() > EARLY_EXIT_SCORE
+    }
+
+    /// Best first
+    fn into_sorted_vec(self) -> Vec<SearchResult> {
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|r| r.0)
+            .collect()
+    }
+}
+
+/// Whether `query` names a path rather than a `/` find: anything under `~/`, or an
+/// absolute path with at least two components such as `/home/user`
+pub fn looks_like_absolute_path(query: &str) -> bool {
+    query.starts_with("~/")
+        || query
+            .strip_prefix('/')
+            .is_some_and(|rest| rest.trim_end_matches('/').contains('/'))
+}
+
+/// The path a query names, with a leading `~/` expanded to the home directory
+pub fn expand_path_query(query: &str) -> Option<PathBuf> {
+    match query.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir().map(|home| home.join(rest)),
+        None => Some(PathBuf::from(query)),
+    }
+}
+
+/// Whether the query shells out to grep/find and should run in the background
+pub fn is_external_query(query: &str) -> bool {
+    query.starts_with('@') || query.starts_with('/')
+}
+
+/// Most results an `@` or `/` search reports
+const MAX_EXTERNAL_RESULTS: usize = 15;
+
+/// Run an `@` or `/` search on a background thread, sending results as rg/grep or fd/find
+/// print them. Dropping the receiver kills the search process at its next result.
+/// `current_dir` is the directory open in the launcher, whose `.gitignore` `find` follows.
+pub fn spawn_external_search(
+    query: String,
+    config: SearchConfig,
+    current_dir: PathBuf,
+) -> Receiver<SearchResult> {
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        if let Some(pattern) = query.strip_prefix('@').map(str::trim) {
+            if !pattern.is_empty() {
+                stream_file_contents(pattern, &config, &tx);
+            }
+        } else if let Some(pattern) = query.strip_prefix('/').map(str::trim)
+            && !pattern.is_empty()
+        {
+            stream_found_files(pattern, &config, &current_dir, &tx);
         }
-        cmd.args([pattern, "."]);
-        cmd.output()
     });
 
-    if let Ok(output) = output {
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        for line in stdout.lines().take(15) {
-            // Parse grep output: filename:line:content
-            let parts: Vec<&str> = line.splitn(3, ':').collect();
-            if parts.len() >= 3 {
-                let path = PathBuf::from(parts[0]);
-                if let Ok(line_num) = parts[1].parse::<u32>() {
-                    results.push(SearchResult::grep_result(
-                        path,
-                        line_num,
-                        parts[2].to_string(),
-                    ));
-                }
+    rx
+}
+
+/// Add `result`, replacing an earlier result for the same file so a later pass can
+/// re-score it
+fn merge_result(results: &mut Vec<SearchResult>, result: SearchResult) {
+    let existing = match &result.kind {
+        SearchResultKind::File(path) => results
+            .iter_mut()
+            .find(|r| matches!(&r.kind, SearchResultKind::File(p) if p == path)),
+        _ => None,
+    };
+    match existing {
+        Some(existing) => *existing = result,
+        None => results.push(result),
+    }
+}
+
+/// Run the first of `commands` that starts and send a result for each line of its output
+/// that `parse` accepts. The process is killed once `MAX_EXTERNAL_RESULTS` are sent or
+/// the receiver is gone. Returns the results sent, or None if the search was cancelled.
+fn stream_results(
+    commands: impl IntoIterator<Item = Command>,
+    parse: impl Fn(&str) -> Option<SearchResult>,
+    tx: &Sender<SearchResult>,
+) -> Option<Vec<SearchResult>> {
+    let mut child = commands.into_iter().find_map(|mut cmd| {
+        cmd.stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok()
+    })?;
+    let stdout = child.stdout.take()?;
+
+    let mut sent = Vec::new();
+    let mut cancelled = false;
+    let mut reader = BufReader::new(stdout);
+    let mut buf = Vec::new();
+    while sent.len() < MAX_EXTERNAL_RESULTS && reader.read_until(b'\n', &mut buf).unwrap_or(0) > 0 {
+        let line = String::from_utf8_lossy(&buf);
+        if let Some(result) = parse(line.trim_end_matches(['\n', '\r'])) {
+            if tx.send(result.clone()).is_err() {
+                cancelled = true;
+                break;
             }
+            sent.push(result);
         }
+        buf.clear();
     }
 
-    results
+    let _ = child.kill();
+    let _ = child.wait();
+    (!cancelled).then_some(sent)
 }
 
-pub fn find_files(pattern: &str, config: &SearchConfig) -> Vec<SearchResult> {
-    let mut results = Vec::new();
+fn stream_file_contents(pattern: &str, config: &SearchConfig, tx: &Sender<SearchResult>) {
+    stream_results(grep_commands(pattern, config), parse_grep_line, tx);
+}
+
+/// ripgrep with exclusions, then grep as a fallback
+fn grep_commands(pattern: &str, config: &SearchConfig) -> [Command; 2] {
+    let mut rg = Command::new("rg");
+    rg.args(["-n", "--max-count", "20", "--max-filesize", "1M"]);
+    if !config.case_sensitive {
+        rg.arg("--smart-case");
+    }
+    for arg in config.rg_exclude_args() {
+        rg.arg(&arg);
+    }
+    rg.args([pattern, "."]);
 
-    // Try fd first (faster) with exclusions
+    let mut grep = Command::new("grep");
+    grep.args(["-r", "-n"]);
+    if !config.case_sensitive {
+        grep.arg("-i");
+    }
+    for arg in config.grep_exclude_args() {
+        grep.arg(&arg);
+    }
+    grep.args([pattern, "."]);
+
+    [rg, grep]
+}
+
+/// Parse `filename:line:content` lines from rg / grep, keeping the first 15
+fn parse_grep_output(stdout: &str) -> Vec<SearchResult> {
+    stdout
+        .lines()
+        .take(15)
+        .filter_map(parse_grep_line)
+        .collect()
+}
+
+fn parse_grep_line(line: &str) -> Option<SearchResult> {
+    let mut parts = line.splitn(3, ':');
+    let path = PathBuf::from(parts.next()?);
+    let line_num = parts.next()?.parse::<u32>().ok()?;
+    let content = parts.next()?;
+    Some(SearchResult::grep_result(
+        path,
+        line_num,
+        content.to_string(),
+    ))
+}
+
+/// Boost for `/pattern` files whose contents also match
+const NAME_AND_CONTENT_BOOST: f32 = 1.2;
+
+/// Most bytes of paths passed to one grep, well under `ARG_MAX` even with a large
+/// environment
+const MAX_PATH_ARG_BYTES: usize = 64 * 1024;
+
+/// Grep `paths`, the files a `/pattern` search found by name, for the pattern itself.
+/// Long lists are split across several runs so the command line can't overflow.
+fn content_matches(pattern: &str, config: &SearchConfig, paths: &[PathBuf]) -> Vec<SearchResult> {
+    path_chunks(paths, MAX_PATH_ARG_BYTES)
+        .into_iter()
+        .flat_map(|chunk| grep_paths(pattern, config, chunk))
+        .take(15)
+        .collect()
+}
+
+/// Split `paths` into runs whose combined length stays within `max_bytes`; a single
+/// longer path gets a run of its own
+fn path_chunks(paths: &[PathBuf], max_bytes: usize) -> Vec<&[PathBuf]> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut bytes = 0;
+    for (i, path) in paths.iter().enumerate() {
+        // Each argument also costs its terminating NUL
+        let len = path.as_os_str().len() + 1;
+        if i > start && bytes + len > max_bytes {
+            chunks.push(&paths[start..i]);
+            start = i;
+            bytes = 0;
+        }
+        bytes += len;
+    }
+    if start < paths.len() {
+        chunks.push(&paths[start..]);
+    }
+    chunks
+}
+
+fn grep_paths(pattern: &str, config: &SearchConfig, paths: &[PathBuf]) -> Vec<SearchResult> {
     let output = {
-        let mut cmd = Command::new("fd");
-        cmd.args(["-i", "--max-results", "20"]);
-        for arg in config.fd_exclude_args() {
-            cmd.arg(&arg);
+        let mut cmd = Command::new("rg");
+        cmd.args(["-n", "--with-filename", "--max-count", "3"]);
+        if !config.case_sensitive {
+            cmd.arg("-i");
         }
-        cmd.arg(pattern);
+        cmd.args(["--", pattern]).args(paths);
         cmd.output()
     }
     .or_else(|_| {
-        // Fall back to find with exclusions
-        let mut cmd = Command::new("find");
-        cmd.args([".", "-maxdepth", "5"]);
-        for arg in config.find_exclude_args() {
-            cmd.arg(&arg);
+        let mut cmd = Command::new("grep");
+        cmd.args(["-n", "-H", "-m", "3"]);
+        if !config.case_sensitive {
+            cmd.arg("-i");
         }
-        cmd.args(["-iname", &format!("*{}*", pattern)]);
+        cmd.args(["--", pattern]).args(paths);
         cmd.output()
     });
+    match output {
+        Ok(output) => parse_grep_output(&String::from_utf8_lossy(&output.stdout)),
+        Err(e) => {
+            tracing::warn!("Content search over {} files failed: {}", paths.len(), e);
+            Vec::new()
+        }
+    }
+}
+
+/// Send files whose names match as they are found. With `find_with_content`, then grep
+/// those files and re-send the ones whose contents match too with a boosted score.
+fn stream_found_files(
+    pattern: &str,
+    config: &SearchConfig,
+    current_dir: &Path,
+    tx: &Sender<SearchResult>,
+) {
+    let commands = find_commands(pattern, config, current_dir);
+    let Some(found) = stream_results(commands, found_file_result, tx) else {
+        return;
+    };
+    if !config.find_with_content {
+        return;
+    }
 
-    if let Ok(output) = output {
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        for line in stdout.lines().take(15) {
-            let path = PathBuf::from(line.trim());
-            if path.exists() {
-                let name = path
-                    .file_name()
-                    .map(|n| n.to_string_lossy().to_string())
-                    .unwrap_or_else(|| path.to_string_lossy().to_string());
-
-                let is_dir = path.is_dir();
-                let icon = if is_dir {
-                    "📁".to_string()
-                } else {
-                    get_file_icon(&name)
-                };
-
-                results.push(SearchResult {
-                    name,
-                    description: path.to_string_lossy().to_string(),
-                    kind: SearchResultKind::File(path),
-                    icon,
-                    score: 50,
-                });
+    let paths: Vec<PathBuf> = found
+        .iter()
+        .filter_map(|r| match &r.kind {
+            SearchResultKind::File(path) if !path.is_dir() => Some(path.clone()),
+            _ => None,
+        })
+        .collect();
+    let content = content_matches(pattern, config, &paths);
+
+    for mut result in found {
+        let SearchResultKind::File(path) = &result.kind else {
+            continue;
+        };
+        let matched = content
+            .iter()
+            .any(|m| matches!(&m.kind, SearchResultKind::GrepResult { path: p, .. } if p == path));
+        if matched {
+            result.score *= NAME_AND_CONTENT_BOOST;
+            if tx.send(result).is_err() {
+                return;
             }
         }
     }
+    for result in content {
+        if tx.send(result).is_err() {
+            return;
+        }
+    }
+}
 
-    results
+/// fd with exclusions, then find as a fallback that skips what the `.gitignore` nearest
+/// `current_dir` ignores
+fn find_commands(pattern: &str, config: &SearchConfig, current_dir: &Path) -> [Command; 2] {
+    let mut fd = Command::new("fd");
+    fd.args(["--max-results", "20"]);
+    if !config.case_sensitive {
+        fd.arg("-i");
+    }
+    if !config.respect_gitignore {
+        fd.arg("--no-ignore");
+    }
+    for arg in config.fd_exclude_args() {
+        fd.arg(&arg);
+    }
+    fd.arg(pattern);
+
+    let mut find = Command::new("find");
+    find.args([".", "-maxdepth", "5"]);
+    for arg in config.find_exclude_args() {
+        find.arg(&arg);
+    }
+    if config.respect_gitignore {
+        let patterns = fs::load_gitignore_patterns(current_dir);
+        find.args(gitignore_find_args(&patterns));
+    }
+    let name_flag = if config.case_sensitive {
+        "-name"
+    } else {
+        "-iname"
+    };
+    find.args([name_flag, &format!("*{}*", pattern)]);
+
+    [fd, find]
+}
+
+/// A result for a path printed by fd/find, if it still exists
+fn found_file_result(line: &str) -> Option<SearchResult> {
+    let path = PathBuf::from(line.trim());
+    if !path.exists() {
+        return None;
+    }
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string_lossy().to_string());
+
+    let icon = if path.is_dir() {
+        "📁".to_string()
+    } else {
+        get_file_icon(&name)
+    };
+
+    Some(SearchResult {
+        name,
+        description: path.to_string_lossy().to_string(),
+        modified: modified_time(&path),
+        kind: SearchResultKind::File(path),
+        icon,
+        score: 0.5,
+    })
+}
+
+/// `-not -path` arguments for find from `.gitignore` patterns. Negations (`!pattern`)
+/// can't be expressed this way and are skipped.
+fn gitignore_find_args(patterns: &[String]) -> Vec<String> {
+    patterns
+        .iter()
+        .filter(|pattern| !pattern.starts_with('!'))
+        .map(|pattern| pattern.trim_start_matches('/').trim_end_matches('/'))
+        .filter(|pattern| !pattern.is_empty())
+        .flat_map(|pattern| {
+            // The entry itself, and everything under it when it is a directory
+            [format!("*/{}", pattern), format!("*/{}/*", pattern)]
+        })
+        .flat_map(|glob| ["-not".to_string(), "-path".to_string(), glob])
+        .collect()
 }
 
 fn get_file_icon(name: &str) -> String {
@@ -371,3 +1182,283 @@ fn get_file_icon(name: &str) -> String {
     }
     .to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ranked_apps(query: &str, apps: &[DesktopApp]) -> Vec<String> {
+        search_all(query, &[], &[], &[], apps, &[], &SearchConfig::default())
+            .into_iter()
+            .filter(|r| matches!(r.kind, SearchResultKind::Application(_)))
+            .map(|r| r.name)
+            .collect()
+    }
+
+    #[test]
+    fn keywords_surface_apps_without_a_name_match() {
+        let apps = [
+            DesktopApp::for_test("Kdenlive", &["video", "editor", "kdenlive"]),
+            DesktopApp::for_test("Calculator", &["math", "arithmetic"]),
+        ];
+        assert_eq!(ranked_apps("video editor", &apps), ["Kdenlive"]);
+    }
+
+    #[test]
+    fn name_matches_outrank_keyword_matches() {
+        let apps = [
+            DesktopApp::for_test("Kdenlive", &["video", "editor"]),
+            DesktopApp::for_test("Text Editor", &["text", "plain"]),
+        ];
+        assert_eq!(ranked_apps("editor", &apps), ["Text Editor", "Kdenlive"]);
+    }
+
+    #[test]
+    fn keyword_score_is_capped_below_a_name_match() {
+        let kdenlive = DesktopApp::for_test("Kdenlive", &["video"]);
+        let keyword_only = app_score("video", &kdenlive, false);
+        assert!(keyword_only > 0.0);
+        assert!(keyword_only <= MAX_KEYWORD_SCORE);
+        assert!(app_score("kdenlive", &kdenlive, false) > keyword_only);
+    }
+
+    #[test]
+    fn multi_word_keyword_queries_need_every_word() {
+        let kdenlive = DesktopApp::for_test("Kdenlive", &["video", "editor"]);
+        assert!(keyword_score("video editor", &kdenlive) > 0.0);
+        assert_eq!(keyword_score("video spreadsheet", &kdenlive), 0.0);
+    }
+
+    #[test]
+    fn camel_case_boundaries_outrank_separators() {
+        let camel = fuzzy_score("fb", "FooBar", false);
+        let snake = fuzzy_score("fb", "foo_bar", false);
+        let inside = fuzzy_score("fb", "fooxbar", false);
+        assert!(camel > snake, "{} <= {}", camel, snake);
+        assert!(snake > inside, "{} <= {}", snake, inside);
+    }
+
+    #[test]
+    fn long_path_lists_are_chunked() {
+        let paths: Vec<PathBuf> = (0..10)
+            .map(|i| PathBuf::from(format!("/tmp/f{}", i)))
+            .collect();
+        // Each path is 7 bytes plus its NUL
+        let chunks = path_chunks(&paths, 24);
+        assert_eq!(
+            chunks.iter().map(|c| c.len()).collect::<Vec<_>>(),
+            [3, 3, 3, 1]
+        );
+        assert_eq!(chunks.concat(), paths);
+
+        let long = [PathBuf::from("/a/very/long/path/indeed")];
+        assert_eq!(path_chunks(&long, 8), [&long[..]]);
+        assert!(path_chunks(&[], 8).is_empty());
+    }
+
+    #[test]
+    fn streamed_file_results_replace_earlier_ones() {
+        let found = |score: f32| SearchResult {
+            name: "notes.txt".to_string(),
+            description: "/tmp/notes.txt".to_string(),
+            kind: SearchResultKind::File(PathBuf::from("/tmp/notes.txt")),
+            icon: String::new(),
+            score,
+            modified: None,
+        };
+        let mut results = Vec::new();
+        merge_result(&mut results, found(0.5));
+        merge_result(
+            &mut results,
+            SearchResult::grep_result(PathBuf::from("/tmp/notes.txt"), 3, "x".into()),
+        );
+        merge_result(&mut results, found(0.6));
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].score, 0.6);
+        assert!(matches!(
+            results[1].kind,
+            SearchResultKind::GrepResult { line: 3, .. }
+        ));
+    }
+
+    /// `fuzzy_score` before word-boundary and position scoring, kept to benchmark against
+    fn fuzzy_score_baseline(query: &str, text: &str) -> u32 {
+        let query_lower = query.to_lowercase();
+        let text_lower = text.to_lowercase();
+
+        if text_lower == query_lower {
+            return 100;
+        }
+        if text_lower.starts_with(&query_lower) {
+            return 90;
+        }
+        if text_lower.contains(&query_lower) {
+            return 70;
+        }
+
+        let query_chars: Vec<char> = query_lower.chars().collect();
+        let text_chars: Vec<char> = text_lower.chars().collect();
+
+        let mut query_idx = 0;
+        let mut consecutive_bonus = 0;
+        let mut last_match_idx: Option<usize> = None;
+        for (i, c) in text_chars.iter().enumerate() {
+            if query_idx < query_chars.len() && *c == query_chars[query_idx] {
+                if last_match_idx.is_some_and(|last| i == last + 1) {
+                    consecutive_bonus += 5;
+                }
+                last_match_idx = Some(i);
+                query_idx += 1;
+            }
+        }
+
+        if query_idx == query_chars.len() {
+            let first = query_lower.chars().next().unwrap_or(' ').to_string();
+            let boundary_bonus = if text_lower.split_whitespace().any(|w| w.starts_with(&first)) {
+                10
+            } else {
+                0
+            };
+            return (40 + consecutive_bonus + boundary_bonus).min(65);
+        }
+        0
+    }
+
+    const CORPUS_STEMS: [&str; 40] = [
+        "README",
+        "main",
+        "lib",
+        "index",
+        "config",
+        "settings",
+        "Cargo",
+        "package",
+        "Makefile",
+        "docker-compose",
+        "invoice_2024",
+        "IMG_4032",
+        "Screenshot from 2024-05-01",
+        "report final",
+        "meeting-notes",
+        "TODO",
+        "CHANGELOG",
+        "LICENSE",
+        "app.module",
+        "UserController",
+        "search_config",
+        "launcher",
+        "theme",
+        "clipboardHistory",
+        "test_utils",
+        "build",
+        "vacation photo",
+        "resume",
+        "budget",
+        "presentation",
+        "backup",
+        "setup",
+        "install",
+        "requirements",
+        "webpack.config",
+        "tsconfig",
+        "HttpClient",
+        "database_schema",
+        "notes",
+        "project-plan",
+    ];
+    const CORPUS_SUFFIXES: [&str; 25] = [
+        "", "_v2", "-old", " (1)", " copy", ".backup", "_final", "-draft", "_2023", "_2024", "-en",
+        "_test", "Spec", "-1", "_a", "_b", "-new", "_orig", " - Copy", "_edit", "-wip", "_tmp",
+        ".min", "_bak", "-review",
+    ];
+    const CORPUS_EXTENSIONS: [&str; 10] = [
+        "rs", "md", "txt", "pdf", "png", "jpg", "json", "yaml", "ts", "py",
+    ];
+
+    /// 10,000 file names combining common stems, suffixes and extensions
+    fn filename_corpus() -> Vec<String> {
+        let mut names = Vec::with_capacity(10_000);
+        for stem in CORPUS_STEMS {
+            for suffix in CORPUS_SUFFIXES {
+                for ext in CORPUS_EXTENSIONS {
+                    names.push(format!("{}{}.{}", stem, suffix, ext));
+                }
+            }
+        }
+        names
+    }
+
+    /// Best-of-five time to score every corpus name against every query
+    fn time_scoring(corpus: &[String], score: impl Fn(&str, &str) -> bool) -> std::time::Duration {
+        const QUERIES: [&str; 8] = [
+            "rd", "conf", "main.rs", "uc", "notes", "scfg", "2024", "xyz",
+        ];
+        (0..5)
+            .map(|_| {
+                let start = std::time::Instant::now();
+                let mut matched = 0;
+                for query in QUERIES {
+                    for name in corpus {
+                        matched += score(query, name) as usize;
+                    }
+                }
+                std::hint::black_box(matched);
+                start.elapsed()
+            })
+            .min()
+            .unwrap_or_default()
+    }
+
+    /// `cargo test --release -- --ignored fuzzy_score_performance`
+    #[test]
+    #[ignore = "timing benchmark; run in release mode"]
+    fn fuzzy_score_performance() {
+        let corpus = filename_corpus();
+        assert_eq!(corpus.len(), 10_000);
+
+        let baseline = time_scoring(&corpus, |q, t| fuzzy_score_baseline(q, t) > 0);
+        let current = time_scoring(&corpus, |q, t| fuzzy_score(q, t, false) > 0.0);
+        assert!(
+            current.as_secs_f64() <= baseline.as_secs_f64() * 1.1,
+            "fuzzy_score regressed: {:?} vs {:?}",
+            current,
+            baseline
+        );
+    }
+
+    #[test]
+    fn absolute_paths_are_recognised() {
+        assert!(looks_like_absolute_path("/home/user/file.txt"));
+        assert!(looks_like_absolute_path(
+            "/home/user/My Documents/report final.pdf"
+        ));
+        assert!(looks_like_absolute_path("/home/user/Музыка/café.mp3"));
+        assert!(looks_like_absolute_path("/tmp/日本語/"));
+        assert!(looks_like_absolute_path("~/notes"));
+        assert!(looks_like_absolute_path("~/Pictures/été 2024/plage.jpg"));
+    }
+
+    #[test]
+    fn find_queries_are_not_paths() {
+        assert!(!looks_like_absolute_path("/readme"));
+        assert!(!looks_like_absolute_path("/my notes"));
+        assert!(!looks_like_absolute_path("/tmp/"));
+        assert!(!looks_like_absolute_path("/"));
+        assert!(!looks_like_absolute_path("~"));
+        assert!(!looks_like_absolute_path("home/user/file.txt"));
+    }
+
+    #[test]
+    fn tilde_expands_to_home() {
+        let Some(home) = dirs::home_dir() else { return };
+        assert_eq!(
+            expand_path_query("~/Café Notes/ünïcödé.txt"),
+            Some(home.join("Café Notes/ünïcödé.txt"))
+        );
+        assert_eq!(
+            expand_path_query("/srv/with space"),
+            Some(PathBuf::from("/srv/with space"))
+        );
+    }
+}
@@ -1,11 +1,47 @@
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use crate::core::settings;
+
+/// Per-directory override file, merged into the global config
+pub const LOCAL_CONFIG_FILE: &str = ".filecast.yml";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SearchConfig {
     #[serde(default)]
     pub exclude_dirs: Vec<String>,
+    /// Queries shorter than this (ignoring any @ / : ~ prefix) are not searched
+    #[serde(default = "default_min_search_length")]
+    pub min_search_length: usize,
+    /// Also grep the files `/pattern` finds by name and list their matching lines
+    #[serde(default)]
+    pub find_with_content: bool,
+    /// Match `exclude_dirs` exactly; by default `node_modules` also excludes `Node_Modules`
+    #[serde(default)]
+    pub case_sensitive_exclusions: bool,
+    /// Longer queries (e.g. an accidental paste) are cut to this many characters
+    #[serde(default = "default_max_search_query_len")]
+    pub max_search_query_len: usize,
+    /// Skip what `.gitignore` lists in `/` searches (fd does this itself; find is given
+    /// the patterns of the nearest `.gitignore`)
+    #[serde(default = "default_respect_gitignore")]
+    pub respect_gitignore: bool,
+    /// Match query case exactly; follows the Alt+C toggle rather than `search.yaml`
+    #[serde(skip)]
+    pub case_sensitive: bool,
+}
+
+fn default_min_search_length() -> usize {
+    2
+}
+
+fn default_max_search_query_len() -> usize {
+    200
+}
+
+fn default_respect_gitignore() -> bool {
+    true
 }
 
 impl Default for SearchConfig {
@@ -23,6 +59,12 @@ impl Default for SearchConfig {
                 "venv".to_string(),
                 ".cache".to_string(),
             ],
+            min_search_length: default_min_search_length(),
+            find_with_content: false,
+            case_sensitive_exclusions: false,
+            max_search_query_len: default_max_search_query_len(),
+            respect_gitignore: default_respect_gitignore(),
+            case_sensitive: false,
         }
     }
 }
@@ -54,47 +96,93 @@ impl SearchConfig {
         }
     }
 
-    fn config_path() -> PathBuf {
-        dirs::config_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join("filecast")
-            .join("search.yaml")
+    /// Load a project-local `.filecast.yml` from `dir`, if present and valid
+    pub fn load_local(dir: &Path) -> Option<Self> {
+        let content = fs::read_to_string(dir.join(LOCAL_CONFIG_FILE)).ok()?;
+        serde_yaml::from_str(&content).ok()
     }
 
-    /// Generate exclude flags for ripgrep
+    /// Union this config's exclusions with a local override's
+    pub fn merged_with(&self, local: &SearchConfig) -> SearchConfig {
+        let mut merged = self.clone();
+        for dir in &local.exclude_dirs {
+            if !merged.exclude_dirs.contains(dir) {
+                merged.exclude_dirs.push(dir.clone());
+            }
+        }
+        merged
+    }
+
+    pub fn config_path() -> PathBuf {
+        settings::config_dir().join("search.yaml")
+    }
+
+    /// Whether a query is too short to be worth searching yet
+    pub fn is_below_min_length(&self, query: &str) -> bool {
+        let text = query
+            .strip_prefix(['@', '/', ':', '~'])
+            .unwrap_or(query)
+            .trim();
+        text.chars().count() < self.min_search_length
+    }
+
+    /// Generate exclude flags for ripgrep (`--iglob` when case-insensitive)
     pub fn rg_exclude_args(&self) -> Vec<String> {
+        let flag = if self.case_sensitive_exclusions {
+            "--glob"
+        } else {
+            "--iglob"
+        };
         self.exclude_dirs
             .iter()
-            .flat_map(|dir| vec!["--glob".to_string(), format!("!{}/**", dir)])
+            .flat_map(|dir| vec![flag.to_string(), format!("!{}/**", dir)])
             .collect()
     }
 
-    /// Generate exclude flags for fd
+    /// Generate exclude flags for fd. fd has no case-insensitive exclude,
+    /// so each letter becomes a `[nN]` class instead
     pub fn fd_exclude_args(&self) -> Vec<String> {
         self.exclude_dirs
             .iter()
-            .flat_map(|dir| vec!["--exclude".to_string(), dir.clone()])
+            .flat_map(|dir| vec!["--exclude".to_string(), self.exclude_glob(dir)])
             .collect()
     }
 
-    /// Generate exclude flags for grep
+    /// Generate exclude flags for grep. `--exclude-dir` is case-sensitive,
+    /// so this uses the same `[nN]` classes as fd
     pub fn grep_exclude_args(&self) -> Vec<String> {
         self.exclude_dirs
             .iter()
-            .map(|dir| format!("--exclude-dir={}", dir))
+            .map(|dir| format!("--exclude-dir={}", self.exclude_glob(dir)))
             .collect()
     }
 
-    /// Generate exclude flags for find
+    /// Generate exclude flags for find (`-ipath` when case-insensitive)
     pub fn find_exclude_args(&self) -> Vec<String> {
+        let flag = if self.case_sensitive_exclusions {
+            "-path"
+        } else {
+            "-ipath"
+        };
         self.exclude_dirs
             .iter()
-            .flat_map(|dir| {
-                vec![
-                    "-not".to_string(),
-                    "-path".to_string(),
-                    format!("*{}*", dir),
-                ]
+            .flat_map(|dir| vec!["-not".to_string(), flag.to_string(), format!("*{}*", dir)])
+            .collect()
+    }
+
+    /// `dir` as a glob, matching either case unless exclusions are case-sensitive
+    fn exclude_glob(&self, dir: &str) -> String {
+        if self.case_sensitive_exclusions {
+            return dir.to_string();
+        }
+        dir.chars()
+            .map(|c| {
+                let (lower, upper) = (c.to_lowercase(), c.to_uppercase());
+                if lower.len() == 1 && upper.len() == 1 && c.is_alphabetic() {
+                    format!("[{}{}]", lower, upper)
+                } else {
+                    c.to_string()
+                }
             })
             .collect()
     }
@@ -1,6 +1,51 @@
 use std::fs;
 use std::path::PathBuf;
 
+use crate::core::clipboard::{self, ClipboardSort};
+use crate::core::fs::{SortDirection, SortOrder};
+use crate::core::search::SearchSortMode;
+
+/// Overrides the directory settings and search config are read from
+pub const CONFIG_DIR_ENV: &str = "FILECAST_CONFIG_DIR";
+
+/// Filecast's config directory: `$FILECAST_CONFIG_DIR` if set, else `~/.config/filecast`
+pub fn config_dir() -> PathBuf {
+    match std::env::var_os(CONFIG_DIR_ENV) {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => dirs::config_dir()
+            .or_else(fallback_config_dir)
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("filecast"),
+    }
+}
+
+/// `%USERPROFILE%\.config` when `%APPDATA%` is unset
+#[cfg(windows)]
+fn fallback_config_dir() -> Option<PathBuf> {
+    std::env::var_os("USERPROFILE").map(|profile| PathBuf::from(profile).join(".config"))
+}
+
+#[cfg(not(windows))]
+fn fallback_config_dir() -> Option<PathBuf> {
+    None
+}
+
+/// `$TERMINAL` if set, else `x-terminal-emulator` when installed, else `xterm`
+pub fn default_terminal_command() -> String {
+    if let Some(terminal) = std::env::var("TERMINAL").ok().filter(|t| !t.is_empty()) {
+        return terminal;
+    }
+
+    let has_alternative = std::env::var_os("PATH").is_some_and(|path| {
+        std::env::split_paths(&path).any(|dir| dir.join("x-terminal-emulator").is_file())
+    });
+    if has_alternative {
+        "x-terminal-emulator".to_string()
+    } else {
+        "xterm".to_string()
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum WindowPosition {
     Center,
@@ -33,26 +78,109 @@ impl Default for LauncherView {
     }
 }
 
-#[derive(Debug, Clone)]
+impl LauncherView {
+    pub const ALL: [LauncherView; 4] = [
+        LauncherView::Search,
+        LauncherView::Files,
+        LauncherView::Clipboard,
+        LauncherView::Settings,
+    ];
+
+    /// The tab to the right, wrapping around
+    pub fn next(self) -> Self {
+        match self {
+            LauncherView::Search => LauncherView::Files,
+            LauncherView::Files => LauncherView::Clipboard,
+            LauncherView::Clipboard => LauncherView::Settings,
+            LauncherView::Settings => LauncherView::Search,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            LauncherView::Search => "Search",
+            LauncherView::Files => "Files",
+            LauncherView::Clipboard => "Clipboard",
+            LauncherView::Settings => "Settings",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct LauncherSettings {
     pub position: WindowPosition,
     pub width: f32,
     pub height: f32,
     pub current_view: LauncherView,
+    pub follow_cursor_monitor: bool,
+    pub monitor_primary_selection: bool,
+    /// Paste into the previously focused window after copying a clipboard entry with Enter
+    pub auto_paste: bool,
+    pub command_output_max_lines: usize,
+    pub default_view: LauncherView,
+    /// Switch to `default_view` every time the window is shown
+    pub reset_to_default_view: bool,
+    pub files_sort_order: SortOrder,
+    pub files_sort_direction: SortDirection,
+    /// Search result order; `ByType` groups results by kind
+    pub search_sort_mode: SearchSortMode,
+    /// Last state of the Alt+C case-sensitive search toggle
+    pub search_case_sensitive: bool,
+    pub clipboard_sort: ClipboardSort,
+    /// Terminal emulator launched by `T` in the Files view, with any arguments
+    pub terminal_command: String,
+    /// Clipboard entries longer than this many characters are truncated
+    pub clipboard_max_entry_size: usize,
 }
 
 impl Default for LauncherSettings {
     fn default() -> Self {
         Self {
             position: WindowPosition::TopCenter,
-            width: 600.0,
-            height: 400.0,
+            width: 650.0,
+            height: 450.0,
             current_view: LauncherView::Search,
+            follow_cursor_monitor: false,
+            monitor_primary_selection: false,
+            auto_paste: false,
+            command_output_max_lines: 500,
+            default_view: LauncherView::Search,
+            reset_to_default_view: false,
+            files_sort_order: SortOrder::Name,
+            files_sort_direction: SortDirection::Asc,
+            search_sort_mode: SearchSortMode::ByScore,
+            search_case_sensitive: false,
+            clipboard_sort: ClipboardSort::PinnedFirst,
+            terminal_command: default_terminal_command(),
+            clipboard_max_entry_size: clipboard::DEFAULT_MAX_ENTRY_SIZE,
         }
     }
 }
 
+/// Geometry of a connected monitor in virtual screen coordinates
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Monitor {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Monitor {
+    fn contains(&self, x: f32, y: f32) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
+}
+
 impl LauncherSettings {
+    pub const MIN_WIDTH: f32 = 400.0;
+    pub const MAX_WIDTH: f32 = 1200.0;
+    pub const MIN_HEIGHT: f32 = 300.0;
+    pub const MAX_HEIGHT: f32 = 800.0;
+    pub const MIN_OUTPUT_LINES: usize = 10;
+    pub const MAX_OUTPUT_LINES: usize = 2000;
+
+    #[tracing::instrument]
     pub fn load() -> Self {
         let config_path = Self::config_path();
         if config_path.exists() {
@@ -68,15 +196,16 @@ impl LauncherSettings {
         if let Some(parent) = config_path.parent() {
             let _ = fs::create_dir_all(parent);
         }
-        let content = self.serialize();
-        let _ = fs::write(config_path, content);
+        // Write beside the file and rename over it, so a save cut short by quitting
+        // never leaves settings.conf empty or half written
+        let tmp_path = config_path.with_extension("conf.tmp");
+        if fs::write(&tmp_path, self.serialize()).is_ok() {
+            let _ = fs::rename(&tmp_path, &config_path);
+        }
     }
 
-    fn config_path() -> PathBuf {
-        dirs::config_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join("filecast")
-            .join("settings.conf")
+    pub fn config_path() -> PathBuf {
+        config_dir().join("settings.conf")
     }
 
     fn parse(content: &str) -> Self {
@@ -121,20 +250,85 @@ impl LauncherSettings {
                         };
                     }
                     "width" => {
-                        if let Ok(w) = value.parse() {
-                            settings.width = w;
+                        if let Ok(w) = value.parse::<f32>() {
+                            settings.width = w.clamp(Self::MIN_WIDTH, Self::MAX_WIDTH);
                         }
                     }
                     "height" => {
-                        if let Ok(h) = value.parse() {
-                            settings.height = h;
+                        if let Ok(h) = value.parse::<f32>() {
+                            settings.height = h.clamp(Self::MIN_HEIGHT, Self::MAX_HEIGHT);
+                        }
+                    }
+                    "follow_cursor_monitor" => {
+                        settings.follow_cursor_monitor = value == "true";
+                    }
+                    "monitor_primary_selection" => {
+                        settings.monitor_primary_selection = value == "true";
+                    }
+                    "search_case_sensitive" => {
+                        settings.search_case_sensitive = value == "true";
+                    }
+                    "auto_paste" => {
+                        settings.auto_paste = value == "true";
+                    }
+                    "command_output_max_lines" => {
+                        if let Ok(n) = value.parse::<usize>() {
+                            settings.command_output_max_lines =
+                                n.clamp(Self::MIN_OUTPUT_LINES, Self::MAX_OUTPUT_LINES);
+                        }
+                    }
+                    "default_view" => {
+                        settings.default_view = match value {
+                            "files" => LauncherView::Files,
+                            "clipboard" => LauncherView::Clipboard,
+                            "settings" => LauncherView::Settings,
+                            _ => LauncherView::Search,
+                        };
+                    }
+                    "reset_to_default_view" => {
+                        settings.reset_to_default_view = value == "true";
+                    }
+                    "files_sort_order" => {
+                        settings.files_sort_order = match value {
+                            "size" => SortOrder::Size,
+                            "date" => SortOrder::Date,
+                            _ => SortOrder::Name,
+                        };
+                    }
+                    "files_sort_direction" => {
+                        settings.files_sort_direction = match value {
+                            "desc" => SortDirection::Desc,
+                            _ => SortDirection::Asc,
+                        };
+                    }
+                    "search_sort" => {
+                        settings.search_sort_mode = match value {
+                            "name" => SearchSortMode::ByName,
+                            "modified" => SearchSortMode::ByModified,
+                            "type" => SearchSortMode::ByType,
+                            _ => SearchSortMode::ByScore,
+                        };
+                    }
+                    "clipboard_max_entry_size" => {
+                        if let Ok(n) = value.parse::<usize>() {
+                            settings.clipboard_max_entry_size = n.max(1);
                         }
                     }
+                    "terminal_command" if !value.is_empty() => {
+                        settings.terminal_command = value.to_string();
+                    }
+                    "clipboard_sort" => {
+                        settings.clipboard_sort = match value {
+                            "time" => ClipboardSort::TimeDesc,
+                            _ => ClipboardSort::PinnedFirst,
+                        };
+                    }
                     _ => {}
                 }
             }
         }
 
+        settings.current_view = settings.default_view;
         settings
     }
 
@@ -150,18 +344,95 @@ impl LauncherSettings {
             WindowPosition::Custom(x, y) => format!("custom:{},{}", x, y),
         };
 
+        let default_view_str = match self.default_view {
+            LauncherView::Search => "search",
+            LauncherView::Files => "files",
+            LauncherView::Clipboard => "clipboard",
+            LauncherView::Settings => "settings",
+        };
+
+        let files_sort_order_str = match self.files_sort_order {
+            SortOrder::Name => "name",
+            SortOrder::Size => "size",
+            SortOrder::Date => "date",
+        };
+
+        let files_sort_direction_str = match self.files_sort_direction {
+            SortDirection::Asc => "asc",
+            SortDirection::Desc => "desc",
+        };
+
+        let search_sort_str = match self.search_sort_mode {
+            SearchSortMode::ByScore => "score",
+            SearchSortMode::ByName => "name",
+            SearchSortMode::ByModified => "modified",
+            SearchSortMode::ByType => "type",
+        };
+
+        let clipboard_sort_str = match self.clipboard_sort {
+            ClipboardSort::PinnedFirst => "pinned_first",
+            ClipboardSort::TimeDesc => "time",
+        };
+
         format!(
-            "# Files Launcher Settings\nposition={}\nwidth={}\nheight={}\n",
-            position_str, self.width, self.height
+            "# Files Launcher Settings\nposition={}\nwidth={}\nheight={}\nfollow_cursor_monitor={}\nmonitor_primary_selection={}\nauto_paste={}\ncommand_output_max_lines={}\ndefault_view={}\nreset_to_default_view={}\nfiles_sort_order={}\nfiles_sort_direction={}\nsearch_sort={}\nsearch_case_sensitive={}\nclipboard_sort={}\nterminal_command={}\nclipboard_max_entry_size={}\n",
+            position_str,
+            self.width,
+            self.height,
+            self.follow_cursor_monitor,
+            self.monitor_primary_selection,
+            self.auto_paste,
+            self.command_output_max_lines,
+            default_view_str,
+            self.reset_to_default_view,
+            files_sort_order_str,
+            files_sort_direction_str,
+            search_sort_str,
+            self.search_case_sensitive,
+            clipboard_sort_str,
+            self.terminal_command,
+            self.clipboard_max_entry_size
         )
     }
 
+    /// Position on the primary monitor
     pub fn get_window_position(&self) -> egui::Pos2 {
-        let (screen_width, screen_height) = Self::detect_screen_size();
+        let primary = Self::detect_screen_size()
+            .into_iter()
+            .next()
+            .unwrap_or(Monitor {
+                x: 0.0,
+                y: 0.0,
+                width: 1920.0,
+                height: 1080.0,
+            });
+
+        self.position_on(&primary)
+    }
+
+    /// Window position on the monitor under the mouse cursor, if detectable. Centered windows
+    /// always follow the cursor; other presets only with `follow_cursor_monitor`.
+    /// Custom positions are absolute and never follow the cursor.
+    pub fn get_cursor_monitor_position(&self) -> Option<egui::Pos2> {
+        let follows = self.follow_cursor_monitor || self.position == WindowPosition::Center;
+        if !follows || matches!(self.position, WindowPosition::Custom(..)) {
+            return None;
+        }
+
+        let (cursor_x, cursor_y) = Self::detect_cursor_position()?;
+        let monitor = Self::detect_screen_size()
+            .into_iter()
+            .find(|m| m.contains(cursor_x, cursor_y))?;
+
+        Some(self.position_on(&monitor))
+    }
+
+    fn position_on(&self, monitor: &Monitor) -> egui::Pos2 {
+        let (screen_width, screen_height) = (monitor.width, monitor.height);
 
         let margin = 30.0;
 
-        match self.position {
+        let pos = match self.position {
             WindowPosition::Center => egui::pos2(
                 (screen_width - self.width) / 2.0,
                 (screen_height - self.height) / 2.0,
@@ -181,55 +452,92 @@ impl LauncherSettings {
                 screen_width - self.width - margin,
                 screen_height - self.height - margin,
             ),
-            WindowPosition::Custom(x, y) => egui::pos2(x as f32, y as f32),
-        }
+            WindowPosition::Custom(x, y) => return egui::pos2(x as f32, y as f32),
+        };
+
+        pos + egui::vec2(monitor.x, monitor.y)
     }
 
-    fn detect_screen_size() -> (f32, f32) {
-        // Try xrandr first (works on X11)
-        if let Ok(output) = std::process::Command::new("xrandr")
+    /// Connected monitor geometries (`WxH+X+Y`) from `xrandr --current`, primary first
+    fn detect_screen_size() -> Vec<Monitor> {
+        let Ok(output) = std::process::Command::new("xrandr")
             .arg("--current")
             .output()
-        {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            for line in stdout.lines() {
-                if line.contains(" connected") && line.contains(" primary") {
-                    if let Some(res) = line.split_whitespace().find(|s| {
-                        s.contains('x')
-                            && s.chars()
-                                .next()
-                                .map(|c| c.is_ascii_digit())
-                                .unwrap_or(false)
-                    }) {
-                        let res = res.split('+').next().unwrap_or(res);
-                        if let Some((w, h)) = res.split_once('x') {
-                            if let (Ok(width), Ok(height)) = (w.parse::<f32>(), h.parse::<f32>()) {
-                                return (width, height);
-                            }
-                        }
-                    }
-                }
-            }
-            for line in stdout.lines() {
-                if line.contains(" connected") {
-                    if let Some(res) = line.split_whitespace().find(|s| {
-                        s.contains('x')
-                            && s.chars()
-                                .next()
-                                .map(|c| c.is_ascii_digit())
-                                .unwrap_or(false)
-                    }) {
-                        let res = res.split('+').next().unwrap_or(res);
-                        if let Some((w, h)) = res.split_once('x') {
-                            if let (Ok(width), Ok(height)) = (w.parse::<f32>(), h.parse::<f32>()) {
-                                return (width, height);
-                            }
-                        }
-                    }
-                }
+        else {
+            return Vec::new();
+        };
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let mut monitors: Vec<(bool, Monitor)> = stdout
+            .lines()
+            .filter(|line| line.contains(" connected"))
+            .filter_map(|line| {
+                let monitor = line.split_whitespace().find_map(Self::parse_geometry)?;
+                Some((line.contains(" primary"), monitor))
+            })
+            .collect();
+        monitors.sort_by_key(|(primary, _)| !primary);
+
+        monitors.into_iter().map(|(_, monitor)| monitor).collect()
+    }
+
+    fn parse_geometry(s: &str) -> Option<Monitor> {
+        let (size, offset) = s.split_once('+')?;
+        let (w, h) = size.split_once('x')?;
+        let (x, y) = offset.split_once('+')?;
+
+        Some(Monitor {
+            x: x.parse().ok()?,
+            y: y.parse().ok()?,
+            width: w.parse().ok()?,
+            height: h.parse().ok()?,
+        })
+    }
+
+    /// Global cursor position via `xdotool` (X11 only)
+    fn detect_cursor_position() -> Option<(f32, f32)> {
+        let output = std::process::Command::new("xdotool")
+            .args(["getmouselocation", "--shell"])
+            .output()
+            .ok()?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let mut x = None;
+        let mut y = None;
+        for line in stdout.lines() {
+            if let Some(value) = line.strip_prefix("X=") {
+                x = value.trim().parse().ok();
+            } else if let Some(value) = line.strip_prefix("Y=") {
+                y = value.trim().parse().ok();
             }
         }
 
-        (1920.0, 1080.0)
+        Some((x?, y?))
+    }
+}
+
+#[cfg(test)]
+mod path_tests {
+    use super::*;
+
+    #[test]
+    fn config_path_ends_with_settings_file() {
+        let path = LauncherSettings::config_path();
+        assert_eq!(
+            path.file_name().and_then(|n| n.to_str()),
+            Some("settings.conf")
+        );
+        assert_eq!(path.parent(), Some(config_dir().as_path()));
+    }
+
+    #[test]
+    fn config_dir_is_named_filecast() {
+        if std::env::var_os(CONFIG_DIR_ENV).is_some() {
+            return;
+        }
+        assert_eq!(
+            config_dir().file_name().and_then(|n| n.to_str()),
+            Some("filecast")
+        );
     }
 }
@@ -0,0 +1,107 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use crate::core::search::SearchResult;
+
+/// A concrete `Host` entry from the SSH config
+#[derive(Debug, Clone, PartialEq)]
+struct SshHost {
+    alias: String,
+    hostname: Option<String>,
+    user: Option<String>,
+}
+
+pub fn ssh_config_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".ssh").join("config"))
+}
+
+/// Hosts from `~/.ssh/config` as search results, skipping wildcard patterns
+pub fn discover_ssh_hosts() -> Vec<SearchResult> {
+    let Some(content) = ssh_config_path().and_then(|path| fs::read_to_string(path).ok()) else {
+        return Vec::new();
+    };
+
+    parse_ssh_config(&content)
+        .into_iter()
+        .map(|host| {
+            let hostname = host.hostname.unwrap_or_else(|| host.alias.clone());
+            SearchResult::ssh_host(host.alias, hostname, host.user, 0.0)
+        })
+        .collect()
+}
+
+fn parse_ssh_config(content: &str) -> Vec<SshHost> {
+    let mut hosts: Vec<SshHost> = Vec::new();
+    // Hosts declared by the current `Host` line, which the following options apply to
+    let mut current = 0..0;
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (keyword, value) = match line.split_once(|c: char| c.is_whitespace() || c == '=') {
+            Some((keyword, value)) => (keyword, value.trim_start_matches([' ', '\t', '=']).trim()),
+            None => (line, ""),
+        };
+
+        match keyword.to_ascii_lowercase().as_str() {
+            "host" => {
+                let start = hosts.len();
+                for alias in value.split_whitespace() {
+                    if alias.contains(['*', '?', '!']) || hosts.iter().any(|h| h.alias == alias) {
+                        continue;
+                    }
+                    hosts.push(SshHost {
+                        alias: alias.to_string(),
+                        hostname: None,
+                        user: None,
+                    });
+                }
+                current = start..hosts.len();
+            }
+            "match" => current = hosts.len()..hosts.len(),
+            // ssh uses the first value it sees for each option
+            "hostname" => {
+                for host in &mut hosts[current.clone()] {
+                    host.hostname.get_or_insert_with(|| value.to_string());
+                }
+            }
+            "user" => {
+                for host in &mut hosts[current.clone()] {
+                    host.user.get_or_insert_with(|| value.to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    hosts
+}
+
+/// SSH host results, re-read whenever `~/.ssh/config` changes on disk
+#[derive(Default)]
+pub struct SshHostCache {
+    modified: Option<SystemTime>,
+    hosts: Vec<SearchResult>,
+}
+
+impl SshHostCache {
+    pub fn hosts(&mut self) -> &[SearchResult] {
+        let modified = ssh_config_path()
+            .and_then(|path| fs::metadata(path).ok())
+            .and_then(|meta| meta.modified().ok());
+
+        if modified != self.modified {
+            self.hosts = if modified.is_some() {
+                discover_ssh_hosts()
+            } else {
+                Vec::new()
+            };
+            self.modified = modified;
+        }
+        &self.hosts
+    }
+}
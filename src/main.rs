@@ -5,24 +5,47 @@ use global_hotkey::{
     hotkey::{Code, HotKey, Modifiers},
 };
 use std::fs;
-use std::path::PathBuf;
-use std::sync::mpsc;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock, mpsc};
+use std::time::{Duration, Instant};
 
 mod core;
 mod ui;
 
-use crate::core::app::App;
-use crate::core::history;
-use crate::core::settings::{LauncherSettings, WindowPosition};
+use crate::core::app::{App, STATUS_TIMEOUT};
+use crate::core::config_watch::{ConfigFile, ConfigWatcher};
+use crate::core::search_config::SearchConfig;
+use crate::core::settings::{self, LauncherSettings, LauncherView};
 use crate::ui::launcher::LauncherUI;
 
 fn main() -> Result<()> {
+    if std::env::args()
+        .skip(1)
+        .any(|arg| arg == "--help" || arg == "-h")
+    {
+        print_help();
+        return Ok(());
+    }
+
+    init_tracing();
+
+    let startup = tracing::info_span!("startup").entered();
+
     let db_path = get_db_path()?;
-    let db_conn = history::initialise(&db_path)?;
 
-    let settings = LauncherSettings::load();
+    let settings = {
+        let _span = tracing::info_span!("settings_load").entered();
+        LauncherSettings::load()
+    };
 
-    let app = App::new(db_conn)?;
+    let mut app = {
+        let _span = tracing::info_span!("app_new").entered();
+        App::new(&db_path)?
+    };
+    app.state
+        .clipboard_monitor
+        .set_primary_selection(settings.monitor_primary_selection);
+    app.ui_state.apply_settings(&mut app.state, &settings);
 
     let hotkey_manager = GlobalHotKeyManager::new().expect("Failed to create hotkey manager");
 
@@ -32,12 +55,18 @@ fn main() -> Result<()> {
         .expect("Failed to register hotkey");
 
     let (hotkey_tx, hotkey_rx) = mpsc::channel();
+    // Set once the UI exists; the hidden window otherwise sleeps through the hotkey
+    let hotkey_repaint: Arc<OnceLock<egui::Context>> = Arc::new(OnceLock::new());
+    let hotkey_repaint_thread = Arc::clone(&hotkey_repaint);
 
     std::thread::spawn(move || {
         loop {
             if let Ok(event) = GlobalHotKeyEvent::receiver().recv() {
                 if event.state == HotKeyState::Pressed {
                     let _ = hotkey_tx.send(event);
+                    if let Some(ctx) = hotkey_repaint_thread.get() {
+                        ctx.request_repaint();
+                    }
                 }
             }
         }
@@ -46,8 +75,8 @@ fn main() -> Result<()> {
     let icon = load_icon();
 
     let mut viewport = egui::ViewportBuilder::default()
-        .with_inner_size([650.0, 450.0])
-        .with_min_inner_size([650.0, 100.0])
+        .with_inner_size([settings.width, settings.height])
+        .with_min_inner_size([settings.width, 100.0])
         .with_decorations(false)
         .with_always_on_top()
         .with_resizable(false)
@@ -57,22 +86,39 @@ fn main() -> Result<()> {
         viewport = viewport.with_icon(std::sync::Arc::new(icon_data));
     }
 
-    let use_centered = matches!(settings.position, WindowPosition::Center);
-    if !use_centered {
-        viewport = viewport.with_position(settings.get_window_position());
-    }
+    // Computed explicitly rather than with `centered`, which always uses the primary monitor
+    let position = settings
+        .get_cursor_monitor_position()
+        .unwrap_or_else(|| settings.get_window_position());
+    viewport = viewport.with_position(position);
+
+    let applied_size = (settings.width, settings.height);
 
     let options = eframe::NativeOptions {
         viewport,
-        centered: use_centered,
         ..Default::default()
     };
 
+    let config_watcher = match ConfigWatcher::start() {
+        Ok(watcher) => Some(watcher),
+        Err(e) => {
+            tracing::warn!("Config hot-reload disabled: {}", e);
+            None
+        }
+    };
+
+    drop(startup);
+
     let result = eframe::run_native(
         "Filecast",
         options,
         Box::new(move |cc| {
+            let _span = tracing::info_span!("ui_create").entered();
             configure_fonts(&cc.egui_ctx);
+            let _ = hotkey_repaint.set(cc.egui_ctx.clone());
+            app.state
+                .clipboard_monitor
+                .set_repaint_context(cc.egui_ctx.clone());
 
             Ok(Box::new(LauncherApp {
                 app,
@@ -80,6 +126,14 @@ fn main() -> Result<()> {
                 hotkey_rx,
                 settings,
                 was_visible: true,
+                applied_size,
+                config_watcher,
+                touch_start: None,
+                touch_start_time: 0.0,
+                visibility_check_at: None,
+                settings_dirty: false,
+                settings_last_changed: Instant::now(),
+                settings_save: None,
             }))
         }),
     );
@@ -87,6 +141,37 @@ fn main() -> Result<()> {
     std::process::exit(if result.is_ok() { 0 } else { 1 });
 }
 
+/// Log to stderr when `FILECAST_LOG` is set (e.g. `FILECAST_LOG=debug`) or
+/// when started with `--profile`.
+fn init_tracing() {
+    use tracing_subscriber::EnvFilter;
+    use tracing_subscriber::fmt::format::FmtSpan;
+
+    let profile = std::env::args().skip(1).any(|arg| arg == "--profile");
+    let filter = match std::env::var("FILECAST_LOG") {
+        Ok(level) => EnvFilter::new(level),
+        Err(_) if profile => EnvFilter::new("debug"),
+        Err(_) => return,
+    };
+
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_span_events(FmtSpan::CLOSE)
+        .with_writer(std::io::stderr)
+        .init();
+}
+
+/// How long after showing or hiding the window to check the viewport actually followed
+const VISIBILITY_CHECK_DELAY: Duration = Duration::from_millis(150);
+
+/// Shortest touch drag that counts as a swipe
+const SWIPE_MIN_DISTANCE: f32 = 50.0;
+/// Slower drags are treated as scrolling rather than swipes, in points per second
+const SWIPE_MIN_VELOCITY: f32 = 300.0;
+
+/// How long settings must stay unchanged before they are written to disk
+const SETTINGS_SAVE_DELAY: Duration = Duration::from_secs(2);
+
 fn load_icon() -> Option<egui::IconData> {
     let icon_bytes = include_bytes!("assets/icon.png");
 
@@ -102,23 +187,87 @@ fn load_icon() -> Option<egui::IconData> {
 }
 
 fn get_db_path() -> Result<PathBuf> {
-    let config_dir = dirs::config_dir()
-        .context("Failed to get config directory")?
-        .join("filecast");
+    if let Some(db_path) = std::env::var_os("FILECAST_DB").filter(|p| !p.is_empty()) {
+        let db_path = PathBuf::from(db_path);
+        let parent = db_path
+            .parent()
+            .filter(|p| !p.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        if !parent.is_dir() {
+            anyhow::bail!("FILECAST_DB directory does not exist: {}", parent.display());
+        }
+        return Ok(db_path);
+    }
+
+    let config_dir = settings::config_dir();
 
     fs::create_dir_all(&config_dir).context("Failed to create config directory")?;
 
     Ok(config_dir.join("history.db"))
 }
 
+fn print_help() {
+    println!(
+        "Filecast {}
+{}
+
+USAGE:
+    filecast [OPTIONS]
+
+OPTIONS:
+    --profile    Print startup timing spans to stderr
+    -h, --help   Print this help
+
+ENVIRONMENT:
+    FILECAST_DB          Path to the history database (default: <config dir>/history.db)
+    FILECAST_CONFIG_DIR  Directory for settings.conf and search.yaml (default: ~/.config/filecast)
+    FILECAST_LOG         Log filter for tracing output, e.g. debug
+    FILECAST_EMOJI_FONT  Emoji font file (default: NotoColorEmoji.ttf from the system font dirs)",
+        env!("CARGO_PKG_VERSION"),
+        env!("CARGO_PKG_DESCRIPTION")
+    );
+}
+
+/// Overrides where the emoji font is loaded from
+const EMOJI_FONT_ENV: &str = "FILECAST_EMOJI_FONT";
+
+/// Where common platforms and distros install a color emoji font
+const EMOJI_FONT_PATHS: &[&str] = &[
+    "/usr/share/fonts/truetype/noto/NotoColorEmoji.ttf",
+    "/usr/share/fonts/noto/NotoColorEmoji.ttf",
+    "/usr/share/fonts/google-noto-emoji/NotoColorEmoji.ttf",
+    "/usr/share/fonts/noto-emoji/NotoColorEmoji.ttf",
+    "C:\\Windows\\Fonts\\seguiemj.ttf",
+];
+
+/// Read the emoji font from `$FILECAST_EMOJI_FONT` or the first standard location that has one
+fn load_emoji_font() -> Option<Vec<u8>> {
+    if let Some(path) = std::env::var_os(EMOJI_FONT_ENV).filter(|p| !p.is_empty()) {
+        match fs::read(&path) {
+            Ok(bytes) => return Some(bytes),
+            Err(e) => tracing::warn!(
+                "Failed to read {} ({}): {}",
+                EMOJI_FONT_ENV,
+                Path::new(&path).display(),
+                e
+            ),
+        }
+    }
+
+    EMOJI_FONT_PATHS.iter().find_map(|path| fs::read(path).ok())
+}
+
 fn configure_fonts(ctx: &egui::Context) {
+    let Some(emoji_font) = load_emoji_font() else {
+        tracing::warn!("No emoji font found, icons will render as boxes");
+        return;
+    };
+
     let mut fonts = egui::FontDefinitions::default();
 
     fonts.font_data.insert(
         "emoji".to_owned(),
-        std::sync::Arc::new(egui::FontData::from_static(include_bytes!(
-            "/usr/share/fonts/truetype/noto/NotoColorEmoji.ttf"
-        ))),
+        std::sync::Arc::new(egui::FontData::from_owned(emoji_font)),
     );
 
     for family in fonts.families.values_mut() {
@@ -134,42 +283,296 @@ struct LauncherApp {
     hotkey_rx: mpsc::Receiver<GlobalHotKeyEvent>,
     settings: LauncherSettings,
     was_visible: bool,
+    applied_size: (f32, f32),
+    config_watcher: Option<ConfigWatcher>,
+    touch_start: Option<egui::Pos2>,
+    touch_start_time: f64,
+    /// When to compare the intended and actual window visibility after a toggle
+    visibility_check_at: Option<Instant>,
+    /// Settings were changed and not yet written to disk
+    settings_dirty: bool,
+    settings_last_changed: Instant,
+    /// The background save in progress, joined before the next one starts
+    settings_save: Option<std::thread::JoinHandle<()>>,
+}
+
+impl LauncherApp {
+    /// Record what the viewport reports and, shortly after a toggle, re-send the show/hide
+    /// commands if the window didn't follow (e.g. the hotkey fired twice in quick succession)
+    fn sync_window_visibility(&mut self, ctx: &egui::Context) {
+        let state = &mut self.app.ui_state.window_visibility_state;
+        state.intended = self.app.ui_state.window_visible;
+        if let Some(focused) = ctx.input(|i| i.viewport().focused) {
+            state.actual = focused;
+        }
+
+        let Some(check_at) = self.visibility_check_at else {
+            return;
+        };
+        if Instant::now() < check_at {
+            return;
+        }
+        self.visibility_check_at = None;
+
+        if state.is_settled() {
+            return;
+        }
+        tracing::debug!(
+            "Window visibility out of sync (intended {}), re-sending",
+            state.intended
+        );
+        if state.intended {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Visible(true));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+        } else {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Visible(false));
+        }
+    }
+
+    /// Turn touchscreen swipes into navigation: right-to-left opens the selected entry,
+    /// left-to-right goes up a directory, and bottom-to-top on the tab bar switches tabs
+    fn handle_swipes(&mut self, ctx: &egui::Context) {
+        let (touches, time) = ctx.input(|i| {
+            let touches: Vec<(egui::TouchPhase, egui::Pos2)> = i
+                .events
+                .iter()
+                .filter_map(|e| match e {
+                    egui::Event::Touch { phase, pos, .. } => Some((*phase, *pos)),
+                    _ => None,
+                })
+                .collect();
+            (touches, i.time)
+        });
+
+        for (phase, pos) in touches {
+            match phase {
+                egui::TouchPhase::Start => {
+                    self.touch_start = Some(pos);
+                    self.touch_start_time = time;
+                }
+                egui::TouchPhase::Move => {}
+                egui::TouchPhase::Cancel => self.touch_start = None,
+                egui::TouchPhase::End => {
+                    if let Some(start) = self.touch_start.take() {
+                        let elapsed = (time - self.touch_start_time).max(0.001) as f32;
+                        self.handle_swipe(start, pos - start, elapsed);
+                    }
+                }
+            }
+        }
+    }
+
+    fn handle_swipe(&mut self, start: egui::Pos2, delta: egui::Vec2, elapsed: f32) {
+        let distance = delta.length();
+        if distance < SWIPE_MIN_DISTANCE || distance / elapsed < SWIPE_MIN_VELOCITY {
+            return;
+        }
+
+        if delta.y.abs() > delta.x.abs() {
+            if delta.y < 0.0 && self.ui.tab_bar_rect.contains(start) {
+                self.settings.current_view = self.settings.current_view.next();
+                self.ui.request_scroll_to_selected();
+            }
+            return;
+        }
+
+        if self.settings.current_view != LauncherView::Files {
+            return;
+        }
+        if delta.x < 0.0 {
+            let _ = self.app.ui_state.enter_selected(&mut self.app.state);
+        } else {
+            let _ = self.app.ui_state.go_up(&mut self.app.state);
+        }
+        self.ui.selected_file = self.app.ui_state.selected_index;
+        self.ui.request_scroll_to_selected();
+    }
+
+    /// Write changed settings once they have settled, on a background thread so a slow
+    /// disk doesn't stall the frame
+    fn save_settings_if_settled(&mut self) {
+        if std::mem::take(&mut self.ui.settings_changed) {
+            self.settings_dirty = true;
+            self.settings_last_changed = Instant::now();
+        }
+        if !self.settings_dirty || self.settings_last_changed.elapsed() < SETTINGS_SAVE_DELAY {
+            return;
+        }
+
+        // Saves must land in order; if the last one is still writing, try again next frame
+        if self
+            .settings_save
+            .as_ref()
+            .is_some_and(|save| !save.is_finished())
+        {
+            return;
+        }
+        let settings = self.settings.clone();
+        self.settings_save = Some(std::thread::spawn(move || settings.save()));
+        self.settings_dirty = false;
+    }
+
+    /// Apply edits made to the config files outside the app
+    fn reload_changed_config(&mut self) {
+        let Some(watcher) = self.config_watcher.as_mut() else {
+            return;
+        };
+
+        for file in watcher.poll() {
+            match file {
+                ConfigFile::Settings => {
+                    // Don't let an earlier save coming back undo edits still waiting to be saved
+                    if self.settings_dirty {
+                        continue;
+                    }
+                    let mut loaded = LauncherSettings::load();
+                    loaded.current_view = self.settings.current_view;
+                    // Our own saves come back through the watcher too
+                    if loaded == self.settings {
+                        continue;
+                    }
+                    self.app
+                        .state
+                        .clipboard_monitor
+                        .set_primary_selection(loaded.monitor_primary_selection);
+                    self.app
+                        .ui_state
+                        .apply_settings(&mut self.app.state, &loaded);
+                    self.settings = loaded;
+                    self.app
+                        .ui_state
+                        .set_status("Reloaded settings.conf", Some(STATUS_TIMEOUT));
+                }
+                ConfigFile::SearchConfig => {
+                    let loaded = SearchConfig::load();
+                    if loaded == self.app.state.search_config {
+                        continue;
+                    }
+                    self.app.state.search_config = loaded;
+                    self.app.state.refresh_active_search_config();
+                    self.app
+                        .ui_state
+                        .set_status("Reloaded search.yaml", Some(STATUS_TIMEOUT));
+                }
+            }
+        }
+    }
+}
+
+/// Quitting before the debounced save ran would lose the last edits, so save them now
+impl Drop for LauncherApp {
+    fn drop(&mut self) {
+        if let Some(save) = self.settings_save.take() {
+            let _ = save.join();
+        }
+        if self.settings_dirty || self.ui.settings_changed {
+            self.settings.save();
+        }
+    }
 }
 
 impl eframe::App for LauncherApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        self.app.check_clipboard_updates();
+        let App { state, ui_state } = &mut self.app;
+        if state.check_clipboard_updates() {
+            state.refresh_clipboard(&ui_state.clipboard_query);
+        }
+        if state.poll_search() {
+            ctx.request_repaint();
+        }
+        if ui_state.poll_file_operation(state) {
+            ctx.request_repaint();
+        }
+        if ui_state.poll_preview() {
+            ctx.request_repaint();
+        }
+        if ui_state.poll_command_output(state) {
+            ctx.request_repaint();
+        }
+        if ui_state.poll_applications(state) {
+            ctx.request_repaint();
+        }
+        if ui_state.expire_status() {
+            ctx.request_repaint();
+        } else if let Some(expires) = ui_state.status_message_expires {
+            ctx.request_repaint_after(expires.saturating_duration_since(Instant::now()));
+        }
+        self.reload_changed_config();
+        self.handle_swipes(ctx);
 
         while let Ok(_event) = self.hotkey_rx.try_recv() {
-            self.app.window_visible = !self.app.window_visible;
-            if self.app.window_visible {
-                self.app.search_query.clear();
-                self.app.search_results.clear();
-                self.app.refresh_history();
-                self.app.refresh_clipboard();
-                let _ = self.app.refresh_directory();
+            self.app.ui_state.window_visible = !self.app.ui_state.window_visible;
+            if self.app.ui_state.window_visible {
+                self.app.ui_state.search_query.clear();
+                self.app.state.search_state.clear();
+                self.app.state.refresh_history();
+                self.app
+                    .state
+                    .refresh_clipboard(&self.app.ui_state.clipboard_query);
+                let _ = self.app.ui_state.refresh_directory(&mut self.app.state);
                 self.ui.search_focused = true;
             }
         }
 
-        if self.app.window_visible != self.was_visible {
-            if self.app.window_visible {
+        if self.app.ui_state.window_visible != self.was_visible {
+            if self.app.ui_state.window_visible {
+                if self.settings.reset_to_default_view {
+                    self.settings.current_view = self.settings.default_view;
+                }
+                if let Some(pos) = self.settings.get_cursor_monitor_position() {
+                    ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(pos));
+                }
                 ctx.send_viewport_cmd(egui::ViewportCommand::Visible(true));
                 ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
             } else {
                 ctx.send_viewport_cmd(egui::ViewportCommand::Visible(false));
             }
-            self.was_visible = self.app.window_visible;
+            self.was_visible = self.app.ui_state.window_visible;
+            self.visibility_check_at = Some(Instant::now() + VISIBILITY_CHECK_DELAY);
+        }
+        self.sync_window_visibility(ctx);
+
+        let size = (self.settings.width, self.settings.height);
+        if size != self.applied_size {
+            ctx.send_viewport_cmd(egui::ViewportCommand::MinInnerSize(egui::vec2(
+                size.0, 100.0,
+            )));
+            ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(egui::vec2(size.0, size.1)));
+            self.applied_size = size;
         }
 
-        if self.app.should_quit {
+        if self.app.ui_state.should_quit {
             ctx.send_viewport_cmd(egui::ViewportCommand::Close);
             return;
         }
 
-        self.ui.show(ctx, &mut self.app, &mut self.settings);
-
-        ctx.request_repaint_after(std::time::Duration::from_millis(250));
+        self.ui.show(
+            ctx,
+            &mut self.app.ui_state,
+            &mut self.app.state,
+            &mut self.settings,
+        );
+        self.save_settings_if_settled();
+
+        // The hotkey and clipboard threads wake the UI themselves, so only redraw
+        // eagerly while something is actually moving on screen
+        if !self.app.ui_state.window_visible {
+            ctx.request_repaint_after(Duration::from_secs(1));
+        } else if self.ui.wants_repaint() {
+            ctx.request_repaint();
+        } else {
+            let idle = if self.app.has_background_work() {
+                Duration::from_millis(50)
+            } else {
+                Duration::from_millis(250)
+            };
+            // A toast only animates while it fades, so wake up for the next fade
+            match self.app.ui_state.next_toast_repaint() {
+                Some(delay) if delay.is_zero() => ctx.request_repaint(),
+                Some(delay) => ctx.request_repaint_after(delay.min(idle)),
+                None => ctx.request_repaint_after(idle),
+            }
+        }
     }
 
     fn clear_color(&self, _visuals: &egui::Visuals) -> [f32; 4] {
@@ -1,9 +1,17 @@
 use egui::{CentralPanel, Context, Frame, Key, RichText, ScrollArea, TextEdit, Ui};
-
-use crate::core::app::App;
-use crate::core::clipboard;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::Read;
+use std::time::{Duration, Instant};
+
+use crate::core::app::{
+    AppState, ExecutedResultKind, STATUS_TIMEOUT, TOAST_FADE, ToastKind, UiState, describe_files,
+    open_ssh_session, open_terminal_here, reveal_in_folder,
+};
+use crate::core::clipboard::{self, ClipboardEntry, ClipboardSort};
+use crate::core::fs::{self, FileOpKind};
 use crate::core::history;
-use crate::core::search::SearchResultKind;
+use crate::core::mode::{AppMode, ModeEvent};
+use crate::core::search::{SearchProvider, SearchResultKind};
 use crate::core::settings::{LauncherSettings, LauncherView, WindowPosition};
 use crate::ui::theme;
 
@@ -11,20 +19,154 @@ use crate::ui::theme;
 enum ClipboardAction {
     Copy,
     TogglePin,
+    PinAsTemplate,
     Delete,
 }
 
+/// A template entry being filled in: its text and a value for each placeholder
+struct TemplateDialog {
+    content: String,
+    values: Vec<(String, String)>,
+}
+
 const OUTER_MARGIN: f32 = 16.0;
 const ITEM_HEIGHT: f32 = 36.0;
+const DEFAULT_WIDTH: f32 = 650.0;
+const COMPACT_TABS_WIDTH: f32 = 560.0;
+/// `(shortcut, description)` shown when hovering each tab, in tab order
+const TAB_TOOLTIPS: &[(&str, &str)] = &[
+    ("Ctrl+1", "Search apps, files, commands"),
+    ("Ctrl+2", "Browse the filesystem"),
+    ("Ctrl+3", "Clipboard history"),
+    ("Ctrl+4", "Window, search and history settings"),
+];
+/// Session command history kept for the Files view command mode
+const MAX_FILES_COMMAND_HISTORY: usize = 20;
+/// Characters of a `:` command shown before it is cut off with an ellipsis
+const COMMAND_PREVIEW_CHARS: usize = 200;
+/// A second Escape within this long hides the window from Search whatever it holds
+const DOUBLE_ESCAPE_INTERVAL: Duration = Duration::from_millis(400);
+/// Minimum gap between keyboard-driven scrolls, so held arrow keys don't jitter
+const SCROLL_DEBOUNCE: Duration = Duration::from_millis(50);
+
+/// Shortcuts listed by the `?` overlay: (keys, description, view they apply to)
+const HELP_TEXT: &[(&str, &str, LauncherView)] = &[
+    ("↑/↓", "Navigate results", LauncherView::Search),
+    ("Enter", "Execute / open selected", LauncherView::Search),
+    (
+        "Tab",
+        "Cycle the selected app's actions",
+        LauncherView::Search,
+    ),
+    ("Ctrl+S", "Cycle result sort", LauncherView::Search),
+    (
+        "Alt+C",
+        "Toggle case-sensitive search",
+        LauncherView::Search,
+    ),
+    (
+        "Ctrl+C",
+        "Copy path of selected result",
+        LauncherView::Search,
+    ),
+    ("m", "Toggle Most Opened", LauncherView::Search),
+    (
+        "Escape",
+        "Clear search, then unfocus, then hide",
+        LauncherView::Search,
+    ),
+    ("Escape Escape", "Hide right away", LauncherView::Search),
+    ("↑/↓ j/k", "Navigate files", LauncherView::Files),
+    (
+        "→ l Enter",
+        "Open file / enter directory",
+        LauncherView::Files,
+    ),
+    (
+        "← h Backspace",
+        "Go to parent directory",
+        LauncherView::Files,
+    ),
+    ("r", "Refresh directory", LauncherView::Files),
+    (
+        "s / Shift+S",
+        "Cycle sort / flip direction",
+        LauncherView::Files,
+    ),
+    ("Shift+T", "Open a terminal here", LauncherView::Files),
+    ("Ctrl+C", "Copy path of selected file", LauncherView::Files),
+    ("Ctrl+I", "Toggle file statistics", LauncherView::Files),
+    (
+        "Ctrl+Z",
+        "Undo last move, copy or trash",
+        LauncherView::Files,
+    ),
+    ("c", "Command mode", LauncherView::Files),
+    ("o", "Open with an application", LauncherView::Files),
+    (
+        "v",
+        "Visual mode (y: yank, d: trash, Enter: open with)",
+        LauncherView::Files,
+    ),
+    (
+        "p / Shift+P",
+        "Copy / move yanked files here",
+        LauncherView::Files,
+    ),
+    ("Escape", "Exit command / visual mode", LauncherView::Files),
+    ("↑/↓ j/k", "Navigate entries", LauncherView::Clipboard),
+    (
+        "Enter",
+        "Copy selected to clipboard",
+        LauncherView::Clipboard,
+    ),
+    ("Space", "Select / deselect entry", LauncherView::Clipboard),
+    ("Ctrl+A", "Select all entries", LauncherView::Clipboard),
+    (
+        "e",
+        "Expand / collapse full text, or fill in a template",
+        LauncherView::Clipboard,
+    ),
+    ("/", "Search entry text", LauncherView::Clipboard),
+    (
+        "p",
+        "Pin / unpin entry (or all selected)",
+        LauncherView::Clipboard,
+    ),
+    ("Shift+P", "Pin as template", LauncherView::Clipboard),
+    (
+        "d",
+        "Delete entry (or all selected)",
+        LauncherView::Clipboard,
+    ),
+    ("Escape", "Clear selection", LauncherView::Clipboard),
+];
+
+/// Shortcuts that work in every view, shown under each view's in the `?` overlay
+const GLOBAL_HELP_TEXT: &[(&str, &str)] = &[
+    ("Super+Space", "Toggle Filecast"),
+    ("Ctrl+1/2/3/4", "Switch views"),
+    ("Tab", "Next view"),
+    ("Escape", "Back to the previous view, then the default view"),
+    ("?", "Show / hide this help"),
+];
 
 pub struct LauncherUI {
     pub selected_result: usize,
+    /// Result under the cursor and its preview, read once when the hover starts
+    hovered_result_idx: Option<usize>,
+    hover_preview: Option<String>,
+    /// `(result index, action index)` of a desktop action chosen with Tab
+    selected_action: Option<(usize, usize)>,
     pub selected_file: usize,
     pub selected_recent: usize,
     pub selected_clipboard: usize,
     pub search_focused: bool,
     pub command_output: Option<String>,
+    running_command: Option<String>,
     scroll_to_selected: bool,
+    last_scroll_request: Instant,
+    accumulated_scroll_delta: f32,
     pub files_command_mode: bool,
     pub files_command_input: String,
     pub exclude_input: String,
@@ -33,19 +175,61 @@ pub struct LauncherUI {
     pub tab_completion_index: usize,
     pub tab_original_token: String,
     cursor_to_end: bool,
+    /// Cursor (in chars) to place in the command input after a line-editing shortcut
+    command_cursor: Option<usize>,
+    /// Commands run this session, newest first
+    pub files_command_history: VecDeque<String>,
+    /// Position while cycling history with ↑/↓ in command mode, and the text typed before it
+    files_history_index: Option<usize>,
+    files_command_draft: String,
     previous_view: Option<LauncherView>,
+    /// Set when Escape goes back a view, so that switch isn't recorded as a new one
+    returning_to_view: bool,
+    /// View to switch to at the start of the next frame, set where `settings` isn't at hand
+    switch_to_view: Option<LauncherView>,
+    last_escape: Option<Instant>,
+    item_height: f32,
+    pub clipboard_type_filter: Option<&'static str>,
+    /// Clipboard entry id whose full content is shown, toggled with Space
+    pub expanded_clipboard_entry: Option<i64>,
+    /// Entries picked with Space for bulk delete / pin / export
+    selected_clipboard_entries: HashSet<i64>,
+    template_dialog: Option<TemplateDialog>,
+    /// Counts line above the clipboard list, until dismissed with ×
+    show_clipboard_stats: bool,
+    clipboard_filter_focused: bool,
+    focus_clipboard_filter: bool,
+    command_output_max_lines: usize,
+    /// `UiState::command_output_version` that `command_output` was last built from
+    command_output_version: u64,
+    terminal_command: String,
+    db_stats: Option<(history::DbStats, u64)>,
+    /// Show `files_stats` in place of the Files view hint, toggled with Ctrl+I
+    show_file_stats: bool,
+    files_stats: Option<FilesStats>,
+    /// Where the tab bar was drawn last frame, for swipe-to-switch-tab
+    pub tab_bar_rect: egui::Rect,
+    pub show_help_overlay: bool,
+    /// Set when a setting was edited this frame; the app saves them to disk later
+    pub settings_changed: bool,
 }
 
 impl Default for LauncherUI {
     fn default() -> Self {
         Self {
             selected_result: 0,
+            hovered_result_idx: None,
+            hover_preview: None,
+            selected_action: None,
             selected_file: 0,
             selected_recent: 0,
             selected_clipboard: 0,
             search_focused: true,
             command_output: None,
+            running_command: None,
             scroll_to_selected: false,
+            last_scroll_request: Instant::now(),
+            accumulated_scroll_delta: 0.0,
             files_command_mode: false,
             files_command_input: String::new(),
             exclude_input: String::new(),
@@ -54,7 +238,31 @@ impl Default for LauncherUI {
             tab_completion_index: 0,
             tab_original_token: String::new(),
             cursor_to_end: false,
+            command_cursor: None,
+            files_command_history: VecDeque::new(),
+            files_history_index: None,
+            files_command_draft: String::new(),
             previous_view: None,
+            returning_to_view: false,
+            switch_to_view: None,
+            last_escape: None,
+            item_height: ITEM_HEIGHT,
+            clipboard_type_filter: None,
+            expanded_clipboard_entry: None,
+            selected_clipboard_entries: HashSet::new(),
+            template_dialog: None,
+            show_clipboard_stats: true,
+            clipboard_filter_focused: false,
+            focus_clipboard_filter: false,
+            command_output_max_lines: 500,
+            command_output_version: 0,
+            terminal_command: String::new(),
+            db_stats: None,
+            show_file_stats: false,
+            files_stats: None,
+            tab_bar_rect: egui::Rect::NOTHING,
+            show_help_overlay: false,
+            settings_changed: false,
         }
     }
 }
@@ -64,17 +272,94 @@ impl LauncherUI {
         Self::default()
     }
 
-    pub fn show(&mut self, ctx: &Context, app: &mut App, settings: &mut LauncherSettings) {
+    /// Scroll the current view's selection into view on the next frame
+    pub fn request_scroll_to_selected(&mut self) {
+        self.scroll_to_selected = true;
+    }
+
+    /// Whether the next frame has pending work (e.g. scrolling to the selection)
+    pub fn wants_repaint(&self) -> bool {
+        self.scroll_to_selected
+    }
+
+    /// Consume a pending scroll-to-selection, deferring it while the last scroll or a
+    /// wheel scroll is still settling. A deferred request stays pending for a later frame.
+    fn take_scroll_request(&mut self) -> bool {
+        if !self.scroll_to_selected
+            || self.accumulated_scroll_delta != 0.0
+            || self.last_scroll_request.elapsed() < SCROLL_DEBOUNCE
+        {
+            return false;
+        }
+        self.scroll_to_selected = false;
+        self.last_scroll_request = Instant::now();
+        true
+    }
+
+    pub fn show(
+        &mut self,
+        ctx: &Context,
+        ui_state: &mut UiState,
+        app_state: &mut AppState,
+        settings: &mut LauncherSettings,
+    ) {
         theme::configure_style(ctx);
 
+        // Scale row height with the window width, within sane bounds
+        self.item_height = (ITEM_HEIGHT * settings.width / DEFAULT_WIDTH)
+            .clamp(ITEM_HEIGHT * 0.8, ITEM_HEIGHT * 1.25);
+        self.command_output_max_lines = settings.command_output_max_lines;
+        if self.terminal_command != settings.terminal_command {
+            self.terminal_command = settings.terminal_command.clone();
+        }
+        self.sync_command_output(ui_state);
+        if let Some(view) = self.switch_to_view.take() {
+            settings.current_view = view;
+        }
+
         // Detect view changes and force scroll sync when entering Files view
         let view_changed = self.previous_view != Some(settings.current_view);
         if view_changed {
             self.scroll_to_selected = true;
+            ui_state.previous_view = if std::mem::take(&mut self.returning_to_view) {
+                None
+            } else {
+                self.previous_view
+            };
             self.previous_view = Some(settings.current_view);
+            if settings.current_view == LauncherView::Settings {
+                self.refresh_db_stats(app_state);
+            }
+        }
+
+        self.handle_global_keys(ctx, ui_state, app_state, settings);
+
+        // Files dragged onto the Files view are moved into the directory shown
+        if settings.current_view == LauncherView::Files {
+            let dropped: Vec<std::path::PathBuf> = ctx.input(|i| {
+                i.raw
+                    .dropped_files
+                    .iter()
+                    .filter_map(|f| f.path.clone())
+                    .collect()
+            });
+            if !dropped.is_empty() {
+                ui_state.move_dropped_files(app_state, &dropped);
+            }
+        }
+
+        // Settings has no selection to follow
+        if settings.current_view == LauncherView::Settings {
+            self.scroll_to_selected = false;
         }
 
-        self.handle_global_keys(ctx, app, settings);
+        // Track an in-progress wheel / touchpad scroll so keyboard scrolling doesn't fight it
+        let wheel_delta = ctx.input(|i| i.smooth_scroll_delta.y);
+        if wheel_delta == 0.0 {
+            self.accumulated_scroll_delta = 0.0;
+        } else {
+            self.accumulated_scroll_delta += wheel_delta;
+        }
 
         CentralPanel::default()
             .frame(
@@ -87,55 +372,503 @@ impl LauncherUI {
             .show(ctx, |ui| {
                 ui.vertical(|ui| {
                     // Navigation tabs with window controls
-                    self.draw_tabs(ui, app, settings);
+                    self.draw_tabs(ui, ui_state, settings, TAB_TOOLTIPS);
 
                     ui.add_space(theme::SPACING);
 
                     // View content
                     match settings.current_view {
-                        LauncherView::Search => self.draw_search_view(ui, app),
-                        LauncherView::Files => self.draw_files_view(ui, app),
-                        LauncherView::Clipboard => self.draw_clipboard_view(ui, app),
-                        LauncherView::Settings => self.draw_settings_view(ui, app, settings),
+                        LauncherView::Search => self.draw_search_view(ui, ui_state, app_state),
+                        LauncherView::Files => self.draw_files_view(ui, ui_state, app_state),
+                        LauncherView::Clipboard => {
+                            self.draw_clipboard_view(ui, ui_state, app_state)
+                        }
+                        LauncherView::Settings => {
+                            self.draw_settings_view(ui, ui_state, app_state, settings)
+                        }
+                    }
+
+                    draw_status_bar(ui, ui_state);
+                });
+            });
+
+        self.draw_file_operation(ctx, app_state);
+        self.draw_toasts(ctx, ui_state);
+        self.draw_help_overlay(ctx, settings.current_view);
+        self.draw_template_dialog(ctx, ui_state, settings.auto_paste);
+    }
+
+    /// Open the fill-in dialog for a template entry's placeholders
+    fn open_template_dialog(&mut self, content: String) {
+        let values = clipboard::extract_template_vars(&content)
+            .into_iter()
+            .map(|name| (name, String::new()))
+            .collect();
+        self.template_dialog = Some(TemplateDialog { content, values });
+    }
+
+    /// Inputs for each `{{variable}}` of the template being filled in; Copy substitutes
+    /// them and copies the result
+    fn draw_template_dialog(&mut self, ctx: &Context, ui_state: &mut UiState, auto_paste: bool) {
+        let Some(dialog) = &mut self.template_dialog else {
+            return;
+        };
+
+        let mut open = true;
+        let mut copy = false;
+        let mut cancel = false;
+        egui::Window::new("Fill in template")
+            .open(&mut open)
+            .order(egui::Order::Foreground)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .frame(
+                Frame::none()
+                    .fill(theme::BG_SECONDARY)
+                    .rounding(theme::ROUNDING)
+                    .inner_margin(theme::PADDING)
+                    .stroke(egui::Stroke::new(1.0, theme::BORDER)),
+            )
+            .show(ctx, |ui| {
+                ui.set_width(340.0);
+                if dialog.values.is_empty() {
+                    ui.label(
+                        RichText::new("No {{variable}} placeholders in this template")
+                            .color(theme::TEXT_MUTED)
+                            .size(10.0),
+                    );
+                }
+                egui::Grid::new("template_vars")
+                    .num_columns(2)
+                    .spacing([12.0, 4.0])
+                    .show(ui, |ui| {
+                        for (idx, (name, value)) in dialog.values.iter_mut().enumerate() {
+                            ui.label(
+                                RichText::new(name.as_str())
+                                    .color(theme::ACCENT)
+                                    .size(12.0)
+                                    .monospace(),
+                            );
+                            let response = ui.add(
+                                TextEdit::singleline(value)
+                                    .desired_width(220.0)
+                                    .text_color(theme::TEXT_PRIMARY),
+                            );
+                            if idx == 0 && !response.has_focus() && value.is_empty() {
+                                response.request_focus();
+                            }
+                            if response.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)) {
+                                copy = true;
+                            }
+                            ui.end_row();
+                        }
+                    });
+                ui.add_space(theme::SPACING);
+                ui.horizontal(|ui| {
+                    ui.label(
+                        RichText::new("Enter: copy | Esc: cancel")
+                            .color(theme::TEXT_MUTED)
+                            .size(10.0),
+                    );
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.button("Copy").clicked() {
+                            copy = true;
+                        }
+                        if ui.button("Cancel").clicked() {
+                            cancel = true;
+                        }
+                    });
+                });
+            });
+
+        if copy {
+            let vars: HashMap<String, String> = dialog.values.iter().cloned().collect();
+            let text = clipboard::instantiate_template(&dialog.content, &vars);
+            ui_state.copy_clipboard_entry(&text, auto_paste);
+        }
+        if copy || cancel || !open {
+            self.template_dialog = None;
+        }
+    }
+
+    /// Shortcut reference for `view` over a dimmed backdrop, toggled with `?`
+    fn draw_help_overlay(&mut self, ctx: &Context, view: LauncherView) {
+        if !self.show_help_overlay {
+            return;
+        }
+
+        let screen = ctx.screen_rect();
+        egui::Area::new(egui::Id::new("help_backdrop"))
+            .order(egui::Order::Middle)
+            .fixed_pos(screen.min)
+            .show(ctx, |ui| {
+                ui.painter().rect_filled(
+                    screen,
+                    theme::ROUNDING,
+                    egui::Color32::from_black_alpha(160),
+                );
+                // Swallow clicks meant for the view underneath; clicking outside closes
+                if ui.allocate_rect(screen, egui::Sense::click()).clicked() {
+                    self.show_help_overlay = false;
+                }
+            });
+
+        let mut open = self.show_help_overlay;
+        egui::Window::new(format!("{} Shortcuts", view.label()))
+            .open(&mut open)
+            .order(egui::Order::Foreground)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .frame(
+                Frame::none()
+                    .fill(theme::BG_SECONDARY)
+                    .rounding(theme::ROUNDING)
+                    .inner_margin(theme::PADDING)
+                    .stroke(egui::Stroke::new(1.0, theme::BORDER)),
+            )
+            .show(ctx, |ui| {
+                ui.set_width(340.0);
+                let view_shortcuts = HELP_TEXT
+                    .iter()
+                    .filter(|(_, _, v)| *v == view)
+                    .map(|(key, desc, _)| (*key, *desc));
+                let sections = [
+                    (
+                        "help_view",
+                        view.label(),
+                        view_shortcuts.collect::<Vec<_>>(),
+                    ),
+                    ("help_global", "Global", GLOBAL_HELP_TEXT.to_vec()),
+                ];
+
+                for (id, title, shortcuts) in sections {
+                    if shortcuts.is_empty() {
+                        continue;
+                    }
+                    ui.label(RichText::new(title).color(theme::TEXT_PRIMARY).size(13.0));
+                    ui.add_space(2.0);
+                    egui::Grid::new(id)
+                        .num_columns(2)
+                        .spacing([12.0, 2.0])
+                        .show(ui, |ui| {
+                            for (key, desc) in shortcuts {
+                                ui.label(RichText::new(key).color(theme::ACCENT).size(12.0));
+                                ui.label(
+                                    RichText::new(desc).color(theme::TEXT_SECONDARY).size(12.0),
+                                );
+                                ui.end_row();
+                            }
+                        });
+                    ui.add_space(theme::SPACING);
+                }
+
+                ui.label(
+                    RichText::new("? or Esc to close")
+                        .color(theme::TEXT_MUTED)
+                        .size(10.0),
+                );
+            });
+        self.show_help_overlay &= open;
+    }
+
+    /// Toasts stacked in the bottom-right corner, newest at the bottom. Each fades out
+    /// during its last half second and is dropped once expired.
+    fn draw_toasts(&self, ctx: &Context, ui_state: &mut UiState) {
+        let now = Instant::now();
+        ui_state.toasts.retain(|toast| toast.expires > now);
+        if ui_state.toasts.is_empty() {
+            return;
+        }
+
+        egui::Area::new(egui::Id::new("toasts"))
+            .anchor(
+                egui::Align2::RIGHT_BOTTOM,
+                egui::vec2(-OUTER_MARGIN, -OUTER_MARGIN * 2.0),
+            )
+            .interactable(false)
+            .show(ctx, |ui| {
+                ui.with_layout(egui::Layout::top_down(egui::Align::Max), |ui| {
+                    for toast in &ui_state.toasts {
+                        let remaining = toast.expires.saturating_duration_since(now);
+                        let (text_color, border) = match toast.kind {
+                            ToastKind::Info => (theme::TEXT_PRIMARY, theme::BORDER),
+                            ToastKind::Success => (theme::ACCENT, theme::ACCENT),
+                            ToastKind::Error => (theme::ERROR, theme::ERROR),
+                        };
+                        ui.scope(|ui| {
+                            ui.set_opacity(
+                                (remaining.as_secs_f32() / TOAST_FADE.as_secs_f32()).min(1.0),
+                            );
+                            Frame::none()
+                                .fill(theme::BG_SECONDARY)
+                                .rounding(theme::ROUNDING)
+                                .inner_margin(theme::SPACING * 2.0)
+                                .stroke(egui::Stroke::new(1.0, border))
+                                .show(ui, |ui| {
+                                    ui.label(
+                                        RichText::new(&toast.message).size(12.0).color(text_color),
+                                    );
+                                });
+                        });
+                        ui.add_space(theme::SPACING);
+                    }
+                });
+            });
+    }
+
+    /// Re-read table counts and file size for the Settings "Database" section
+    fn refresh_db_stats(&mut self, app_state: &AppState) {
+        let size = std::fs::metadata(&app_state.db_path)
+            .map(|m| m.len())
+            .unwrap_or(0);
+        self.db_stats = history::db_stats(&app_state.db_connection)
+            .ok()
+            .map(|stats| (stats, size));
+    }
+
+    /// Modal progress window for a running copy / move
+    fn draw_file_operation(&self, ctx: &Context, app_state: &mut AppState) {
+        let Some(op) = &app_state.file_operation else {
+            return;
+        };
+
+        let title = match op.kind {
+            FileOpKind::Copy => "Copying",
+            FileOpKind::Move => "Moving",
+        };
+        let name = op
+            .src
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+        let destination = op
+            .dst
+            .parent()
+            .map(|p| p.display().to_string())
+            .unwrap_or_default();
+        let progress = op.progress;
+        let queued = app_state.pending_file_operations.len();
+
+        let mut cancel = false;
+        egui::Window::new(title)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .frame(
+                Frame::none()
+                    .fill(theme::BG_SECONDARY)
+                    .rounding(theme::ROUNDING)
+                    .inner_margin(theme::PADDING)
+                    .stroke(egui::Stroke::new(1.0, theme::BORDER)),
+            )
+            .show(ctx, |ui| {
+                ui.set_width(320.0);
+                ui.label(RichText::new(&name).size(13.0).color(theme::TEXT_PRIMARY));
+                ui.label(
+                    RichText::new(format!("to {}", destination))
+                        .size(10.0)
+                        .color(theme::TEXT_MUTED),
+                );
+                ui.add_space(theme::SPACING);
+                ui.add(egui::ProgressBar::new(progress).show_percentage());
+                ui.add_space(theme::SPACING);
+                ui.horizontal(|ui| {
+                    if queued > 0 {
+                        ui.label(
+                            RichText::new(format!("{} more queued", queued))
+                                .size(10.0)
+                                .color(theme::TEXT_MUTED),
+                        );
                     }
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.button("Cancel").clicked() {
+                            cancel = true;
+                        }
+                    });
                 });
             });
+
+        if cancel {
+            app_state.cancel_file_operation();
+        }
+    }
+
+    /// Run the selected search result, or its chosen desktop action, then reset the search.
+    /// Directories switch to the Files view; commands keep the query to show their output.
+    fn run_search_result(&mut self, ui_state: &mut UiState, app_state: &mut AppState, idx: usize) {
+        // SSH hosts open here so the configured terminal is used
+        let ssh_alias = match app_state.search_state.get(idx).map(|r| &r.kind) {
+            Some(SearchResultKind::SshHost { alias, .. }) => Some(alias.clone()),
+            _ => None,
+        };
+
+        if let Some(alias) = ssh_alias {
+            match open_ssh_session(&alias, &self.terminal_command) {
+                Ok(()) => {
+                    ui_state.set_status(format!("Connecting to {}", alias), Some(STATUS_TIMEOUT))
+                }
+                Err(e) => ui_state.set_error(format!("Failed to open terminal: {}", e)),
+            }
+        } else {
+            let executed = match self.selected_action {
+                Some((result, action)) if result == idx => ui_state
+                    .execute_search_action(app_state, idx, action)
+                    .map(|()| None),
+                _ => ui_state.execute_search_result(app_state, idx).map(Some),
+            };
+            match executed {
+                Ok(Some(ExecutedResultKind::CommandRun(command))) => {
+                    self.execute_command(&command, ui_state, app_state);
+                    return;
+                }
+                Ok(Some(ExecutedResultKind::NavigatedTo(path))) => {
+                    tracing::debug!("Showing {} in Files", path.display());
+                    self.switch_to_view = Some(LauncherView::Files);
+                    self.selected_file = ui_state.selected_index;
+                }
+                Ok(Some(ExecutedResultKind::Opened(path))) => {
+                    tracing::debug!("Opened {}", path.display());
+                }
+                Ok(Some(ExecutedResultKind::Launched(name))) => {
+                    tracing::debug!("Launched {}", name);
+                }
+                Ok(Some(ExecutedResultKind::Copied(text))) => {
+                    tracing::debug!("Copied {}", text);
+                }
+                Ok(None) => {}
+                Err(e) => ui_state.set_error(e.to_string()),
+            }
+        }
+        ui_state.search_query.clear();
+        app_state.search_state.clear();
+        self.selected_result = 0;
+        self.selected_action = None;
+    }
+
+    /// Desktop actions of the selected result, if it is an application
+    fn selected_app_action_count(&self, app_state: &AppState) -> usize {
+        match app_state
+            .search_state
+            .get(self.selected_result)
+            .map(|r| &r.kind)
+        {
+            Some(SearchResultKind::Application(desktop_app)) => desktop_app.actions.len(),
+            _ => 0,
+        }
     }
 
     fn handle_global_keys(
         &mut self,
         ctx: &Context,
-        app: &mut App,
+        ui_state: &mut UiState,
+        app_state: &mut AppState,
         settings: &mut LauncherSettings,
     ) {
+        // Tab cycles an application result's actions; consume it before the search box
+        // or view switching sees it
+        let action_count = self.selected_app_action_count(app_state);
+        if settings.current_view == LauncherView::Search
+            && action_count > 0
+            && ctx.input_mut(|i| i.consume_key(egui::Modifiers::NONE, Key::Tab))
+        {
+            let current = self
+                .selected_action
+                .filter(|(result, _)| *result == self.selected_result)
+                .map(|(_, action)| action);
+            self.selected_action = match current {
+                None => Some((self.selected_result, 0)),
+                Some(a) if a + 1 < action_count => Some((self.selected_result, a + 1)),
+                Some(_) => None,
+            };
+        }
+
         ctx.input(|i| {
+            let question_pressed = i
+                .events
+                .iter()
+                .any(|e| matches!(e, egui::Event::Text(text) if text == "?"));
+
+            // The help overlay takes every key until it is closed
+            if self.show_help_overlay {
+                if question_pressed || i.key_pressed(Key::Escape) {
+                    self.show_help_overlay = false;
+                }
+                return;
+            }
+            // So does the template dialog, whose inputs get the typing
+            if self.template_dialog.is_some() {
+                if i.key_pressed(Key::Escape) {
+                    self.template_dialog = None;
+                }
+                return;
+            }
+            if question_pressed
+                && !self.search_focused
+                && !self.files_command_mode
+                && !self.clipboard_filter_focused
+                && settings.current_view != LauncherView::Settings
+            {
+                self.show_help_overlay = true;
+                return;
+            }
+
             if i.key_pressed(Key::Escape) {
+                let double_escape = self
+                    .last_escape
+                    .is_some_and(|at| at.elapsed() < DOUBLE_ESCAPE_INTERVAL);
+                self.last_escape = Some(Instant::now());
+
                 match settings.current_view {
+                    LauncherView::Search if double_escape => {
+                        self.last_escape = None;
+                        if ui_state.window_visible {
+                            ui_state.toggle_visibility(app_state);
+                        }
+                    }
                     LauncherView::Search => {
-                        if !app.search_query.is_empty() {
-                            app.search_query.clear();
-                            app.search_results.clear();
+                        if !ui_state.search_query.is_empty() {
+                            ui_state.search_query.clear();
+                            app_state.search_state.clear();
                             self.selected_result = 0;
                             self.command_output = None;
+                        } else if !ui_state.open_with_files.is_empty() {
+                            ui_state.open_with_files.clear();
                         } else if self.search_focused {
                             self.search_focused = false;
                         } else {
-                            app.toggle_visibility();
+                            ui_state.toggle_visibility(app_state);
                         }
                     }
+                    LauncherView::Files if ui_state.mode == AppMode::Visual => {
+                        ui_state.exit_visual_mode();
+                    }
+                    LauncherView::Clipboard if self.clipboard_filter_focused => {
+                        // The filter box drops focus on Escape by itself
+                    }
+                    LauncherView::Clipboard if !self.selected_clipboard_entries.is_empty() => {
+                        self.selected_clipboard_entries.clear();
+                    }
                     LauncherView::Files | LauncherView::Clipboard | LauncherView::Settings => {
-                        settings.current_view = LauncherView::Search;
+                        let target = ui_state
+                            .previous_view
+                            .filter(|view| *view != settings.current_view)
+                            .unwrap_or(settings.default_view);
+                        if target == settings.current_view {
+                            // Already back at the default view, so Escape hides like in Search
+                            ui_state.toggle_visibility(app_state);
+                        } else {
+                            settings.current_view = target;
+                            self.returning_to_view = true;
+                        }
                     }
                 }
             }
 
             if i.key_pressed(Key::Tab) && !self.search_focused && !self.files_command_mode {
-                let new_view = match settings.current_view {
-                    LauncherView::Search => LauncherView::Files,
-                    LauncherView::Files => LauncherView::Clipboard,
-                    LauncherView::Clipboard => LauncherView::Settings,
-                    LauncherView::Settings => LauncherView::Search,
-                };
+                let new_view = settings.current_view.next();
                 if new_view != settings.current_view {
                     self.scroll_to_selected = true;
                 }
@@ -165,11 +898,50 @@ impl LauncherUI {
                 self.scroll_to_selected = true;
             }
 
+            // egui turns Ctrl+C into a Copy event rather than a key press. While a text
+            // field has focus the copy belongs to its selection, so leave it alone.
+            let copy_pressed = !self.search_focused
+                && !self.files_command_mode
+                && i.events.iter().any(|e| matches!(e, egui::Event::Copy));
+
             match settings.current_view {
                 LauncherView::Search => {
-                    if !app.search_results.is_empty() {
+                    if i.modifiers.ctrl && i.key_pressed(Key::S) {
+                        ui_state.cycle_search_sort_mode(app_state);
+                        self.selected_result = 0;
+                        settings.search_sort_mode = app_state.search_sort_mode;
+                        self.settings_changed = true;
+                    }
+
+                    if i.modifiers.alt && i.key_pressed(Key::C) {
+                        let case_sensitive = !app_state.search_case_sensitive;
+                        ui_state.set_search_case_sensitive(app_state, case_sensitive);
+                        self.selected_result = 0;
+                        settings.search_case_sensitive = case_sensitive;
+                        self.settings_changed = true;
+                        ui_state.set_status(
+                            if case_sensitive {
+                                "Case-sensitive search"
+                            } else {
+                                "Case-insensitive search"
+                            },
+                            Some(STATUS_TIMEOUT),
+                        );
+                    }
+
+                    if copy_pressed {
+                        if let Some(text) = app_state
+                            .search_state
+                            .get(self.selected_result)
+                            .map(|r| r.kind.copy_text())
+                        {
+                            ui_state.copy_path(&text);
+                        }
+                    }
+
+                    if !app_state.search_state.is_empty() {
                         if i.key_pressed(Key::ArrowDown) {
-                            let max = app.search_results.len().saturating_sub(1);
+                            let max = app_state.search_state.len().saturating_sub(1);
                             self.selected_result = (self.selected_result + 1).min(max);
                             self.scroll_to_selected = true;
                         }
@@ -178,16 +950,23 @@ impl LauncherUI {
                             self.scroll_to_selected = true;
                         }
                         if i.key_pressed(Key::Enter) && !self.search_focused {
-                            let _ = app.execute_search_result(self.selected_result);
-                            app.search_query.clear();
-                            app.search_results.clear();
-                            self.selected_result = 0;
+                            self.run_search_result(ui_state, app_state, self.selected_result);
+                        }
+                    } else if ui_state.search_query.is_empty() && !self.search_focused {
+                        if i.key_pressed(Key::M) {
+                            ui_state.show_most_opened = !ui_state.show_most_opened;
+                            self.selected_recent = 0;
                         }
-                    } else if app.search_query.is_empty() && !self.search_focused {
-                        let recent_count = app.recent_files.len().min(5);
-                        let cmd_count = app.command_history.len().min(5);
-                        let app_count = app.applications.len().min(5);
-                        let total = recent_count + cmd_count + app_count;
+
+                        let recent_count = app_state.recent_files.len().min(5);
+                        let most_count = if ui_state.show_most_opened {
+                            app_state.most_opened_files.len().min(5)
+                        } else {
+                            0
+                        };
+                        let cmd_count = app_state.command_history.len().min(5);
+                        let app_count = app_state.applications.len().min(5);
+                        let total = recent_count + most_count + cmd_count + app_count;
 
                         if total > 0 {
                             let current = self.selected_recent;
@@ -202,29 +981,43 @@ impl LauncherUI {
                             }
 
                             if i.key_pressed(Key::Enter) {
-                                if self.selected_recent < recent_count {
-                                    if let Some(recent) = app.recent_files.get(self.selected_recent)
-                                    {
-                                        let path = recent.path.clone();
-                                        if path.is_dir() {
-                                            let _ = app.change_directory(path);
-                                        } else {
-                                            let _ = app.open_file(path);
-                                        }
+                                let recent = if self.selected_recent < recent_count {
+                                    app_state.recent_files.get(self.selected_recent)
+                                } else if self.selected_recent < recent_count + most_count {
+                                    app_state
+                                        .most_opened_files
+                                        .get(self.selected_recent - recent_count)
+                                } else {
+                                    None
+                                };
+
+                                if let Some(recent) = recent {
+                                    let path = recent.path.clone();
+                                    if path.is_dir() {
+                                        let _ = ui_state.change_directory(app_state, path);
+                                    } else {
+                                        let _ = ui_state.open_file(app_state, path);
                                     }
-                                } else if self.selected_recent < recent_count + cmd_count {
-                                    let cmd_idx = self.selected_recent - recent_count;
-                                    if let Some(cmd_entry) = app.command_history.get(cmd_idx) {
+                                } else if self.selected_recent
+                                    < recent_count + most_count + cmd_count
+                                {
+                                    let cmd_idx = self.selected_recent - recent_count - most_count;
+                                    if let Some(cmd_entry) = app_state.command_history.get(cmd_idx)
+                                    {
                                         let cmd = cmd_entry.command.clone();
                                         let path = cmd_entry.path.clone();
-                                        let _ = app.change_directory(path);
-                                        self.execute_command_sync(&cmd, app);
+                                        let _ = ui_state.change_directory(app_state, path);
+                                        self.execute_command(&cmd, ui_state, app_state);
                                     }
                                 } else {
-                                    let app_idx = self.selected_recent - recent_count - cmd_count;
-                                    if let Some(desktop_app) = app.applications.get(app_idx).cloned() {
-                                        let _ = history::log_app_launch(&app.db_connection, &desktop_app.name, &desktop_app.path);
-                                        app.refresh_app_launch_history();
+                                    let app_idx = self.selected_recent
+                                        - recent_count
+                                        - most_count
+                                        - cmd_count;
+                                    if let Some(desktop_app) =
+                                        app_state.applications.get(app_idx).cloned()
+                                    {
+                                        app_state.log_app_launch(&desktop_app);
                                         let _ = desktop_app.launch();
                                     }
                                 }
@@ -233,14 +1026,19 @@ impl LauncherUI {
                     }
                 }
                 LauncherView::Files => {
+                    // The list is about to be replaced; indices into it may not survive
+                    if ui_state.is_loading_directory {
+                        return;
+                    }
                     if self.files_command_mode {
                         if i.key_pressed(Key::Escape) {
                             self.files_command_mode = false;
+                            ui_state.set_mode(ModeEvent::Cancel);
                             self.files_command_input.clear();
                             self.tab_completions.clear();
                         }
                         if i.key_pressed(Key::Tab) && !self.files_command_input.is_empty() {
-                            self.handle_tab_completion(app);
+                            self.handle_tab_completion(app_state);
                         } else if i.key_pressed(Key::Tab) {
                             // consume Tab when input is empty to prevent view switching
                         } else if !i.key_pressed(Key::ArrowUp)
@@ -255,7 +1053,19 @@ impl LauncherUI {
                         return;
                     }
 
-                    let file_count = app.get_display_list().len();
+                    if i.modifiers.ctrl && i.key_pressed(Key::I) {
+                        self.show_file_stats = !self.show_file_stats;
+                        return;
+                    }
+                    if i.modifiers.ctrl && i.key_pressed(Key::Z) {
+                        if let Err(e) = ui_state.undo_last(app_state) {
+                            ui_state.set_error(format!("Undo failed: {}", e));
+                        }
+                        self.selected_file = ui_state.selected_index;
+                        return;
+                    }
+
+                    let file_count = ui_state.get_display_list(app_state).len();
                     let old_selection = self.selected_file;
 
                     if i.key_pressed(Key::ArrowDown) || i.key_pressed(Key::J) {
@@ -271,22 +1081,29 @@ impl LauncherUI {
                     }
 
                     if self.selected_file != old_selection {
-                        app.selected_index = self.selected_file;
+                        ui_state.selected_index = self.selected_file;
                         self.scroll_to_selected = true;
                     }
 
-                    if i.key_pressed(Key::Enter)
+                    // Enter on a visual selection opens all of its files with one application
+                    if ui_state.mode == AppMode::Visual && i.key_pressed(Key::Enter) {
+                        if ui_state.start_open_with_selection(app_state) {
+                            settings.current_view = LauncherView::Search;
+                            self.search_focused = true;
+                            self.selected_result = 0;
+                        }
+                    } else if i.key_pressed(Key::Enter)
                         || i.key_pressed(Key::L)
                         || i.key_pressed(Key::ArrowRight)
                     {
-                        let is_dir = app
-                            .get_display_list()
+                        let is_dir = ui_state
+                            .get_display_list(app_state)
                             .get(self.selected_file)
                             .map(|f| f.is_dir)
                             .unwrap_or(false);
-                        let _ = app.enter_selected();
+                        let _ = ui_state.enter_selected(app_state);
                         if is_dir {
-                            self.selected_file = 0;
+                            self.selected_file = ui_state.selected_index;
                             self.scroll_to_selected = true;
                         }
                     }
@@ -295,59 +1112,197 @@ impl LauncherUI {
                         || i.key_pressed(Key::H)
                         || i.key_pressed(Key::Backspace)
                     {
-                        let _ = app.go_up();
-                        self.selected_file = 0;
+                        let _ = ui_state.go_up(app_state);
+                        self.selected_file = ui_state.selected_index;
                         self.scroll_to_selected = true;
                     }
 
-                    if i.key_pressed(Key::R) {
-                        let _ = app.refresh_directory();
+                    if copy_pressed {
+                        if let Some(path) = ui_state
+                            .get_display_list(app_state)
+                            .get(self.selected_file)
+                            .map(|f| f.path.to_string_lossy().into_owned())
+                        {
+                            ui_state.copy_path(&path);
+                        }
                     }
 
-                    if i.key_pressed(Key::C) {
-                        self.files_command_mode = true;
-                        self.files_command_input.clear();
-                        self.command_output = None;
-                    }
-                }
-                LauncherView::Clipboard => {
-                    let count = app.clipboard_history.len();
-                    if count > 0 {
-                        if i.key_pressed(Key::ArrowDown) || i.key_pressed(Key::J) {
-                            self.selected_clipboard =
-                                (self.selected_clipboard + 1).min(count.saturating_sub(1));
-                            self.scroll_to_selected = true;
+                    if ui_state.mode == AppMode::Visual {
+                        if i.key_pressed(Key::Y) {
+                            ui_state.yank_selection(app_state);
                         }
-                        if i.key_pressed(Key::ArrowUp) || i.key_pressed(Key::K) {
-                            self.selected_clipboard = self.selected_clipboard.saturating_sub(1);
-                            self.scroll_to_selected = true;
+                        if i.key_pressed(Key::D) {
+                            if let Err(e) = ui_state.trash_selection(app_state) {
+                                ui_state.set_error(format!("Failed to trash: {}", e));
+                            }
+                            self.selected_file = ui_state.selected_index;
+                        }
+                        return;
+                    }
+
+                    if i.key_pressed(Key::V) {
+                        ui_state.enter_visual_mode();
+                    }
+
+                    if i.key_pressed(Key::R) {
+                        let _ = ui_state.refresh_directory(app_state);
+                    }
+
+                    if i.modifiers.shift && i.key_pressed(Key::T) {
+                        match open_terminal_here(
+                            &app_state.current_path,
+                            &settings.terminal_command,
+                        ) {
+                            Ok(()) => ui_state.window_visible = false,
+                            Err(e) => ui_state.set_error(format!("Failed to open terminal: {}", e)),
+                        }
+                    }
+
+                    if i.key_pressed(Key::S) {
+                        let (order, direction) = if i.modifiers.shift {
+                            (app_state.sort_order, app_state.sort_direction.toggle())
+                        } else {
+                            (app_state.sort_order.next(), app_state.sort_direction)
+                        };
+                        ui_state.set_files_sort(app_state, order, direction);
+                        ui_state.set_status(
+                            format!("Sort: {} ({})", order.label(), direction.label()),
+                            Some(STATUS_TIMEOUT),
+                        );
+                        self.selected_file = ui_state.selected_index;
+                        self.scroll_to_selected = true;
+                        settings.files_sort_order = order;
+                        settings.files_sort_direction = direction;
+                        self.settings_changed = true;
+                    }
+
+                    if i.key_pressed(Key::O) {
+                        if let Some(entry) = ui_state
+                            .get_display_list(app_state)
+                            .get(self.selected_file)
+                            .filter(|f| !f.is_dir)
+                            .cloned()
+                        {
+                            ui_state.start_open_with(app_state, vec![entry.path]);
+                            settings.current_view = LauncherView::Search;
+                            self.search_focused = true;
+                            self.selected_result = 0;
+                        }
+                    }
+
+                    if i.key_pressed(Key::P) {
+                        let kind = if i.modifiers.shift {
+                            FileOpKind::Move
+                        } else {
+                            FileOpKind::Copy
+                        };
+                        ui_state.paste_yanked(app_state, kind);
+                    }
+
+                    if i.key_pressed(Key::C) {
+                        self.files_command_mode = true;
+                        ui_state.set_mode(ModeEvent::EnterCommand);
+                        self.files_command_input.clear();
+                        self.files_history_index = None;
+                        self.command_output = None;
+                    }
+                }
+                LauncherView::Clipboard => {
+                    let entries = self.visible_clipboard(app_state);
+                    let count = entries.len();
+                    let visible_ids: Vec<i64> = entries.iter().map(|e| e.id).collect();
+                    let selected = entries
+                        .get(self.selected_clipboard)
+                        .map(|e| (e.id, e.content.clone()));
+                    let selected_is_template = entries
+                        .get(self.selected_clipboard)
+                        .is_some_and(|e| e.content_type == clipboard::CONTENT_TYPE_TEMPLATE);
+
+                    // While typing in the filter only arrows and Enter act on the list
+                    if self.clipboard_filter_focused {
+                        if i.key_pressed(Key::ArrowDown) {
+                            self.selected_clipboard =
+                                (self.selected_clipboard + 1).min(count.saturating_sub(1));
+                            self.scroll_to_selected = true;
+                        }
+                        if i.key_pressed(Key::ArrowUp) {
+                            self.selected_clipboard = self.selected_clipboard.saturating_sub(1);
+                            self.scroll_to_selected = true;
                         }
                         if i.key_pressed(Key::Enter) {
-                            if let Some(entry) = app.clipboard_history.get(self.selected_clipboard)
-                            {
-                                let _ = clipboard::copy_to_clipboard(&entry.content);
+                            if let Some((_, content)) = &selected {
+                                ui_state.copy_clipboard_entry(content, settings.auto_paste);
                             }
                         }
-                        if i.key_pressed(Key::P) {
-                            if let Some(entry) = app.clipboard_history.get(self.selected_clipboard)
-                            {
-                                let _ = clipboard::toggle_pin(&app.db_connection, entry.id);
-                                app.refresh_clipboard();
+                        return;
+                    }
+
+                    if i.key_pressed(Key::Slash) {
+                        self.focus_clipboard_filter = true;
+                    }
+
+                    if count > 0 {
+                        if i.key_pressed(Key::ArrowDown) || i.key_pressed(Key::J) {
+                            self.selected_clipboard =
+                                (self.selected_clipboard + 1).min(count.saturating_sub(1));
+                            self.scroll_to_selected = true;
+                        }
+                        if i.key_pressed(Key::ArrowUp) || i.key_pressed(Key::K) {
+                            self.selected_clipboard = self.selected_clipboard.saturating_sub(1);
+                            self.scroll_to_selected = true;
+                        }
+                        if i.key_pressed(Key::Enter) {
+                            if let Some((_, content)) = &selected {
+                                ui_state.copy_clipboard_entry(content, settings.auto_paste);
                             }
                         }
-                        if i.key_pressed(Key::D) || i.key_pressed(Key::X) {
-                            if let Some(entry) = app.clipboard_history.get(self.selected_clipboard)
-                            {
-                                let _ = clipboard::delete_entry(&app.db_connection, entry.id);
-                                app.refresh_clipboard();
-                                if self.selected_clipboard > 0
-                                    && self.selected_clipboard >= app.clipboard_history.len()
-                                {
-                                    self.selected_clipboard =
-                                        app.clipboard_history.len().saturating_sub(1);
+                        if i.key_pressed(Key::Space) {
+                            if let Some((id, _)) = &selected {
+                                if !self.selected_clipboard_entries.remove(id) {
+                                    self.selected_clipboard_entries.insert(*id);
                                 }
                             }
                         }
+                        if i.modifiers.ctrl && i.key_pressed(Key::A) {
+                            self.selected_clipboard_entries
+                                .extend(visible_ids.iter().copied());
+                        }
+                        if i.key_pressed(Key::E) && selected_is_template {
+                            if let Some((_, content)) = &selected {
+                                self.open_template_dialog(content.clone());
+                            }
+                        } else if i.key_pressed(Key::E) {
+                            if let Some((id, _)) = &selected {
+                                self.expanded_clipboard_entry =
+                                    if self.expanded_clipboard_entry == Some(*id) {
+                                        None
+                                    } else {
+                                        Some(*id)
+                                    };
+                            }
+                        }
+                        if i.key_pressed(Key::P) && i.modifiers.shift {
+                            if let Some((id, _)) = selected {
+                                let _ = clipboard::pin_as_template(&app_state.db_connection, id);
+                                app_state.refresh_clipboard(&ui_state.clipboard_query);
+                            }
+                        } else if i.key_pressed(Key::P) {
+                            if !self.selected_clipboard_entries.is_empty() {
+                                self.pin_selected_clipboard(ui_state, app_state);
+                            } else if let Some((id, _)) = selected {
+                                let _ = clipboard::toggle_pin(&app_state.db_connection, id);
+                                app_state.refresh_clipboard(&ui_state.clipboard_query);
+                            }
+                        }
+                        if i.key_pressed(Key::D) || i.key_pressed(Key::X) {
+                            if !self.selected_clipboard_entries.is_empty() {
+                                self.delete_selected_clipboard(ui_state, app_state);
+                            } else if let Some((id, _)) = selected {
+                                let _ = clipboard::delete_entry(&app_state.db_connection, id);
+                                app_state.refresh_clipboard(&ui_state.clipboard_query);
+                                self.clamp_clipboard_selection(app_state);
+                            }
+                        }
                     }
                 }
                 LauncherView::Settings => {}
@@ -355,21 +1310,35 @@ impl LauncherUI {
         });
     }
 
-    fn draw_tabs(&mut self, ui: &mut Ui, app: &mut App, settings: &mut LauncherSettings) {
-        Frame::none()
+    fn draw_tabs(
+        &mut self,
+        ui: &mut Ui,
+        ui_state: &mut UiState,
+        settings: &mut LauncherSettings,
+        tooltip_data: &[(&str, &str)],
+    ) {
+        self.tab_bar_rect = Frame::none()
             .fill(theme::BG_SECONDARY)
             .rounding(theme::ROUNDING)
             .inner_margin(egui::Margin::symmetric(theme::PADDING, theme::SPACING))
             .show(ui, |ui| {
                 ui.horizontal(|ui| {
                     let tabs = [
-                        (LauncherView::Search, "🔍 Search", "Ctrl+1"),
-                        (LauncherView::Files, "📁 Files", "Ctrl+2"),
-                        (LauncherView::Clipboard, "📋 Clipboard", "Ctrl+3"),
-                        (LauncherView::Settings, "☰ Settings", "Ctrl+4"),
+                        (LauncherView::Search, "🔍", "Search"),
+                        (LauncherView::Files, "📁", "Files"),
+                        (LauncherView::Clipboard, "📋", "Clipboard"),
+                        (LauncherView::Settings, "☰", "Settings"),
                     ];
 
-                    for (view, label, shortcut) in tabs {
+                    // Narrow windows only show the tab icons
+                    let compact = settings.width < COMPACT_TABS_WIDTH;
+
+                    for (i, (view, icon, name)) in tabs.into_iter().enumerate() {
+                        let label = if compact {
+                            icon.to_string()
+                        } else {
+                            format!("{} {}", icon, name)
+                        };
                         let is_active = settings.current_view == view;
                         let color = if is_active {
                             theme::ACCENT
@@ -386,7 +1355,12 @@ impl LauncherUI {
                             settings.current_view = view;
                         }
 
-                        response.on_hover_text(shortcut);
+                        // egui waits `tooltip_delay` (500 ms by default) before showing this
+                        if let Some(&(shortcut, description)) = tooltip_data.get(i) {
+                            response.on_hover_ui(|ui| {
+                                draw_tab_tooltip(ui, name, shortcut, description);
+                            });
+                        }
                         ui.add_space(theme::SPACING);
                     }
 
@@ -402,7 +1376,7 @@ impl LauncherUI {
                             .frame(false),
                         );
                         if close_btn.clicked() {
-                            app.should_quit = true;
+                            ui_state.set_mode(ModeEvent::Quit);
                         }
                         if close_btn.hovered() {
                             ui.ctx().set_cursor_icon(egui::CursorIcon::PointingHand);
@@ -419,7 +1393,7 @@ impl LauncherUI {
                             .frame(false),
                         );
                         if min_btn.clicked() {
-                            app.window_visible = false;
+                            ui_state.window_visible = false;
                         }
                         if min_btn.hovered() {
                             ui.ctx().set_cursor_icon(egui::CursorIcon::PointingHand);
@@ -427,26 +1401,49 @@ impl LauncherUI {
                         min_btn.on_hover_text("Minimize");
                     });
                 });
-            });
+            })
+            .response
+            .rect;
     }
 
-    fn draw_search_view(&mut self, ui: &mut Ui, app: &mut App) {
-        self.draw_search_input(ui, app);
+    fn draw_search_view(&mut self, ui: &mut Ui, ui_state: &mut UiState, app_state: &mut AppState) {
+        self.draw_search_input(ui, ui_state, app_state);
+        ui.add_space(theme::SPACING);
+        self.draw_provider_badges(ui, ui_state, app_state);
         ui.add_space(theme::SPACING);
 
-        if app.search_query.is_empty() && app.search_results.is_empty() {
-            self.draw_recent_and_apps(ui, app);
-        } else if app.search_query.starts_with(':') {
-            self.draw_command_view(ui, app);
-        } else if !app.search_results.is_empty() {
-            self.draw_results(ui, app);
-        } else if !app.search_query.is_empty() {
-            self.draw_no_results(ui, &app.search_query);
+        if !ui_state.open_with_files.is_empty() {
+            ui.label(
+                RichText::new(format!(
+                    "Opening {} — choose an application (Esc to cancel)",
+                    describe_files(&ui_state.open_with_files)
+                ))
+                .color(theme::TEXT_MUTED)
+                .size(10.0),
+            );
+            ui.add_space(theme::SPACING);
+        }
+
+        if ui_state.search_query.is_empty() && app_state.search_state.is_empty() {
+            self.draw_recent_and_apps(ui, ui_state, app_state);
+        } else if ui_state.search_query.starts_with(':') {
+            self.draw_command_view(ui, ui_state, app_state);
+        } else if !app_state.search_state.is_empty() {
+            self.draw_results(ui, ui_state, app_state);
+        } else if app_state.search_state.is_streaming() {
+            self.draw_searching(ui, &ui_state.search_query);
+        } else if app_state
+            .active_search_config
+            .is_below_min_length(&ui_state.search_query)
+        {
+            self.draw_min_length_hint(ui, app_state.active_search_config.min_search_length);
+        } else if !ui_state.search_query.is_empty() {
+            self.draw_no_results(ui, &ui_state.search_query);
         }
     }
 
-    fn draw_command_view(&mut self, ui: &mut Ui, app: &mut App) {
-        let command = app.search_query.strip_prefix(':').unwrap_or("").trim();
+    fn draw_command_view(&mut self, ui: &mut Ui, ui_state: &mut UiState, app_state: &mut AppState) {
+        let command = ui_state.search_query.strip_prefix(':').unwrap_or("").trim();
 
         Frame::none()
             .fill(theme::BG_SECONDARY)
@@ -467,8 +1464,13 @@ impl LauncherUI {
                             .size(12.0),
                     );
                 } else {
+                    // Only the preview is shortened; the full command still runs
+                    let shown = match command.char_indices().nth(COMMAND_PREVIEW_CHARS) {
+                        Some((end, _)) => format!("{}…", &command[..end]),
+                        None => command.to_string(),
+                    };
                     ui.label(
-                        RichText::new(format!("$ {}", command))
+                        RichText::new(format!("$ {}", shown))
                             .color(theme::TEXT_PRIMARY)
                             .size(13.0)
                             .monospace(),
@@ -476,11 +1478,74 @@ impl LauncherUI {
                 }
             });
 
+        // Past commands matching what's typed; ↑↓ picks one to run instead
+        let suggestions: Vec<(usize, String, String)> = app_state
+            .search_state
+            .results()
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, r)| match &r.kind {
+                SearchResultKind::Command(cmd) if cmd.as_str() != command => {
+                    Some((idx, cmd.clone(), r.description.clone()))
+                }
+                _ => None,
+            })
+            .collect();
+        if self.command_output.is_none() && !suggestions.is_empty() {
+            ui.add_space(theme::SPACING);
+            let mut run = None;
+            for (idx, cmd, description) in suggestions {
+                let is_selected = idx == self.selected_result;
+                let response = Frame::none()
+                    .fill(if is_selected {
+                        theme::BG_SELECTED
+                    } else {
+                        theme::BG_SECONDARY
+                    })
+                    .rounding(theme::ROUNDING)
+                    .inner_margin(egui::Margin::symmetric(theme::PADDING, theme::SPACING))
+                    .show(ui, |ui| {
+                        ui.set_width(ui.available_width());
+                        ui.horizontal(|ui| {
+                            ui.label(
+                                RichText::new(format!("$ {}", cmd))
+                                    .color(theme::TEXT_PRIMARY)
+                                    .size(12.0)
+                                    .monospace(),
+                            );
+                            ui.with_layout(
+                                egui::Layout::right_to_left(egui::Align::Center),
+                                |ui| {
+                                    ui.label(
+                                        RichText::new(description)
+                                            .color(theme::TEXT_MUTED)
+                                            .size(10.0),
+                                    );
+                                },
+                            );
+                        });
+                    })
+                    .response
+                    .interact(egui::Sense::click());
+                if is_selected && self.scroll_to_selected {
+                    response.scroll_to_me(Some(egui::Align::Center));
+                }
+                if response.clicked() {
+                    run = Some(idx);
+                }
+                ui.add_space(2.0);
+            }
+            if let Some(idx) = run {
+                self.run_search_result(ui_state, app_state, idx);
+            }
+        }
+
         if let Some(output) = &self.command_output {
             ui.add_space(theme::SPACING);
+            draw_output_header(ui, output);
             ScrollArea::vertical()
                 .id_salt("cmd_output_search")
-                .max_height(250.0)
+                .max_height(ui.available_height() - theme::PADDING)
                 .show(ui, |ui| {
                     Frame::none()
                         .fill(theme::BG_SECONDARY)
@@ -498,28 +1563,55 @@ impl LauncherUI {
         }
     }
 
-    fn draw_files_view(&mut self, ui: &mut Ui, app: &mut App) {
+    fn draw_files_view(&mut self, ui: &mut Ui, ui_state: &mut UiState, app_state: &mut AppState) {
         // Defensive refresh: if file_list is empty and path exists, reload
-        if app.file_list.is_empty() && app.current_path.exists() {
-            let _ = app.refresh_directory();
+        if app_state.file_list.is_empty() && app_state.current_path.exists() {
+            let _ = ui_state.refresh_directory(app_state);
         }
 
+        let mut crumb_clicked: Option<std::path::PathBuf> = None;
         Frame::none()
             .fill(theme::BG_SECONDARY)
             .rounding(theme::ROUNDING)
             .inner_margin(theme::PADDING)
             .show(ui, |ui| {
-                ui.horizontal(|ui| {
-                    ui.label(RichText::new("📂").size(16.0));
+                ui.horizontal_wrapped(|ui| {
+                    ui.spacing_mut().item_spacing.x = 2.0;
+                    ui.label(RichText::new("📂").size(16.0))
+                        .on_hover_text(app_state.current_path.to_string_lossy());
                     ui.add_space(theme::SPACING);
-                    ui.label(
-                        RichText::new(app.current_path.to_string_lossy())
-                            .color(theme::TEXT_PRIMARY)
-                            .size(13.0),
-                    );
+
+                    let crumbs = fs::breadcrumbs(&app_state.current_path);
+                    let last = crumbs.len().saturating_sub(1);
+                    for (i, (label, target)) in crumbs.into_iter().enumerate() {
+                        let color = if i == last {
+                            theme::TEXT_PRIMARY
+                        } else {
+                            theme::TEXT_SECONDARY
+                        };
+                        let crumb = ui.add(
+                            egui::Button::new(RichText::new(&label).color(color).size(13.0))
+                                .frame(false),
+                        );
+                        if crumb.hovered() {
+                            ui.ctx().set_cursor_icon(egui::CursorIcon::PointingHand);
+                        }
+                        if crumb.clicked() && i != last {
+                            crumb_clicked = Some(target);
+                        }
+                        if i != last && label != "/" {
+                            ui.label(RichText::new("/").color(theme::TEXT_MUTED).size(13.0));
+                        }
+                    }
                 });
             });
 
+        if let Some(target) = crumb_clicked {
+            let _ = ui_state.change_directory(app_state, target);
+            self.selected_file = ui_state.selected_index;
+            self.scroll_to_selected = true;
+        }
+
         ui.add_space(theme::SPACING);
 
         let mut should_run_command = false;
@@ -528,6 +1620,11 @@ impl LauncherUI {
         let mut run_history_command: Option<(String, std::path::PathBuf)> = None;
 
         if self.files_command_mode {
+            if !self.tab_completions.is_empty() {
+                self.draw_completion_candidates(ui);
+                ui.add_space(4.0);
+            }
+
             Frame::none()
                 .fill(theme::BG_SECONDARY)
                 .rounding(theme::ROUNDING)
@@ -537,9 +1634,24 @@ impl LauncherUI {
                         ui.label(RichText::new("$").size(16.0).color(theme::ACCENT));
                         ui.add_space(theme::SPACING);
 
+                        // Emacs-style line editing; consumed before the text box sees the keys
+                        let edit_id = egui::Id::new("files_command_input");
+                        let cursor = TextEdit::load_state(ui.ctx(), edit_id)
+                            .and_then(|state| state.cursor.char_range())
+                            .map(|range| range.primary.index)
+                            .unwrap_or_else(|| self.files_command_input.chars().count());
+                        for key in LINE_EDIT_KEYS {
+                            if ui.input_mut(|i| i.consume_key(egui::Modifiers::CTRL, key)) {
+                                let at = self.command_cursor.unwrap_or(cursor);
+                                self.command_cursor =
+                                    Some(apply_line_edit(&mut self.files_command_input, at, key));
+                            }
+                        }
+
                         let response = ui.add_sized(
                             [ui.available_width(), 22.0],
                             TextEdit::singleline(&mut self.files_command_input)
+                                .id(edit_id)
                                 .hint_text("Enter command and press Enter... (Tab: complete)")
                                 .font(egui::FontId::monospace(14.0))
                                 .frame(false)
@@ -548,13 +1660,15 @@ impl LauncherUI {
 
                         response.request_focus();
 
-                        // Move cursor to end after tab completion
-                        if self.cursor_to_end {
-                            self.cursor_to_end = false;
+                        // Move cursor to end after tab completion, or to where a line edit left it
+                        let new_cursor = if std::mem::take(&mut self.cursor_to_end) {
+                            Some(self.files_command_input.chars().count())
+                        } else {
+                            self.command_cursor.take()
+                        };
+                        if let Some(new_cursor) = new_cursor {
                             if let Some(mut state) = TextEdit::load_state(ui.ctx(), response.id) {
-                                let ccursor = egui::text::CCursor::new(
-                                    self.files_command_input.chars().count(),
-                                );
+                                let ccursor = egui::text::CCursor::new(new_cursor);
                                 state
                                     .cursor
                                     .set_char_range(Some(egui::text::CCursorRange::one(ccursor)));
@@ -566,10 +1680,10 @@ impl LauncherUI {
                             if !self.files_command_input.is_empty() {
                                 should_run_command = true;
                                 command_to_run = self.files_command_input.clone();
-                            } else if !app.command_history.is_empty() {
+                            } else if !app_state.command_history.is_empty() {
                                 // Enter on history item
                                 if let Some(entry) =
-                                    app.command_history.get(self.selected_command_history)
+                                    app_state.command_history.get(self.selected_command_history)
                                 {
                                     run_history_command =
                                         Some((entry.command.clone(), entry.path.clone()));
@@ -577,28 +1691,19 @@ impl LauncherUI {
                             }
                         }
 
-                        if ui.input(|i| i.key_pressed(Key::ArrowUp))
-                            && self.files_command_input.is_empty()
-                        {
-                            if self.selected_command_history > 0 {
-                                self.selected_command_history -= 1;
-                            }
+                        if ui.input(|i| i.key_pressed(Key::ArrowUp)) {
+                            self.recall_files_command(app_state, true);
                         }
-                        if ui.input(|i| i.key_pressed(Key::ArrowDown))
-                            && self.files_command_input.is_empty()
-                        {
-                            let max = app.command_history.len().saturating_sub(1);
-                            if self.selected_command_history < max {
-                                self.selected_command_history += 1;
-                            }
+                        if ui.input(|i| i.key_pressed(Key::ArrowDown)) {
+                            self.recall_files_command(app_state, false);
                         }
                     });
                 });
 
             // Show command history when input is empty
-            if self.files_command_input.is_empty() && !app.command_history.is_empty() {
+            if self.files_command_input.is_empty() && !app_state.command_history.is_empty() {
                 ui.add_space(4.0);
-                let cmd_entries: Vec<_> = app
+                let cmd_entries: Vec<_> = app_state
                     .command_history
                     .iter()
                     .enumerate()
@@ -665,24 +1770,29 @@ impl LauncherUI {
         }
 
         if let Some((cmd, path)) = run_history_command {
-            let _ = app.change_directory(path);
-            self.execute_command_sync(&cmd, app);
+            let _ = ui_state.change_directory(app_state, path);
+            self.execute_command(&cmd, ui_state, app_state);
             self.files_command_mode = false;
+            ui_state.set_mode(ModeEvent::Confirm);
             self.files_command_input.clear();
+            self.tab_completions.clear();
             self.selected_command_history = 0;
         }
 
         if should_run_command {
-            self.execute_command_sync(&command_to_run, app);
+            self.execute_command(&command_to_run, ui_state, app_state);
             self.files_command_mode = false;
+            ui_state.set_mode(ModeEvent::Confirm);
             self.files_command_input.clear();
+            self.tab_completions.clear();
         }
 
         if let Some(output) = &self.command_output {
             if !self.files_command_mode {
+                draw_output_header(ui, output);
                 ScrollArea::vertical()
                     .id_salt("cmd_output_files")
-                    .max_height(80.0)
+                    .max_height(ui.available_height() * 0.3)
                     .show(ui, |ui| {
                         Frame::none()
                             .fill(theme::BG_SECONDARY)
@@ -701,8 +1811,18 @@ impl LauncherUI {
             }
         }
 
+        if ui_state.is_loading_directory {
+            draw_loading_directory(ui);
+            return;
+        }
+
+        if !app_state.dir_errors.is_empty() {
+            draw_directory_errors(ui, &app_state.dir_errors);
+        }
+
         let mut action: Option<usize> = None;
         let selected = self.selected_file;
+        let visual_range = ui_state.visual_range();
 
         let max_height = if self.command_output.is_some() && !self.files_command_mode {
             200.0
@@ -710,164 +1830,542 @@ impl LauncherUI {
             280.0
         };
 
-        let files: Vec<_> = app
-            .get_display_list()
-            .iter()
-            .enumerate()
-            .map(|(i, f)| (i, f.name.clone(), f.is_dir, f.size))
-            .collect();
+        let file_count = ui_state.get_display_list(app_state).len();
+        let do_scroll = self.take_scroll_request();
 
-        let file_count = files.len();
-        let do_scroll = self.scroll_to_selected;
-        self.scroll_to_selected = false;
-
-        ScrollArea::vertical()
+        // Only the rows in view are built, so huge directories stay responsive.
+        // Rows off screen have no rect to scroll to, so jump by offset instead.
+        let row_height = self.item_height;
+        let mut scroll_area = ScrollArea::vertical()
             .id_salt("files_scroll")
             .max_height(max_height)
-            .auto_shrink([false, false])
-            .show(ui, |ui| {
-                for (idx, name, is_dir, size) in &files {
-                    let is_selected = *idx == selected;
-                    let bg_color = if is_selected {
-                        theme::BG_SELECTED
-                    } else {
-                        theme::BG_PRIMARY
-                    };
+            .auto_shrink([false, false]);
+        if do_scroll {
+            let stride = row_height + ui.spacing().item_spacing.y;
+            let offset = selected as f32 * stride - (max_height - row_height) / 2.0;
+            scroll_area = scroll_area.vertical_scroll_offset(offset.max(0.0));
+        }
 
-                    let response = Frame::none()
-                        .fill(bg_color)
-                        .rounding(theme::ROUNDING / 2.0)
-                        .inner_margin(egui::Margin::symmetric(theme::PADDING, 4.0))
-                        .show(ui, |ui| {
-                            ui.set_min_height(ITEM_HEIGHT - 8.0);
-                            ui.horizontal(|ui| {
-                                let icon = if *is_dir { "📁" } else { "📄" };
-                                ui.label(RichText::new(icon).size(14.0));
-                                ui.add_space(theme::SPACING);
-                                ui.label(
-                                    RichText::new(name)
-                                        .color(if is_selected {
-                                            theme::ACCENT
-                                        } else {
-                                            theme::TEXT_PRIMARY
-                                        })
-                                        .size(13.0),
-                                );
+        scroll_area.show_rows(ui, row_height, file_count, |ui, row_range| {
+            let files: Vec<_> = ui_state.get_display_list(app_state)[row_range.clone()]
+                .iter()
+                .zip(row_range)
+                .map(|(f, i)| (i, f.name.clone(), f.is_dir, f.size))
+                .collect();
+
+            for (idx, name, is_dir, size) in &files {
+                let is_selected = *idx == selected;
+                let in_visual = visual_range.as_ref().is_some_and(|r| r.contains(idx));
+                let bg_color = if is_selected {
+                    theme::BG_SELECTED
+                } else if in_visual {
+                    theme::BG_VISUAL
+                } else {
+                    theme::BG_PRIMARY
+                };
 
-                                ui.with_layout(
-                                    egui::Layout::right_to_left(egui::Align::Center),
-                                    |ui| {
-                                        if !*is_dir {
-                                            ui.label(
-                                                RichText::new(format_size(*size))
-                                                    .color(theme::TEXT_MUTED)
-                                                    .size(11.0),
-                                            );
-                                        }
-                                    },
-                                );
-                            });
-                        });
+                let response = Frame::none()
+                    .fill(bg_color)
+                    .rounding(theme::ROUNDING / 2.0)
+                    .inner_margin(egui::Margin::symmetric(theme::PADDING, 4.0))
+                    .show(ui, |ui| {
+                        ui.set_min_height(self.item_height - 8.0);
+                        ui.horizontal(|ui| {
+                            let icon = if *is_dir { "📁" } else { "📄" };
+                            ui.label(RichText::new(icon).size(14.0));
+                            ui.add_space(theme::SPACING);
+                            ui.label(
+                                RichText::new(name)
+                                    .color(if is_selected {
+                                        theme::ACCENT
+                                    } else {
+                                        theme::TEXT_PRIMARY
+                                    })
+                                    .size(13.0),
+                            );
 
-                    if is_selected && do_scroll {
-                        ui.scroll_to_rect(response.response.rect, Some(egui::Align::Center));
-                    }
+                            ui.with_layout(
+                                egui::Layout::right_to_left(egui::Align::Center),
+                                |ui| {
+                                    if !*is_dir {
+                                        ui.label(
+                                            RichText::new(format_size(*size))
+                                                .color(theme::TEXT_MUTED)
+                                                .size(11.0),
+                                        );
+                                    }
+                                },
+                            );
+                        });
+                    });
 
-                    if response.response.clicked() {
-                        action = Some(*idx);
-                    }
-                    if response.response.hovered() && !is_selected {
-                        self.selected_file = *idx;
-                        app.selected_index = *idx;
-                    }
+                if response.response.clicked() {
+                    action = Some(*idx);
                 }
-
-                if file_count == 0 {
-                    ui.label(
-                        RichText::new("Empty directory")
-                            .color(theme::TEXT_MUTED)
-                            .size(12.0),
-                    );
+                if response.response.hovered() && !is_selected {
+                    self.selected_file = *idx;
+                    ui_state.selected_index = *idx;
                 }
-            });
+            }
+
+            if file_count == 0 {
+                ui.label(
+                    RichText::new("Empty directory")
+                        .color(theme::TEXT_MUTED)
+                        .size(12.0),
+                );
+            }
+        });
 
         if let Some(idx) = action {
             self.selected_file = idx;
-            app.selected_index = idx;
-            let is_dir = app
-                .get_display_list()
+            ui_state.selected_index = idx;
+            let is_dir = ui_state
+                .get_display_list(app_state)
                 .get(idx)
                 .map(|f| f.is_dir)
                 .unwrap_or(false);
-            let _ = app.enter_selected();
+            let _ = ui_state.enter_selected(app_state);
             if is_dir {
-                self.selected_file = 0;
+                self.selected_file = ui_state.selected_index;
                 self.scroll_to_selected = true;
             }
         }
 
-        ui.add_space(theme::SPACING);
-        let hint = if self.files_command_mode {
-            "Enter: run command | Tab: complete | ↑↓: history | Esc: cancel"
-        } else {
-            "↑↓ jk: Navigate | →l: Open | ←h: Up | r: Refresh | c: Command"
-        };
-        ui.label(RichText::new(hint).color(theme::TEXT_MUTED).size(10.0));
-    }
+        ui.add_space(theme::SPACING);
+        if self.show_file_stats && !self.files_command_mode && visual_range.is_none() {
+            let version = app_state.file_list_version;
+            let is_filtering = ui_state.is_filtering;
+            let stale = self.files_stats.as_ref().map_or(true, |s| {
+                s.version != version || s.is_filtering != is_filtering
+            });
+            if stale {
+                self.files_stats = Some(FilesStats::compute(ui_state, app_state, version));
+            }
+            if let Some(stats) = &self.files_stats {
+                ui.label(
+                    RichText::new(stats.summary())
+                        .color(theme::TEXT_MUTED)
+                        .size(10.0),
+                );
+            }
+            return;
+        }
+        let hint = if self.files_command_mode {
+            "Enter: run command | Tab: complete | ↑↓: history | Esc: cancel".to_string()
+        } else if let Some(range) = &visual_range {
+            format!(
+                "VISUAL: {} selected | ↑↓ jk: Extend | y: Yank | d: Trash | Enter: Open with | Esc: Cancel",
+                range.clone().count()
+            )
+        } else {
+            "↑↓ jk: Navigate | →l: Open | ←h: Up | T: Open terminal | r: Refresh | c: Command | v: Visual"
+                .to_string()
+        };
+        ui.label(RichText::new(hint).color(theme::TEXT_MUTED).size(10.0));
+    }
+
+    fn draw_settings_view(
+        &mut self,
+        ui: &mut Ui,
+        ui_state: &mut UiState,
+        app_state: &mut AppState,
+        settings: &mut LauncherSettings,
+    ) {
+        ui.label(
+            RichText::new("Settings")
+                .color(theme::TEXT_PRIMARY)
+                .size(18.0),
+        );
+        ui.add_space(theme::PADDING);
+
+        ScrollArea::vertical()
+            .id_salt("settings_scroll")
+            .max_height(350.0)
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                // Window Position
+                Frame::none()
+                    .fill(theme::BG_SECONDARY)
+                    .rounding(theme::ROUNDING)
+                    .inner_margin(theme::PADDING)
+                    .show(ui, |ui| {
+                        ui.label(
+                            RichText::new("Window Position")
+                                .color(theme::TEXT_PRIMARY)
+                                .size(14.0),
+                        );
+                        ui.add_space(theme::SPACING);
+
+                        let positions = [
+                            (WindowPosition::TopCenter, "Top Center"),
+                            (WindowPosition::Center, "Center"),
+                            (WindowPosition::TopLeft, "Top Left"),
+                            (WindowPosition::TopRight, "Top Right"),
+                            (WindowPosition::BottomCenter, "Bottom Center"),
+                            (WindowPosition::BottomLeft, "Bottom Left"),
+                            (WindowPosition::BottomRight, "Bottom Right"),
+                        ];
+
+                        ui.horizontal_wrapped(|ui| {
+                            for (pos, label) in positions {
+                                let is_selected = std::mem::discriminant(&settings.position)
+                                    == std::mem::discriminant(&pos);
+                                if ui.selectable_label(is_selected, label).clicked() {
+                                    settings.position = pos;
+                                    self.settings_changed = true;
+                                }
+                            }
+                        });
+
+                        ui.add_space(theme::SPACING);
+                        if ui
+                            .checkbox(
+                                &mut settings.follow_cursor_monitor,
+                                "Open on the monitor under the cursor",
+                            )
+                            .changed()
+                        {
+                            self.settings_changed = true;
+                        }
+
+                        ui.add_space(theme::SPACING);
+                        ui.label(
+                            RichText::new("Restart required for position changes")
+                                .color(theme::TEXT_MUTED)
+                                .size(10.0),
+                        );
+                    });
+
+                ui.add_space(theme::PADDING);
+
+                // Window Size
+                Frame::none()
+                    .fill(theme::BG_SECONDARY)
+                    .rounding(theme::ROUNDING)
+                    .inner_margin(theme::PADDING)
+                    .show(ui, |ui| {
+                        ui.label(
+                            RichText::new("Window Size")
+                                .color(theme::TEXT_PRIMARY)
+                                .size(14.0),
+                        );
+                        ui.add_space(theme::SPACING);
+
+                        let width = ui.add(
+                            egui::Slider::new(
+                                &mut settings.width,
+                                LauncherSettings::MIN_WIDTH..=LauncherSettings::MAX_WIDTH,
+                            )
+                            .step_by(10.0)
+                            .text("Width"),
+                        );
+                        let height = ui.add(
+                            egui::Slider::new(
+                                &mut settings.height,
+                                LauncherSettings::MIN_HEIGHT..=LauncherSettings::MAX_HEIGHT,
+                            )
+                            .step_by(10.0)
+                            .text("Height"),
+                        );
+
+                        // Only persist once the slider is released
+                        let released =
+                            |r: &egui::Response| r.drag_stopped() || (r.changed() && !r.dragged());
+                        if released(&width) || released(&height) {
+                            self.settings_changed = true;
+                        }
+                    });
+
+                ui.add_space(theme::PADDING);
+
+                // Default View
+                Frame::none()
+                    .fill(theme::BG_SECONDARY)
+                    .rounding(theme::ROUNDING)
+                    .inner_margin(theme::PADDING)
+                    .show(ui, |ui| {
+                        ui.label(
+                            RichText::new("Default View")
+                                .color(theme::TEXT_PRIMARY)
+                                .size(14.0),
+                        );
+                        ui.add_space(theme::SPACING);
+
+                        let previous = settings.default_view;
+                        egui::ComboBox::from_id_salt("default_view")
+                            .selected_text(settings.default_view.label())
+                            .show_ui(ui, |ui| {
+                                for view in LauncherView::ALL {
+                                    ui.selectable_value(
+                                        &mut settings.default_view,
+                                        view,
+                                        view.label(),
+                                    );
+                                }
+                            });
+                        if settings.default_view != previous {
+                            self.settings_changed = true;
+                        }
+
+                        if ui
+                            .checkbox(
+                                &mut settings.reset_to_default_view,
+                                "Open to this view every time the window is shown",
+                            )
+                            .changed()
+                        {
+                            self.settings_changed = true;
+                        }
+                    });
+
+                ui.add_space(theme::PADDING);
+
+                // Terminal
+                Frame::none()
+                    .fill(theme::BG_SECONDARY)
+                    .rounding(theme::ROUNDING)
+                    .inner_margin(theme::PADDING)
+                    .show(ui, |ui| {
+                        ui.label(
+                            RichText::new("Terminal")
+                                .color(theme::TEXT_PRIMARY)
+                                .size(14.0),
+                        );
+                        ui.add_space(theme::SPACING);
+
+                        let response = ui.add_sized(
+                            [ui.available_width(), 20.0],
+                            TextEdit::singleline(&mut settings.terminal_command)
+                                .hint_text("e.g. kitty --single-instance")
+                                .font(egui::FontId::monospace(12.0))
+                                .frame(true)
+                                .text_color(theme::TEXT_PRIMARY),
+                        );
+                        if response.changed() {
+                            self.settings_changed = true;
+                        }
+
+                        ui.add_space(theme::SPACING);
+                        ui.label(
+                            RichText::new(
+                                "Opened in the current directory with T in the Files view",
+                            )
+                            .color(theme::TEXT_MUTED)
+                            .size(10.0),
+                        );
+                    });
+
+                ui.add_space(theme::PADDING);
+
+                // Clipboard
+                Frame::none()
+                    .fill(theme::BG_SECONDARY)
+                    .rounding(theme::ROUNDING)
+                    .inner_margin(theme::PADDING)
+                    .show(ui, |ui| {
+                        ui.label(
+                            RichText::new("Clipboard")
+                                .color(theme::TEXT_PRIMARY)
+                                .size(14.0),
+                        );
+                        ui.add_space(theme::SPACING);
+
+                        if ui
+                            .checkbox(
+                                &mut settings.monitor_primary_selection,
+                                "Track primary selection (middle-click paste)",
+                            )
+                            .changed()
+                        {
+                            app_state
+                                .clipboard_monitor
+                                .set_primary_selection(settings.monitor_primary_selection);
+                            self.settings_changed = true;
+                        }
+                        if ui
+                            .checkbox(&mut settings.auto_paste, "Paste after copying with Enter")
+                            .on_hover_text("Hides the window and sends Ctrl+V (needs xdotool)")
+                            .changed()
+                        {
+                            self.settings_changed = true;
+                        }
+
+                        ui.add_space(theme::SPACING);
+                        let previous = settings.clipboard_sort;
+                        ui.horizontal(|ui| {
+                            ui.label(RichText::new("Order").color(theme::TEXT_SECONDARY));
+                            egui::ComboBox::from_id_salt("clipboard_sort")
+                                .selected_text(settings.clipboard_sort.label())
+                                .show_ui(ui, |ui| {
+                                    for sort in ClipboardSort::ALL {
+                                        ui.selectable_value(
+                                            &mut settings.clipboard_sort,
+                                            sort,
+                                            sort.label(),
+                                        );
+                                    }
+                                });
+                        });
+                        if settings.clipboard_sort != previous {
+                            app_state.clipboard_sort = settings.clipboard_sort;
+                            app_state.refresh_clipboard(&ui_state.clipboard_query);
+                            self.settings_changed = true;
+                        }
+
+                        ui.add_space(theme::SPACING);
+                        ui.label(
+                            RichText::new("Primary selection entries expire after 1 hour")
+                                .color(theme::TEXT_MUTED)
+                                .size(10.0),
+                        );
+                    });
+
+                ui.add_space(theme::PADDING);
+
+                // History
+                Frame::none()
+                    .fill(theme::BG_SECONDARY)
+                    .rounding(theme::ROUNDING)
+                    .inner_margin(theme::PADDING)
+                    .show(ui, |ui| {
+                        ui.label(
+                            RichText::new("History")
+                                .color(theme::TEXT_PRIMARY)
+                                .size(14.0),
+                        );
+                        ui.add_space(theme::SPACING);
+
+                        ui.horizontal(|ui| {
+                            if ui
+                                .add(
+                                    egui::Button::new(RichText::new("Remove missing").size(12.0))
+                                        .rounding(theme::ROUNDING / 2.0),
+                                )
+                                .clicked()
+                            {
+                                let _ = ui_state.remove_missing_recent(app_state);
+                            }
+                            ui.label(
+                                RichText::new("Forget recent files that have been deleted")
+                                    .color(theme::TEXT_MUTED)
+                                    .size(10.0),
+                            );
+                        });
+                    });
+
+                ui.add_space(theme::PADDING);
+
+                // Database
+                Frame::none()
+                    .fill(theme::BG_SECONDARY)
+                    .rounding(theme::ROUNDING)
+                    .inner_margin(theme::PADDING)
+                    .show(ui, |ui| {
+                        ui.label(
+                            RichText::new("Database")
+                                .color(theme::TEXT_PRIMARY)
+                                .size(14.0),
+                        );
+                        ui.add_space(theme::SPACING);
+
+                        if let Some((stats, size)) = &self.db_stats {
+                            let rows = [
+                                ("Recent files", stats.recent_access),
+                                ("Clipboard entries", stats.clipboard_history),
+                                ("Commands", stats.command_history),
+                                ("App launches", stats.app_launch_history),
+                            ];
+                            for (label, count) in rows {
+                                ui.horizontal(|ui| {
+                                    ui.label(
+                                        RichText::new(label)
+                                            .color(theme::TEXT_SECONDARY)
+                                            .size(12.0),
+                                    );
+                                    ui.label(
+                                        RichText::new(count.to_string())
+                                            .color(theme::TEXT_PRIMARY)
+                                            .size(12.0),
+                                    );
+                                });
+                            }
+                            ui.horizontal(|ui| {
+                                ui.label(
+                                    RichText::new("Size")
+                                        .color(theme::TEXT_SECONDARY)
+                                        .size(12.0),
+                                );
+                                ui.label(
+                                    RichText::new(format_size(*size))
+                                        .color(theme::TEXT_PRIMARY)
+                                        .size(12.0),
+                                );
+                            });
+                        }
+
+                        let path = app_state.db_path.display().to_string();
+                        if ui
+                            .add(
+                                egui::Label::new(
+                                    RichText::new(&path).color(theme::TEXT_MUTED).size(10.0),
+                                )
+                                .sense(egui::Sense::click()),
+                            )
+                            .on_hover_text("Click to copy")
+                            .clicked()
+                        {
+                            if clipboard::copy_to_clipboard(&path).is_ok() {
+                                ui_state.show_toast("Copied database path", ToastKind::Success);
+                            }
+                        }
+                        ui.add_space(theme::SPACING);
+
+                        if ui
+                            .add(
+                                egui::Button::new(RichText::new("Vacuum").size(12.0))
+                                    .rounding(theme::ROUNDING / 2.0),
+                            )
+                            .clicked()
+                        {
+                            match history::vacuum(&app_state.db_connection) {
+                                Ok(()) => {
+                                    ui_state.show_toast("Database compacted", ToastKind::Success)
+                                }
+                                Err(e) => ui_state.set_error(format!("Vacuum failed: {}", e)),
+                            }
+                            self.refresh_db_stats(app_state);
+                        }
+                    });
 
-    fn draw_settings_view(&mut self, ui: &mut Ui, app: &mut App, settings: &mut LauncherSettings) {
-        ui.label(
-            RichText::new("Settings")
-                .color(theme::TEXT_PRIMARY)
-                .size(18.0),
-        );
-        ui.add_space(theme::PADDING);
+                ui.add_space(theme::PADDING);
 
-        ScrollArea::vertical()
-            .id_salt("settings_scroll")
-            .max_height(350.0)
-            .auto_shrink([false, false])
-            .show(ui, |ui| {
-                // Window Position
+                // Commands
                 Frame::none()
                     .fill(theme::BG_SECONDARY)
                     .rounding(theme::ROUNDING)
                     .inner_margin(theme::PADDING)
                     .show(ui, |ui| {
                         ui.label(
-                            RichText::new("Window Position")
+                            RichText::new("Commands")
                                 .color(theme::TEXT_PRIMARY)
                                 .size(14.0),
                         );
                         ui.add_space(theme::SPACING);
 
-                        let positions = [
-                            (WindowPosition::TopCenter, "Top Center"),
-                            (WindowPosition::Center, "Center"),
-                            (WindowPosition::TopLeft, "Top Left"),
-                            (WindowPosition::TopRight, "Top Right"),
-                            (WindowPosition::BottomCenter, "Bottom Center"),
-                            (WindowPosition::BottomLeft, "Bottom Left"),
-                            (WindowPosition::BottomRight, "Bottom Right"),
-                        ];
-
-                        ui.horizontal_wrapped(|ui| {
-                            for (pos, label) in positions {
-                                let is_selected = std::mem::discriminant(&settings.position)
-                                    == std::mem::discriminant(&pos);
-                                if ui.selectable_label(is_selected, label).clicked() {
-                                    settings.position = pos;
-                                    settings.save();
-                                }
-                            }
-                        });
-
-                        ui.add_space(theme::SPACING);
-                        ui.label(
-                            RichText::new("Restart required for position changes")
-                                .color(theme::TEXT_MUTED)
-                                .size(10.0),
+                        let max_lines = ui.add(
+                            egui::Slider::new(
+                                &mut settings.command_output_max_lines,
+                                LauncherSettings::MIN_OUTPUT_LINES
+                                    ..=LauncherSettings::MAX_OUTPUT_LINES,
+                            )
+                            .logarithmic(true)
+                            .text("Max output lines"),
                         );
+                        if max_lines.drag_stopped() || (max_lines.changed() && !max_lines.dragged())
+                        {
+                            self.settings_changed = true;
+                        }
                     });
 
                 ui.add_space(theme::PADDING);
@@ -915,9 +2413,12 @@ impl LauncherUI {
 
                         if add_dir {
                             let dir = self.exclude_input.trim().to_string();
-                            if !dir.is_empty() && !app.search_config.exclude_dirs.contains(&dir) {
-                                app.search_config.exclude_dirs.push(dir);
-                                app.search_config.save();
+                            if !dir.is_empty()
+                                && !app_state.search_config.exclude_dirs.contains(&dir)
+                            {
+                                app_state.search_config.exclude_dirs.push(dir);
+                                app_state.search_config.save();
+                                app_state.refresh_active_search_config();
                             }
                             self.exclude_input.clear();
                         }
@@ -926,7 +2427,7 @@ impl LauncherUI {
 
                         // List current exclusions
                         let mut remove_idx: Option<usize> = None;
-                        let dirs: Vec<_> = app
+                        let dirs: Vec<_> = app_state
                             .search_config
                             .exclude_dirs
                             .iter()
@@ -959,8 +2460,21 @@ impl LauncherUI {
                         });
 
                         if let Some(idx) = remove_idx {
-                            app.search_config.exclude_dirs.remove(idx);
-                            app.search_config.save();
+                            app_state.search_config.exclude_dirs.remove(idx);
+                            app_state.search_config.save();
+                            app_state.refresh_active_search_config();
+                        }
+
+                        ui.add_space(theme::SPACING);
+                        if ui
+                            .checkbox(
+                                &mut app_state.search_config.respect_gitignore,
+                                "Respect .gitignore in / searches",
+                            )
+                            .changed()
+                        {
+                            app_state.search_config.save();
+                            app_state.refresh_active_search_config();
                         }
                     });
 
@@ -1037,17 +2551,76 @@ impl LauncherUI {
                             });
                         }
                     });
+
+                ui.add_space(theme::PADDING);
+
+                // About
+                Frame::none()
+                    .fill(theme::BG_SECONDARY)
+                    .rounding(theme::ROUNDING)
+                    .inner_margin(theme::PADDING)
+                    .show(ui, |ui| {
+                        egui::CollapsingHeader::new(
+                            RichText::new("About").color(theme::TEXT_PRIMARY).size(14.0),
+                        )
+                        .id_salt("settings_about")
+                        .show(ui, |ui| {
+                            for (label, value) in [
+                                ("Version", env!("CARGO_PKG_VERSION")),
+                                ("Built", env!("FILECAST_BUILD_DATE")),
+                                ("Compiler", env!("RUSTC_VERSION")),
+                                ("Target", env!("TARGET")),
+                            ] {
+                                ui.horizontal(|ui| {
+                                    ui.label(
+                                        RichText::new(label)
+                                            .color(theme::TEXT_SECONDARY)
+                                            .size(12.0),
+                                    );
+                                    ui.label(
+                                        RichText::new(value)
+                                            .color(theme::TEXT_PRIMARY)
+                                            .size(12.0)
+                                            .monospace(),
+                                    );
+                                });
+                            }
+                            ui.add_space(theme::SPACING);
+
+                            ui.horizontal(|ui| {
+                                if ui
+                                    .add(
+                                        egui::Button::new(
+                                            RichText::new("Copy version info").size(11.0),
+                                        )
+                                        .frame(true)
+                                        .rounding(theme::ROUNDING / 2.0),
+                                    )
+                                    .clicked()
+                                {
+                                    match clipboard::copy_to_clipboard(&version_info()) {
+                                        Ok(()) => ui_state
+                                            .show_toast("Copied version info", ToastKind::Success),
+                                        Err(e) => {
+                                            ui_state.set_error(format!("Failed to copy: {}", e))
+                                        }
+                                    }
+                                }
+                                ui.hyperlink_to("GitHub", env!("CARGO_PKG_REPOSITORY"));
+                            });
+                        });
+                    });
             });
     }
 
-    fn draw_search_input(&mut self, ui: &mut Ui, app: &mut App) {
+    fn draw_search_input(&mut self, ui: &mut Ui, ui_state: &mut UiState, app_state: &mut AppState) {
         Frame::none()
             .fill(theme::BG_SECONDARY)
             .rounding(theme::ROUNDING)
             .inner_margin(theme::PADDING)
             .show(ui, |ui| {
                 ui.horizontal(|ui| {
-                    let icon = match app.search_query.chars().next() {
+                    let icon = match ui_state.search_query.chars().next() {
                         Some(':') => ">",
                         Some('@') => "🔎",
                         Some('/') => "📂",
@@ -1055,11 +2628,21 @@ impl LauncherUI {
                     };
                     ui.label(RichText::new(icon).size(18.0).color(theme::TEXT_SECONDARY));
                     ui.add_space(theme::SPACING);
+                    if app_state.search_case_sensitive {
+                        ui.label(
+                            RichText::new("Aa")
+                                .size(10.0)
+                                .monospace()
+                                .color(theme::ACCENT),
+                        )
+                        .on_hover_text("Case-sensitive (Alt+C)");
+                        ui.add_space(theme::SPACING);
+                    }
 
                     let response = ui.add_sized(
                         [ui.available_width(), 24.0],
-                        TextEdit::singleline(&mut app.search_query)
-                            .hint_text("Search apps, files... (@grep, /find, :cmd)")
+                        TextEdit::singleline(&mut ui_state.search_query)
+                            .hint_text("Search apps, files... (@grep, /find, :cmd, =calc)")
                             .font(theme::search_input_font())
                             .frame(false)
                             .text_color(theme::TEXT_PRIMARY),
@@ -1067,79 +2650,223 @@ impl LauncherUI {
 
                     self.search_focused = response.has_focus();
 
-                    if app.window_visible && self.search_focused {
+                    if ui_state.window_visible && self.search_focused {
                         response.request_focus();
                     }
 
                     if response.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)) {
-                        if app.search_query.starts_with(':') {
-                            let command = app
-                                .search_query
-                                .strip_prefix(':')
-                                .unwrap_or("")
-                                .trim()
-                                .to_string();
-                            if !command.is_empty() {
-                                self.execute_command_sync(&command, app);
-                            }
-                        } else if !app.search_results.is_empty() {
-                            let _ = app.execute_search_result(self.selected_result);
-                            app.search_query.clear();
-                            app.search_results.clear();
-                            self.selected_result = 0;
+                        if !app_state.search_state.is_empty() {
+                            self.run_search_result(ui_state, app_state, self.selected_result);
                         }
                     }
 
                     if response.changed() {
-                        if !app.search_query.starts_with(':') {
-                            app.update_search(&app.search_query.clone());
-                        }
+                        ui_state.update_search(app_state, &ui_state.search_query.clone());
                         self.selected_result = 0;
+                        self.selected_action = None;
                         self.command_output = None;
                     }
                 });
             });
     }
 
-    fn execute_command_sync(&mut self, command: &str, app: &mut App) {
-        let parts: Vec<&str> = command.split_whitespace().collect();
-        if parts.is_empty() {
+    /// One badge per search backend, highlighting those that will handle the query.
+    /// Clicking a badge swaps the query's prefix for that backend's.
+    fn draw_provider_badges(
+        &mut self,
+        ui: &mut Ui,
+        ui_state: &mut UiState,
+        app_state: &mut AppState,
+    ) {
+        let mut clicked = None;
+        ui.horizontal_wrapped(|ui| {
+            for provider in SearchProvider::ALL {
+                let color = if provider.handles(&ui_state.search_query) {
+                    theme::ACCENT
+                } else {
+                    theme::TEXT_MUTED
+                };
+                let badge = egui::Button::new(
+                    RichText::new(format!("{} {}", provider.icon(), provider.label()))
+                        .color(color)
+                        .size(10.0),
+                )
+                .fill(theme::BG_SECONDARY)
+                .rounding(theme::ROUNDING)
+                .small();
+                let hint = match provider.prefix() {
+                    Some(prefix) => format!("Start the query with {}", prefix),
+                    None => String::from("Plain queries search apps and files"),
+                };
+                if ui.add(badge).on_hover_text(hint).clicked() {
+                    clicked = Some(provider);
+                }
+            }
+        });
+
+        let Some(provider) = clicked else {
+            return;
+        };
+        let rest = SearchProvider::strip_prefix(&ui_state.search_query);
+        let query = match provider.prefix() {
+            Some(prefix) => format!("{}{}", prefix, rest),
+            None => rest.to_string(),
+        };
+        ui_state.update_search(app_state, &query);
+        self.selected_result = 0;
+        self.selected_action = None;
+        self.command_output = None;
+        self.search_focused = true;
+    }
+
+    /// This session's commands followed by earlier ones run in the current directory,
+    /// newest first and without duplicates
+    fn files_command_candidates(&self, app_state: &AppState) -> Vec<String> {
+        let mut candidates: Vec<String> = Vec::new();
+        let from_db = app_state
+            .command_history
+            .iter()
+            .filter(|entry| entry.path == app_state.current_path)
+            .map(|entry| &entry.command);
+        for command in self.files_command_history.iter().chain(from_db) {
+            if !candidates.contains(command) {
+                candidates.push(command.clone());
+            }
+        }
+        candidates
+    }
+
+    /// Replace the command input with the previous (`older`) or next history entry,
+    /// restoring what was typed once ↓ goes past the newest
+    fn recall_files_command(&mut self, app_state: &AppState, older: bool) {
+        let candidates = self.files_command_candidates(app_state);
+        if candidates.is_empty() {
+            return;
+        }
+
+        let index = match (self.files_history_index, older) {
+            (None, true) => {
+                self.files_command_draft = self.files_command_input.clone();
+                Some(0)
+            }
+            (None, false) => None,
+            (Some(i), true) => Some((i + 1).min(candidates.len() - 1)),
+            (Some(0), false) => None,
+            (Some(i), false) => Some(i - 1),
+        };
+
+        self.files_command_input = match index {
+            Some(i) => candidates[i].clone(),
+            None => std::mem::take(&mut self.files_command_draft),
+        };
+        self.files_history_index = index;
+        self.tab_completions.clear();
+        self.cursor_to_end = true;
+    }
+
+    /// Start `command` in the background; its output streams in via `sync_command_output`
+    fn execute_command(&mut self, command: &str, ui_state: &mut UiState, app_state: &mut AppState) {
+        let stages: Vec<&str> = command.split(" | ").map(str::trim).collect();
+        if command.trim().is_empty() {
             return;
         }
 
         // Persist command to history
-        let _ = history::log_command(&app.db_connection, command, &app.current_path);
-        app.refresh_command_history();
-
-        let output = std::process::Command::new(parts[0])
-            .args(&parts[1..])
-            .current_dir(&app.current_path)
-            .output();
-
-        match output {
-            Ok(output) => {
-                let stdout = String::from_utf8_lossy(&output.stdout);
-                let stderr = String::from_utf8_lossy(&output.stderr);
-
-                if output.status.success() {
-                    if stdout.is_empty() {
-                        self.command_output = Some("(no output)".to_string());
-                    } else {
-                        self.command_output = Some(stdout.to_string());
-                    }
-                } else {
-                    self.command_output = Some(format!("Error:\n{}{}", stdout, stderr));
-                }
+        app_state.log_command(command);
 
-                let _ = app.refresh_directory();
+        match ui_state.start_command(app_state, &stages, self.command_output_max_lines) {
+            Ok(()) => {
+                self.running_command = Some(command.to_string());
+                self.command_output = Some("(running…)".to_string());
             }
             Err(e) => {
+                self.running_command = None;
                 self.command_output = Some(format!("Failed: {}", e));
             }
         }
     }
 
-    fn handle_tab_completion(&mut self, app: &App) {
+    /// Mirror the running command's output when it changes, and record it once it finishes
+    fn sync_command_output(&mut self, ui_state: &UiState) {
+        if self.running_command.is_none() {
+            return;
+        }
+
+        let version = ui_state.command_output_version;
+        if ui_state.is_command_running() {
+            if version != self.command_output_version {
+                self.command_output_version = version;
+                self.command_output = Some(ui_state.command_output_text());
+            }
+            return;
+        }
+
+        let command = self.running_command.take().unwrap_or_default();
+        self.command_output_version = version;
+        let output = ui_state.command_output_text();
+        if ui_state.command_succeeded == Some(true) {
+            self.files_command_history.retain(|c| *c != command);
+            self.files_command_history.push_front(command);
+            self.files_command_history
+                .truncate(MAX_FILES_COMMAND_HISTORY);
+
+            self.command_output = Some(if ui_state.live_command_output.is_empty() {
+                format!("(no output){}", output)
+            } else {
+                output
+            });
+        } else {
+            self.command_output = Some(format!("Error:\n{}", output));
+        }
+    }
+
+    /// Horizontal menu of tab-completion candidates, scrolled to keep the current one visible
+    fn draw_completion_candidates(&self, ui: &mut Ui) {
+        const MAX_VISIBLE: usize = 10;
+
+        let current = self.tab_completion_index;
+        let start = (current + 1).saturating_sub(MAX_VISIBLE);
+        let end = (start + MAX_VISIBLE).min(self.tab_completions.len());
+
+        ui.horizontal_wrapped(|ui| {
+            ui.spacing_mut().item_spacing = egui::vec2(4.0, 4.0);
+            if start > 0 {
+                ui.label(RichText::new("…").color(theme::TEXT_MUTED).size(11.0));
+            }
+            for (idx, candidate) in self.tab_completions[start..end].iter().enumerate() {
+                let is_current = start + idx == current;
+                Frame::none()
+                    .fill(if is_current {
+                        theme::ACCENT
+                    } else {
+                        theme::BG_SECONDARY
+                    })
+                    .rounding(theme::ROUNDING / 2.0)
+                    .inner_margin(egui::Margin::symmetric(6.0, 2.0))
+                    .show(ui, |ui| {
+                        ui.label(
+                            RichText::new(candidate)
+                                .color(if is_current {
+                                    theme::BG_PRIMARY
+                                } else {
+                                    theme::TEXT_SECONDARY
+                                })
+                                .size(11.0)
+                                .monospace(),
+                        );
+                    });
+            }
+            if end < self.tab_completions.len() {
+                ui.label(
+                    RichText::new(format!("+{}", self.tab_completions.len() - end))
+                        .color(theme::TEXT_MUTED)
+                        .size(11.0),
+                );
+            }
+        });
+    }
+
+    fn handle_tab_completion(&mut self, app_state: &AppState) {
         let input = &self.files_command_input;
 
         // Find the last token (the part we're completing)
@@ -1155,10 +2882,10 @@ impl LauncherUI {
                 // Partial contains a path separator — resolve the directory
                 let dir_part = &partial[..=slash_pos];
                 let file_part = &partial[slash_pos + 1..];
-                let resolved = app.current_path.join(dir_part);
+                let resolved = app_state.current_path.join(dir_part);
                 (resolved, file_part.to_string())
             } else {
-                (app.current_path.clone(), partial.to_string())
+                (app_state.current_path.clone(), partial.to_string())
             };
 
             let prefix_lower = prefix.to_lowercase();
@@ -1215,22 +2942,32 @@ impl LauncherUI {
         }
     }
 
-    fn draw_results(&mut self, ui: &mut Ui, app: &mut App) {
+    fn draw_results(&mut self, ui: &mut Ui, ui_state: &mut UiState, app_state: &mut AppState) {
+        let do_scroll = self.take_scroll_request();
         let mut clicked_idx: Option<usize> = None;
         let mut reveal_idx: Option<usize> = None;
         let selected = self.selected_result;
 
-        let results_data: Vec<_> = app
-            .search_results
+        let results_data: Vec<_> = app_state
+            .search_state
+            .results()
             .iter()
             .enumerate()
             .map(|(idx, result)| {
-                let (type_label, path) = match &result.kind {
-                    SearchResultKind::File(p) => ("file", Some(p.clone())),
-                    SearchResultKind::RecentFile(p) => ("recent", Some(p.clone())),
-                    SearchResultKind::Application(_) => ("app", None),
-                    SearchResultKind::Command(_) => ("cmd", None),
-                    SearchResultKind::GrepResult { path, .. } => ("grep", Some(path.clone())),
+                let type_label = result.kind.label();
+                let path = match &result.kind {
+                    SearchResultKind::File(p) | SearchResultKind::RecentFile(p) => Some(p.clone()),
+                    SearchResultKind::GrepResult { path, .. } => Some(path.clone()),
+                    SearchResultKind::Application(_)
+                    | SearchResultKind::Command(_)
+                    | SearchResultKind::SshHost { .. }
+                    | SearchResultKind::Calculation(_) => None,
+                };
+                let actions: Vec<String> = match &result.kind {
+                    SearchResultKind::Application(app) => {
+                        app.actions.iter().map(|a| a.name.clone()).collect()
+                    }
+                    _ => Vec::new(),
                 };
                 (
                     idx,
@@ -1239,17 +2976,40 @@ impl LauncherUI {
                     result.description.clone(),
                     type_label,
                     path,
+                    actions,
                 )
             })
             .collect();
 
+        let count = results_data.len();
+        ui.label(
+            RichText::new(format!(
+                "{} result{} • ↓{}",
+                count,
+                if count == 1 { "" } else { "s" },
+                app_state.search_sort_mode.label()
+            ))
+            .color(theme::TEXT_MUTED)
+            .size(10.0),
+        )
+        .on_hover_text("Ctrl+S: change sort");
+
         ScrollArea::vertical()
             .id_salt("search_results_scroll")
             .max_height(300.0)
             .auto_shrink([false, false])
             .show(ui, |ui| {
-                for (idx, icon, name, description, type_text, path) in &results_data {
+                for (idx, icon, name, description, type_text, path, actions) in &results_data {
                     let is_selected = *idx == selected;
+                    let active_action = self
+                        .selected_action
+                        .filter(|(result, _)| is_selected && result == idx)
+                        .map(|(_, action)| action);
+                    let description = match active_action.and_then(|a| actions.get(a)) {
+                        Some(action) => format!("▸ {}", action),
+                        None => description.clone(),
+                    };
+                    let show_actions = is_selected && !actions.is_empty();
                     let bg_color = if is_selected {
                         theme::BG_SELECTED
                     } else {
@@ -1261,7 +3021,7 @@ impl LauncherUI {
                         .rounding(theme::ROUNDING / 2.0)
                         .inner_margin(egui::Margin::symmetric(theme::PADDING, 6.0))
                         .show(ui, |ui| {
-                            ui.set_min_height(ITEM_HEIGHT - 12.0);
+                            ui.set_min_height(self.item_height - 12.0);
                             ui.horizontal(|ui| {
                                 ui.label(
                                     RichText::new(icon)
@@ -1271,20 +3031,44 @@ impl LauncherUI {
                                 ui.add_space(theme::SPACING);
 
                                 ui.vertical(|ui| {
+                                    ui.horizontal(|ui| {
+                                        ui.label(
+                                            RichText::new(name)
+                                                .font(theme::result_name_font())
+                                                .color(if is_selected {
+                                                    theme::ACCENT
+                                                } else {
+                                                    theme::TEXT_PRIMARY
+                                                }),
+                                        );
+                                        if show_actions {
+                                            ui.label(
+                                                RichText::new("▸")
+                                                    .font(theme::result_desc_font())
+                                                    .color(theme::TEXT_MUTED),
+                                            )
+                                            .on_hover_text("Tab: cycle actions");
+                                        }
+                                    });
                                     ui.label(
-                                        RichText::new(name).font(theme::result_name_font()).color(
-                                            if is_selected {
-                                                theme::ACCENT
-                                            } else {
-                                                theme::TEXT_PRIMARY
-                                            },
-                                        ),
-                                    );
-                                    ui.label(
-                                        RichText::new(description)
+                                        RichText::new(&description)
                                             .font(theme::result_desc_font())
                                             .color(theme::TEXT_MUTED),
                                     );
+                                    if show_actions {
+                                        ui.horizontal_wrapped(|ui| {
+                                            for (i, action) in actions.iter().enumerate() {
+                                                let color = if active_action == Some(i) {
+                                                    theme::ACCENT
+                                                } else {
+                                                    theme::TEXT_MUTED
+                                                };
+                                                ui.label(
+                                                    RichText::new(action).size(10.0).color(color),
+                                                );
+                                            }
+                                        });
+                                    }
                                 });
 
                                 ui.with_layout(
@@ -1313,8 +3097,8 @@ impl LauncherUI {
                             });
                         });
 
-                    if is_selected && self.scroll_to_selected {
-                        response.response.scroll_to_me(Some(egui::Align::Center));
+                    if is_selected && do_scroll {
+                        ui.scroll_to_rect(response.response.rect, Some(egui::Align::Center));
                     }
 
                     let rect = response.response.rect;
@@ -1322,26 +3106,112 @@ impl LauncherUI {
                     if interact.clicked() {
                         clicked_idx = Some(*idx);
                     }
+                    let popup_id = ui.id().with("result_preview");
                     if interact.hovered() {
                         self.selected_result = *idx;
+                        if self.hovered_result_idx != Some(*idx) {
+                            self.hovered_result_idx = Some(*idx);
+                            self.hover_preview = app_state
+                                .search_state
+                                .get(*idx)
+                                .and_then(|r| result_hover_preview(&r.kind));
+                        }
+                        if let Some(preview) = &self.hover_preview {
+                            ui.memory_mut(|m| m.open_popup(popup_id));
+                            egui::popup_below_widget(
+                                ui,
+                                popup_id,
+                                &interact,
+                                egui::PopupCloseBehavior::IgnoreClicks,
+                                |ui| {
+                                    Frame::none()
+                                        .fill(theme::BG_SECONDARY)
+                                        .inner_margin(theme::SPACING)
+                                        .show(ui, |ui| {
+                                            ui.set_max_width(350.0);
+                                            ui.set_max_height(200.0);
+                                            ui.label(
+                                                RichText::new(preview)
+                                                    .color(theme::TEXT_SECONDARY)
+                                                    .size(10.0)
+                                                    .monospace(),
+                                            );
+                                        });
+                                },
+                            );
+                        }
+                    } else if self.hovered_result_idx == Some(*idx) {
+                        self.hovered_result_idx = None;
+                        self.hover_preview = None;
+                        ui.memory_mut(|m| m.close_popup());
                     }
                 }
-            });
 
-        self.scroll_to_selected = false;
+                // More results may still arrive below the ones shown so far
+                if app_state.search_state.is_streaming() {
+                    ui.add_space(theme::SPACING);
+                    ui.horizontal(|ui| {
+                        ui.add(egui::Spinner::new().size(12.0).color(theme::ACCENT));
+                        ui.label(
+                            RichText::new("Searching...")
+                                .color(theme::TEXT_MUTED)
+                                .size(11.0),
+                        );
+                    });
+                }
+            });
 
         if let Some(idx) = reveal_idx {
-            if let Some((_, _, _, _, _, Some(path))) = results_data.get(idx) {
-                let _ = app.reveal_in_folder(path);
+            if let Some((_, _, _, _, _, Some(path), _)) = results_data.get(idx) {
+                let _ = reveal_in_folder(path);
             }
         } else if let Some(idx) = clicked_idx {
-            let _ = app.execute_search_result(idx);
-            app.search_query.clear();
-            app.search_results.clear();
-            self.selected_result = 0;
+            self.run_search_result(ui_state, app_state, idx);
         }
     }
 
+    fn draw_min_length_hint(&mut self, ui: &mut Ui, min_length: usize) {
+        Frame::none()
+            .fill(theme::BG_SECONDARY)
+            .rounding(theme::ROUNDING)
+            .inner_margin(theme::PADDING)
+            .show(ui, |ui| {
+                ui.vertical_centered(|ui| {
+                    ui.add_space(theme::PADDING);
+                    ui.label(
+                        RichText::new(format!(
+                            "Type at least {} characters to search…",
+                            min_length
+                        ))
+                        .color(theme::TEXT_MUTED)
+                        .size(13.0),
+                    );
+                    ui.add_space(theme::PADDING);
+                });
+            });
+    }
+
+    /// Placeholder while a grep/find search is running and nothing has matched yet
+    fn draw_searching(&mut self, ui: &mut Ui, query: &str) {
+        Frame::none()
+            .fill(theme::BG_SECONDARY)
+            .rounding(theme::ROUNDING)
+            .inner_margin(theme::PADDING)
+            .show(ui, |ui| {
+                ui.vertical_centered(|ui| {
+                    ui.add_space(theme::PADDING);
+                    ui.add(egui::Spinner::new().size(20.0).color(theme::ACCENT));
+                    ui.add_space(theme::SPACING);
+                    ui.label(
+                        RichText::new(format!("Searching for \"{}\"…", query))
+                            .color(theme::TEXT_MUTED)
+                            .size(13.0),
+                    );
+                    ui.add_space(theme::PADDING);
+                });
+            });
+    }
+
     fn draw_no_results(&mut self, ui: &mut Ui, query: &str) {
         Frame::none()
             .fill(theme::BG_SECONDARY)
@@ -1366,14 +3236,43 @@ impl LauncherUI {
             });
     }
 
-    fn draw_recent_and_apps(&mut self, ui: &mut Ui, app: &mut App) {
-        let recent_count = app.recent_files.len().min(5);
-        let cmd_count = app.command_history.len().min(5);
+    fn draw_recent_and_apps(
+        &mut self,
+        ui: &mut Ui,
+        ui_state: &mut UiState,
+        app_state: &mut AppState,
+    ) {
+        let do_scroll = self.take_scroll_request();
+        let recent_count = app_state.recent_files.len().min(5);
+        let most_count = if ui_state.show_most_opened {
+            app_state.most_opened_files.len().min(5)
+        } else {
+            0
+        };
+        let cmd_count = app_state.command_history.len().min(5);
+
+        let recent_data: Vec<_> = app_state
+            .recent_files
+            .iter()
+            .take(5)
+            .enumerate()
+            .map(|(idx, recent)| {
+                let name = recent
+                    .path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| recent.path.to_string_lossy().to_string());
+                let path = recent.path.clone();
+                let is_dir = path.is_dir();
+                let exists = app_state.recent_file_exists(&path);
+                (idx, name, path, is_dir, exists)
+            })
+            .collect();
 
-        let recent_data: Vec<_> = app
-            .recent_files
+        let most_data: Vec<_> = app_state
+            .most_opened_files
             .iter()
-            .take(5)
+            .take(most_count)
             .enumerate()
             .map(|(idx, recent)| {
                 let name = recent
@@ -1383,11 +3282,14 @@ impl LauncherUI {
                     .unwrap_or_else(|| recent.path.to_string_lossy().to_string());
                 let path = recent.path.clone();
                 let is_dir = path.is_dir();
-                (idx, name, path, is_dir)
+                let exists = app_state.recent_file_exists(&path);
+                (idx, name, path, is_dir, recent.access_count, exists)
             })
             .collect();
+        let has_most_opened = !app_state.most_opened_files.is_empty();
+        let mut toggle_most_opened = false;
 
-        let cmd_data: Vec<_> = app
+        let cmd_data: Vec<_> = app_state
             .command_history
             .iter()
             .take(5)
@@ -1395,7 +3297,7 @@ impl LauncherUI {
             .map(|(idx, entry)| (idx, entry.command.clone(), entry.path.clone()))
             .collect();
 
-        let apps_data: Vec<_> = app
+        let apps_data: Vec<_> = app_state
             .applications
             .iter()
             .take(5)
@@ -1420,7 +3322,7 @@ impl LauncherUI {
                     );
                     ui.add_space(4.0);
 
-                    for (idx, name, path, is_dir) in &recent_data {
+                    for (idx, name, path, is_dir, exists) in &recent_data {
                         let is_selected = !self.search_focused && self.selected_recent == *idx;
                         let bg_color = if is_selected {
                             theme::BG_SELECTED
@@ -1437,20 +3339,12 @@ impl LauncherUI {
                                     let icon = if *is_dir { "📁" } else { "📄" };
                                     ui.label(RichText::new(icon).size(14.0));
                                     ui.add_space(theme::SPACING);
-                                    ui.label(
-                                        RichText::new(name)
-                                            .color(if is_selected {
-                                                theme::ACCENT
-                                            } else {
-                                                theme::TEXT_PRIMARY
-                                            })
-                                            .size(13.0),
-                                    );
+                                    draw_recent_name(ui, name, is_selected, *exists);
                                 });
                             });
 
-                        if is_selected && self.scroll_to_selected {
-                            response.response.scroll_to_me(Some(egui::Align::Center));
+                        if is_selected && do_scroll {
+                            ui.scroll_to_rect(response.response.rect, Some(egui::Align::Center));
                         }
 
                         if response.response.clicked() {
@@ -1464,6 +3358,70 @@ impl LauncherUI {
                     ui.add_space(theme::SPACING);
                 }
 
+                if has_most_opened {
+                    let arrow = if most_data.is_empty() { "▸" } else { "▾" };
+                    let header = ui.add(
+                        egui::Label::new(
+                            RichText::new(format!("{} Most Opened", arrow))
+                                .color(theme::TEXT_SECONDARY)
+                                .size(11.0),
+                        )
+                        .sense(egui::Sense::click()),
+                    );
+                    if header.clicked() {
+                        toggle_most_opened = true;
+                    }
+                    header.on_hover_text("Toggle (m)");
+                    ui.add_space(4.0);
+
+                    for (idx, name, path, is_dir, count, exists) in &most_data {
+                        let global_idx = recent_count + *idx;
+                        let is_selected =
+                            !self.search_focused && self.selected_recent == global_idx;
+                        let bg_color = if is_selected {
+                            theme::BG_SELECTED
+                        } else {
+                            theme::BG_PRIMARY
+                        };
+
+                        let response = Frame::none()
+                            .fill(bg_color)
+                            .rounding(theme::ROUNDING / 2.0)
+                            .inner_margin(egui::Margin::symmetric(theme::PADDING, 4.0))
+                            .show(ui, |ui| {
+                                ui.horizontal(|ui| {
+                                    let icon = if *is_dir { "📁" } else { "📄" };
+                                    ui.label(RichText::new(icon).size(14.0));
+                                    ui.add_space(theme::SPACING);
+                                    draw_recent_name(ui, name, is_selected, *exists);
+                                    ui.with_layout(
+                                        egui::Layout::right_to_left(egui::Align::Center),
+                                        |ui| {
+                                            ui.label(
+                                                RichText::new(format!("{}×", count))
+                                                    .color(theme::TEXT_MUTED)
+                                                    .size(10.0),
+                                            );
+                                        },
+                                    );
+                                });
+                            });
+
+                        if is_selected && do_scroll {
+                            ui.scroll_to_rect(response.response.rect, Some(egui::Align::Center));
+                        }
+
+                        if response.response.clicked() {
+                            clicked_recent = Some((path.clone(), *is_dir));
+                        }
+                        if response.response.hovered() {
+                            self.selected_recent = global_idx;
+                        }
+                    }
+
+                    ui.add_space(theme::SPACING);
+                }
+
                 if !cmd_data.is_empty() {
                     ui.label(
                         RichText::new("Recent Commands")
@@ -1473,7 +3431,7 @@ impl LauncherUI {
                     ui.add_space(4.0);
 
                     for (idx, command, path) in &cmd_data {
-                        let global_idx = recent_count + *idx;
+                        let global_idx = recent_count + most_count + *idx;
                         let is_selected =
                             !self.search_focused && self.selected_recent == global_idx;
                         let bg_color = if is_selected {
@@ -1510,8 +3468,8 @@ impl LauncherUI {
                                 });
                             });
 
-                        if is_selected && self.scroll_to_selected {
-                            response.response.scroll_to_me(Some(egui::Align::Center));
+                        if is_selected && do_scroll {
+                            ui.scroll_to_rect(response.response.rect, Some(egui::Align::Center));
                         }
 
                         if response.response.clicked() {
@@ -1533,7 +3491,7 @@ impl LauncherUI {
                 ui.add_space(4.0);
 
                 for (idx, name, desktop_app) in &apps_data {
-                    let global_idx = recent_count + cmd_count + *idx;
+                    let global_idx = recent_count + most_count + cmd_count + *idx;
                     let is_selected = !self.search_focused && self.selected_recent == global_idx;
                     let bg_color = if is_selected {
                         theme::BG_SELECTED
@@ -1561,8 +3519,8 @@ impl LauncherUI {
                             });
                         });
 
-                    if is_selected && self.scroll_to_selected {
-                        response.response.scroll_to_me(Some(egui::Align::Center));
+                    if is_selected && do_scroll {
+                        ui.scroll_to_rect(response.response.rect, Some(egui::Align::Center));
                     }
 
                     if response.response.clicked() {
@@ -1575,39 +3533,236 @@ impl LauncherUI {
 
                 ui.add_space(theme::PADDING);
                 ui.label(
-                    RichText::new("Esc: unfocus search | ↑↓: navigate | Enter: open/run")
-                        .color(theme::TEXT_MUTED)
-                        .size(10.0),
+                    RichText::new(
+                        "Esc: unfocus search | ↑↓: navigate | Enter: open/run | m: most opened",
+                    )
+                    .color(theme::TEXT_MUTED)
+                    .size(10.0),
                 );
             });
 
-        self.scroll_to_selected = false;
+        if toggle_most_opened {
+            ui_state.show_most_opened = !ui_state.show_most_opened;
+            self.selected_recent = 0;
+        }
 
         if let Some((path, is_dir)) = clicked_recent {
             if is_dir {
-                let _ = app.change_directory(path);
+                let _ = ui_state.change_directory(app_state, path);
             } else {
-                let _ = app.open_file(path);
+                let _ = ui_state.open_file(app_state, path);
             }
         }
         if let Some((cmd, path)) = clicked_cmd {
-            let _ = app.change_directory(path);
-            self.execute_command_sync(&cmd, app);
+            let _ = ui_state.change_directory(app_state, path);
+            self.execute_command(&cmd, ui_state, app_state);
         }
         if let Some(desktop_app) = clicked_app {
-            let _ = history::log_app_launch(&app.db_connection, &desktop_app.name, &desktop_app.path);
-            app.refresh_app_launch_history();
+            app_state.log_app_launch(&desktop_app);
             let _ = desktop_app.launch();
         }
     }
 
-    fn draw_clipboard_view(&mut self, ui: &mut Ui, app: &mut App) {
+    /// Clipboard entries shown in the Clipboard view, after the header filter
+    fn visible_clipboard<'a>(&self, app_state: &'a AppState) -> Vec<&'a ClipboardEntry> {
+        app_state
+            .clipboard_history
+            .iter()
+            .filter(|e| {
+                self.clipboard_type_filter
+                    .is_none_or(|t| e.content_type == t)
+            })
+            .collect()
+    }
+
+    fn clamp_clipboard_selection(&mut self, app_state: &AppState) {
+        let count = self.visible_clipboard(app_state).len();
+        if self.selected_clipboard > 0 && self.selected_clipboard >= count {
+            self.selected_clipboard = count.saturating_sub(1);
+        }
+    }
+
+    fn delete_selected_clipboard(&mut self, ui_state: &mut UiState, app_state: &mut AppState) {
+        for id in self.selected_clipboard_entries.drain() {
+            let _ = clipboard::delete_entry(&app_state.db_connection, id);
+        }
+        app_state.refresh_clipboard(&ui_state.clipboard_query);
+        self.clamp_clipboard_selection(app_state);
+    }
+
+    fn pin_selected_clipboard(&mut self, ui_state: &mut UiState, app_state: &mut AppState) {
+        for id in self.selected_clipboard_entries.drain() {
+            let _ = clipboard::toggle_pin(&app_state.db_connection, id);
+        }
+        app_state.refresh_clipboard(&ui_state.clipboard_query);
+    }
+
+    fn export_selected_clipboard(&mut self, ui_state: &mut UiState, app_state: &mut AppState) {
+        let entries: Vec<&ClipboardEntry> = app_state
+            .clipboard_history
+            .iter()
+            .filter(|e| self.selected_clipboard_entries.contains(&e.id))
+            .collect();
+        let count = entries.len();
+        match clipboard::export_entries(&entries) {
+            Ok(path) => ui_state.show_toast(
+                format!(
+                    "Exported {} entries to {}",
+                    count,
+                    fs::abbreviate_path(&path)
+                ),
+                ToastKind::Success,
+            ),
+            Err(e) => ui_state.set_error(format!("Export failed: {}", e)),
+        }
+        self.selected_clipboard_entries.clear();
+    }
+
+    /// "N selected — Delete | Pin | Export", shown while entries are multi-selected
+    fn draw_clipboard_selection_bar(
+        &mut self,
+        ui: &mut Ui,
+        ui_state: &mut UiState,
+        app_state: &mut AppState,
+    ) {
+        if self.selected_clipboard_entries.is_empty() {
+            return;
+        }
+
+        let (mut delete, mut pin, mut export) = (false, false, false);
+        Frame::none()
+            .fill(theme::BG_SECONDARY)
+            .rounding(theme::ROUNDING)
+            .inner_margin(egui::Margin::symmetric(theme::PADDING, theme::SPACING))
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(
+                        RichText::new(format!(
+                            "{} selected —",
+                            self.selected_clipboard_entries.len()
+                        ))
+                        .color(theme::ACCENT)
+                        .size(12.0),
+                    );
+                    let button = |ui: &mut Ui, label: &str| {
+                        ui.add(
+                            egui::Button::new(RichText::new(label).size(11.0))
+                                .frame(true)
+                                .rounding(theme::ROUNDING / 2.0),
+                        )
+                        .clicked()
+                    };
+                    delete = button(ui, "Delete");
+                    pin = button(ui, "Pin");
+                    export = button(ui, "Export");
+                });
+            });
+        ui.add_space(theme::SPACING);
+
+        if delete {
+            self.delete_selected_clipboard(ui_state, app_state);
+        } else if pin {
+            self.pin_selected_clipboard(ui_state, app_state);
+        } else if export {
+            self.export_selected_clipboard(ui_state, app_state);
+        }
+    }
+
+    /// "N entries | M pinned | Size: X | Oldest: Y days", from the loaded history
+    fn draw_clipboard_stats(&mut self, ui: &mut Ui, app_state: &AppState) {
+        let history = &app_state.clipboard_history;
+        let pinned = history.iter().filter(|e| e.pinned).count();
+        let size: usize = history.iter().map(|e| e.content.len()).sum();
+        let now = chrono::Utc::now();
+        let oldest_days = history
+            .iter()
+            .filter(|e| !e.pinned)
+            .map(|e| (now - e.created_at).num_days())
+            .max();
+
+        let mut text = format!(
+            "{} entries | {} pinned | Size: {}",
+            history.len(),
+            pinned,
+            format_size(size as u64)
+        );
+        if let Some(days) = oldest_days {
+            text.push_str(&format!(
+                " | Oldest: {} day{}",
+                days,
+                if days == 1 { "" } else { "s" }
+            ));
+        }
+
+        Frame::none().show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(RichText::new(text).color(theme::TEXT_MUTED).size(10.0));
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    let close = ui.add(
+                        egui::Button::new(RichText::new("×").color(theme::TEXT_MUTED).size(10.0))
+                            .frame(false),
+                    );
+                    if close.on_hover_text("Hide").clicked() {
+                        self.show_clipboard_stats = false;
+                    }
+                });
+            });
+        });
+        ui.add_space(theme::SPACING);
+    }
+
+    fn draw_clipboard_view(
+        &mut self,
+        ui: &mut Ui,
+        ui_state: &mut UiState,
+        app_state: &mut AppState,
+    ) {
         ui.horizontal(|ui| {
             ui.label(
                 RichText::new("Clipboard History")
                     .color(theme::TEXT_PRIMARY)
                     .size(16.0),
             );
+            ui.add_space(theme::SPACING);
+            let selected_text = match self.clipboard_type_filter {
+                Some(t) => format!("{} {}", content_type_icon(t), content_type_label(t)),
+                None => "All types".to_string(),
+            };
+            let previous_filter = self.clipboard_type_filter;
+            egui::ComboBox::from_id_salt("clipboard_type_filter")
+                .selected_text(RichText::new(selected_text).size(11.0))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.clipboard_type_filter, None, "All types");
+                    for &content_type in clipboard::CONTENT_TYPES {
+                        ui.selectable_value(
+                            &mut self.clipboard_type_filter,
+                            Some(content_type),
+                            format!(
+                                "{} {}",
+                                content_type_icon(content_type),
+                                content_type_label(content_type)
+                            ),
+                        );
+                    }
+                });
+            if self.clipboard_type_filter != previous_filter {
+                self.selected_clipboard = 0;
+            }
+
+            let filter = ui.add(
+                TextEdit::singleline(&mut ui_state.clipboard_query)
+                    .hint_text("Search… (/)")
+                    .desired_width(140.0)
+                    .font(egui::TextStyle::Small),
+            );
+            if std::mem::take(&mut self.focus_clipboard_filter) {
+                filter.request_focus();
+            }
+            self.clipboard_filter_focused = filter.has_focus();
+            if filter.changed() {
+                app_state.refresh_clipboard(&ui_state.clipboard_query);
+                self.selected_clipboard = 0;
+            }
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                 if ui
                     .add(
@@ -1617,24 +3772,56 @@ impl LauncherUI {
                     )
                     .clicked()
                 {
-                    let _ = clipboard::cleanup_expired(&app.db_connection);
-                    app.refresh_clipboard();
+                    let _ = clipboard::cleanup_expired(&app_state.db_connection);
+                    app_state.refresh_clipboard(&ui_state.clipboard_query);
+                }
+                if ui
+                    .add(
+                        egui::Button::new(RichText::new("Deduplicate").size(11.0))
+                            .frame(true)
+                            .rounding(theme::ROUNDING / 2.0),
+                    )
+                    .on_hover_text("Merge copies of the same text made within a few seconds")
+                    .clicked()
+                {
+                    match clipboard::deduplicate_clipboard(&app_state.db_connection) {
+                        Ok(0) => ui_state.show_toast("No duplicates found", ToastKind::Info),
+                        Ok(n) => ui_state.show_toast(
+                            format!("Removed {} duplicate{}", n, if n == 1 { "" } else { "s" }),
+                            ToastKind::Success,
+                        ),
+                        Err(e) => ui_state.set_error(format!("Deduplicate failed: {}", e)),
+                    }
+                    app_state.refresh_clipboard(&ui_state.clipboard_query);
+                    self.selected_clipboard = 0;
                 }
             });
         });
         ui.add_space(theme::SPACING);
+        if self.show_clipboard_stats {
+            self.draw_clipboard_stats(ui, app_state);
+        }
+        self.draw_clipboard_selection_bar(ui, ui_state, app_state);
 
         let mut action: Option<(i64, ClipboardAction)> = None;
         let selected = self.selected_clipboard;
-        let do_scroll = self.scroll_to_selected;
-        self.scroll_to_selected = false;
+        let do_scroll = self.take_scroll_request();
+
+        let entries = self.visible_clipboard(app_state);
+
+        // Moving the selection collapses the expanded entry
+        let selected_id = entries.get(selected).map(|e| e.id);
+        if self.expanded_clipboard_entry != selected_id {
+            self.expanded_clipboard_entry = None;
+        }
+        let expanded = self.expanded_clipboard_entry;
 
         ScrollArea::vertical()
             .id_salt("clipboard_scroll")
             .max_height(320.0)
             .auto_shrink([false, false])
             .show(ui, |ui| {
-                if app.clipboard_history.is_empty() {
+                if entries.is_empty() {
                     Frame::none()
                         .fill(theme::BG_SECONDARY)
                         .rounding(theme::ROUNDING)
@@ -1658,9 +3845,10 @@ impl LauncherUI {
                     return;
                 }
 
-                for (idx, entry) in app.clipboard_history.iter().enumerate() {
+                for (idx, entry) in entries.iter().enumerate() {
                     let is_selected = idx == selected;
-                    let bg_color = if is_selected {
+                    let is_multi_selected = self.selected_clipboard_entries.contains(&entry.id);
+                    let bg_color = if is_selected || is_multi_selected {
                         theme::BG_SELECTED
                     } else {
                         theme::BG_PRIMARY
@@ -1672,8 +3860,28 @@ impl LauncherUI {
                         .inner_margin(egui::Margin::symmetric(theme::PADDING, 6.0))
                         .show(ui, |ui| {
                             ui.horizontal(|ui| {
-                                let pin_icon = if entry.pinned { "📌" } else { "📄" };
+                                let pin_icon = if is_multi_selected {
+                                    "✔"
+                                } else if entry.pinned {
+                                    "📌"
+                                } else {
+                                    content_type_icon(&entry.content_type)
+                                };
                                 ui.label(RichText::new(pin_icon).size(14.0));
+
+                                if let Some((r, g, b)) =
+                                    clipboard::parse_hex_color(entry.content.trim())
+                                {
+                                    let (rect, _) = ui.allocate_exact_size(
+                                        egui::vec2(14.0, 14.0),
+                                        egui::Sense::hover(),
+                                    );
+                                    ui.painter().rect_filled(
+                                        rect,
+                                        3.0,
+                                        egui::Color32::from_rgb(r, g, b),
+                                    );
+                                }
                                 ui.add_space(theme::SPACING);
 
                                 let preview: String = entry
@@ -1738,6 +3946,20 @@ impl LauncherUI {
                                             "Pin (won't expire)"
                                         });
 
+                                        if entry.content_type != clipboard::CONTENT_TYPE_TEMPLATE {
+                                            let template_btn = ui.add(
+                                                egui::Button::new(RichText::new("🧩").size(12.0))
+                                                    .frame(false),
+                                            );
+                                            if template_btn.clicked() {
+                                                action = Some((
+                                                    entry.id,
+                                                    ClipboardAction::PinAsTemplate,
+                                                ));
+                                            }
+                                            template_btn.on_hover_text("Pin as template");
+                                        }
+
                                         let copy_btn = ui.add(
                                             egui::Button::new(RichText::new("📋").size(12.0))
                                                 .frame(false),
@@ -1749,6 +3971,10 @@ impl LauncherUI {
                                     },
                                 );
                             });
+
+                            if expanded == Some(entry.id) {
+                                draw_expanded_clipboard_entry(ui, entry);
+                            }
                         });
 
                     if is_selected && do_scroll {
@@ -1770,35 +3996,399 @@ impl LauncherUI {
         if let Some((id, action_type)) = action {
             match action_type {
                 ClipboardAction::Copy => {
-                    if let Some(entry) = app.clipboard_history.iter().find(|e| e.id == id) {
+                    if let Some(entry) = app_state.clipboard_history.iter().find(|e| e.id == id) {
                         let _ = clipboard::copy_to_clipboard(&entry.content);
                     }
                 }
                 ClipboardAction::TogglePin => {
-                    let _ = clipboard::toggle_pin(&app.db_connection, id);
-                    app.refresh_clipboard();
+                    let _ = clipboard::toggle_pin(&app_state.db_connection, id);
+                    app_state.refresh_clipboard(&ui_state.clipboard_query);
+                }
+                ClipboardAction::PinAsTemplate => {
+                    let _ = clipboard::pin_as_template(&app_state.db_connection, id);
+                    app_state.refresh_clipboard(&ui_state.clipboard_query);
                 }
                 ClipboardAction::Delete => {
-                    let _ = clipboard::delete_entry(&app.db_connection, id);
-                    app.refresh_clipboard();
-                    if self.selected_clipboard > 0
-                        && self.selected_clipboard >= app.clipboard_history.len()
-                    {
-                        self.selected_clipboard = app.clipboard_history.len().saturating_sub(1);
-                    }
+                    let _ = clipboard::delete_entry(&app_state.db_connection, id);
+                    app_state.refresh_clipboard(&ui_state.clipboard_query);
+                    self.clamp_clipboard_selection(app_state);
                 }
             }
         }
 
         ui.add_space(theme::SPACING);
         ui.label(
-            RichText::new("↑↓ jk: Navigate | Enter: Copy | p: Pin | d: Delete")
+            RichText::new(
+                "↑↓ jk: Navigate | Enter: Copy | Space: Select | e: Expand | p: Pin | P: Template | d: Delete",
+            )
                 .color(theme::TEXT_MUTED)
                 .size(10.0),
         );
     }
 }
 
+/// Bytes of a file read for its hover preview
+const HOVER_PREVIEW_BYTES: u64 = 500;
+
+/// What hovering a search result shows: the start of a file, an image's size, a
+/// directory's item count, or an application's description
+fn result_hover_preview(kind: &SearchResultKind) -> Option<String> {
+    let path = match kind {
+        SearchResultKind::File(path)
+        | SearchResultKind::RecentFile(path)
+        | SearchResultKind::GrepResult { path, .. } => path,
+        SearchResultKind::Application(desktop_app) => {
+            return desktop_app.description.clone();
+        }
+        _ => return None,
+    };
+
+    if path.is_dir() {
+        let count = std::fs::read_dir(path).ok()?.count();
+        return Some(format!(
+            "{} item{}",
+            count,
+            if count == 1 { "" } else { "s" }
+        ));
+    }
+
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    if matches!(
+        extension.as_str(),
+        "png" | "jpg" | "jpeg" | "gif" | "bmp" | "webp" | "ico" | "tiff"
+    ) {
+        let (width, height) = image::image_dimensions(path).ok()?;
+        return Some(format!("{} × {} pixels", width, height));
+    }
+
+    let mut bytes = Vec::new();
+    std::fs::File::open(path)
+        .ok()?
+        .take(HOVER_PREVIEW_BYTES)
+        .read_to_end(&mut bytes)
+        .ok()?;
+    if bytes.contains(&0) {
+        return Some(String::from("Binary file"));
+    }
+    Some(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Full entry text (scrolls past 10 lines) with a character / word / line count bar
+fn draw_expanded_clipboard_entry(ui: &mut Ui, entry: &ClipboardEntry) {
+    ui.add_space(theme::SPACING);
+    let line_height = ui.text_style_height(&egui::TextStyle::Monospace);
+    ScrollArea::vertical()
+        .id_salt(("clipboard_expanded", entry.id))
+        .max_height(line_height * 10.0 + theme::SPACING)
+        .show(ui, |ui| {
+            let mut text = entry.content.as_str();
+            ui.add(
+                TextEdit::multiline(&mut text)
+                    .interactive(false)
+                    .font(egui::TextStyle::Monospace)
+                    .desired_width(f32::INFINITY),
+            );
+        });
+
+    ui.add_space(theme::SPACING);
+    ui.label(
+        RichText::new(format!(
+            "{} chars • {} words • {} lines",
+            entry.content.chars().count(),
+            entry.content.split_whitespace().count(),
+            entry.content.lines().count()
+        ))
+        .color(theme::TEXT_MUTED)
+        .size(10.0),
+    );
+}
+
+fn content_type_icon(content_type: &str) -> &'static str {
+    match content_type {
+        clipboard::CONTENT_TYPE_PRIMARY => "🖱️",
+        clipboard::CONTENT_TYPE_URL => "🔗",
+        clipboard::CONTENT_TYPE_JSON => "📋",
+        clipboard::CONTENT_TYPE_EMAIL => "📧",
+        clipboard::CONTENT_TYPE_FILE_PATH => "📁",
+        clipboard::CONTENT_TYPE_COLOR => "🎨",
+        clipboard::CONTENT_TYPE_UUID => "🆔",
+        clipboard::CONTENT_TYPE_TEMPLATE => "🧩",
+        _ => "📄",
+    }
+}
+
+fn content_type_label(content_type: &str) -> &'static str {
+    match content_type {
+        clipboard::CONTENT_TYPE_PRIMARY => "Primary",
+        clipboard::CONTENT_TYPE_URL => "URL",
+        clipboard::CONTENT_TYPE_JSON => "JSON",
+        clipboard::CONTENT_TYPE_EMAIL => "Email",
+        clipboard::CONTENT_TYPE_FILE_PATH => "File path",
+        clipboard::CONTENT_TYPE_COLOR => "Color",
+        clipboard::CONTENT_TYPE_UUID => "UUID",
+        clipboard::CONTENT_TYPE_TEMPLATE => "Template",
+        _ => "Text",
+    }
+}
+
+/// Ctrl+<key> shortcuts handled by `apply_line_edit`
+const LINE_EDIT_KEYS: [Key; 5] = [Key::A, Key::E, Key::W, Key::U, Key::K];
+
+/// Apply a shell-style Ctrl+`key` edit to `text` with the cursor at char index `cursor`,
+/// returning the new cursor: A/E jump to start/end, W deletes the word before the cursor,
+/// U and K delete to the start and end of the line
+fn apply_line_edit(text: &mut String, cursor: usize, key: Key) -> usize {
+    let chars: Vec<char> = text.chars().collect();
+    let cursor = cursor.min(chars.len());
+    let byte_at = |idx: usize| text.char_indices().nth(idx).map_or(text.len(), |(b, _)| b);
+
+    match key {
+        Key::A => 0,
+        Key::E => chars.len(),
+        Key::W => {
+            let mut start = cursor;
+            while start > 0 && chars[start - 1].is_whitespace() {
+                start -= 1;
+            }
+            while start > 0 && !chars[start - 1].is_whitespace() {
+                start -= 1;
+            }
+            let range = byte_at(start)..byte_at(cursor);
+            text.replace_range(range, "");
+            start
+        }
+        Key::U => {
+            let end = byte_at(cursor);
+            text.replace_range(..end, "");
+            0
+        }
+        Key::K => {
+            let start = byte_at(cursor);
+            text.truncate(start);
+            cursor
+        }
+        _ => cursor,
+    }
+}
+
+/// The current status message, if any (red for errors, green otherwise), with the
+/// Ctrl / Alt / Shift indicator on the right
+fn draw_status_bar(ui: &mut Ui, ui_state: &UiState) {
+    let modifiers = ui.input(|i| i.modifiers);
+
+    ui.add_space(theme::SPACING);
+    ui.horizontal(|ui| {
+        let message = &ui_state.status_message;
+        if !message.is_empty() {
+            let color = if ui_state.status_is_error {
+                theme::ERROR
+            } else {
+                theme::ACCENT
+            };
+            ui.label(RichText::new(message).color(color).size(10.0));
+        }
+
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            for (name, held) in [
+                ("Shift", modifiers.shift),
+                ("Alt", modifiers.alt),
+                ("Ctrl", modifiers.ctrl),
+            ] {
+                draw_modifier_badge(ui, name, held);
+            }
+        });
+    });
+}
+
+fn draw_modifier_badge(ui: &mut Ui, name: &str, held: bool) {
+    let color = if held {
+        theme::ACCENT
+    } else {
+        theme::TEXT_MUTED
+    };
+    Frame::none()
+        .fill(if held {
+            theme::BG_SELECTED
+        } else {
+            theme::BG_SECONDARY
+        })
+        .rounding(theme::ROUNDING)
+        .inner_margin(egui::Margin::symmetric(6.0, 1.0))
+        .show(ui, |ui| {
+            ui.label(RichText::new(name).color(color).size(10.0).monospace());
+        });
+}
+
+/// Tab name with its shortcut in a pill, and what the tab is for
+fn draw_tab_tooltip(ui: &mut Ui, name: &str, shortcut: &str, description: &str) {
+    ui.horizontal(|ui| {
+        ui.label(
+            RichText::new(name)
+                .color(theme::TEXT_PRIMARY)
+                .size(12.0)
+                .strong(),
+        );
+        Frame::none()
+            .fill(theme::BG_SECONDARY)
+            .rounding(theme::ROUNDING)
+            .inner_margin(egui::Margin::symmetric(6.0, 2.0))
+            .show(ui, |ui| {
+                ui.label(
+                    RichText::new(shortcut)
+                        .color(theme::ACCENT)
+                        .size(11.0)
+                        .monospace(),
+                );
+            });
+    });
+    ui.label(
+        RichText::new(description)
+            .color(theme::TEXT_MUTED)
+            .size(11.0),
+    );
+}
+
+/// Shown in place of the file list while a directory is being read
+fn draw_loading_directory(ui: &mut Ui) {
+    Frame::none()
+        .fill(theme::BG_SECONDARY)
+        .rounding(theme::ROUNDING)
+        .inner_margin(theme::PADDING)
+        .show(ui, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.add_space(theme::PADDING);
+                ui.add(egui::Spinner::new().size(20.0).color(theme::ACCENT));
+                ui.add_space(theme::SPACING);
+                ui.label(
+                    RichText::new("Loading…")
+                        .color(theme::TEXT_MUTED)
+                        .size(13.0),
+                );
+                ui.add_space(theme::PADDING);
+            });
+        });
+}
+
+/// Not a row of the file list, so selection indices are unaffected
+fn draw_directory_errors(ui: &mut Ui, errors: &[String]) {
+    let count = errors.len();
+    let label = format!(
+        "⚠ {} {} inaccessible",
+        count,
+        if count == 1 { "entry" } else { "entries" }
+    );
+    ui.label(RichText::new(label).color(theme::TEXT_MUTED).size(10.0))
+        .on_hover_text(errors.join("\n"));
+    ui.add_space(theme::SPACING);
+}
+
+/// One line for bug reports, e.g. "filecast 0.2.1 (2026-01-31, rustc 1.85.0, x86_64-unknown-linux-gnu)"
+fn version_info() -> String {
+    format!(
+        "filecast {} ({}, {}, {})",
+        env!("CARGO_PKG_VERSION"),
+        env!("FILECAST_BUILD_DATE"),
+        env!("RUSTC_VERSION"),
+        env!("TARGET")
+    )
+}
+
+fn draw_output_header(ui: &mut Ui, output: &str) {
+    let lines = output.lines().count();
+    ui.label(
+        RichText::new(format!(
+            "Output ({} line{})",
+            lines,
+            if lines == 1 { "" } else { "s" }
+        ))
+        .color(theme::TEXT_SECONDARY)
+        .size(11.0),
+    );
+}
+
+/// Recent file name, muted and struck through when the file no longer exists
+fn draw_recent_name(ui: &mut Ui, name: &str, is_selected: bool, exists: bool) {
+    let color = if !exists {
+        theme::TEXT_MUTED
+    } else if is_selected {
+        theme::ACCENT
+    } else {
+        theme::TEXT_PRIMARY
+    };
+
+    let label = ui.label(RichText::new(name).color(color).size(13.0));
+    if !exists {
+        let rect = label.rect;
+        ui.painter().line_segment(
+            [rect.left_center(), rect.right_center()],
+            egui::Stroke::new(1.0, theme::TEXT_MUTED),
+        );
+        label.on_hover_text("File no longer exists");
+    }
+}
+
+/// Counts for the Files view footer, tied to the `file_list_version` they were taken at
+struct FilesStats {
+    version: u64,
+    is_filtering: bool,
+    files: usize,
+    dirs: usize,
+    total_size: u64,
+    /// Entries in the unfiltered directory listing, for "Filtered: x of y"
+    total_entries: usize,
+}
+
+impl FilesStats {
+    fn compute(ui_state: &UiState, app_state: &AppState, version: u64) -> Self {
+        let mut stats = FilesStats {
+            version,
+            is_filtering: ui_state.is_filtering,
+            files: 0,
+            dirs: 0,
+            total_size: 0,
+            total_entries: app_state
+                .file_list
+                .iter()
+                .filter(|e| e.name != "..")
+                .count(),
+        };
+        for entry in ui_state
+            .get_display_list(app_state)
+            .iter()
+            .filter(|e| e.name != "..")
+        {
+            if entry.is_dir {
+                stats.dirs += 1;
+            } else {
+                stats.files += 1;
+                stats.total_size += entry.size;
+            }
+        }
+        stats
+    }
+
+    /// e.g. "127 files, 15 dirs, 2.3G | Filtered: 12 of 142"
+    fn summary(&self) -> String {
+        let mut text = format!(
+            "{} files, {} dirs, {}",
+            self.files,
+            self.dirs,
+            format_size(self.total_size)
+        );
+        if self.is_filtering {
+            text.push_str(&format!(
+                " | Filtered: {} of {}",
+                self.files + self.dirs,
+                self.total_entries
+            ));
+        }
+        text
+    }
+}
+
 fn format_size(size: u64) -> String {
     const K: u64 = 1024;
     const M: u64 = K * 1024;
@@ -4,12 +4,14 @@ pub const BG_PRIMARY: Color32 = Color32::from_rgb(30, 30, 30);
 pub const BG_SECONDARY: Color32 = Color32::from_rgb(40, 40, 40);
 pub const BG_HOVER: Color32 = Color32::from_rgb(50, 50, 50);
 pub const BG_SELECTED: Color32 = Color32::from_rgb(60, 80, 60);
+pub const BG_VISUAL: Color32 = Color32::from_rgb(50, 60, 90);
 
 pub const TEXT_PRIMARY: Color32 = Color32::from_rgb(220, 220, 220);
 pub const TEXT_SECONDARY: Color32 = Color32::from_rgb(150, 150, 150);
 pub const TEXT_MUTED: Color32 = Color32::from_rgb(100, 100, 100);
 
 pub const ACCENT: Color32 = Color32::from_rgb(100, 200, 100);
+pub const ERROR: Color32 = Color32::from_rgb(220, 90, 90);
 
 pub const BORDER: Color32 = Color32::from_rgb(60, 60, 60);
 